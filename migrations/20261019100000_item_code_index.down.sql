@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP INDEX item_objects_item_code_idx;
@@ -0,0 +1,2 @@
+-- Add up migration script here
+CREATE INDEX item_objects_item_code_idx ON item_objects (item_code);
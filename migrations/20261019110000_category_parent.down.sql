@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX category_parent_id_idx;
+ALTER TABLE category DROP COLUMN parent_id;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TABLE category
+    ADD COLUMN parent_id uuid,
+    ADD CONSTRAINT fk_parent
+        FOREIGN KEY(parent_id)
+            REFERENCES category(id);
+
+CREATE INDEX category_parent_id_idx ON category (parent_id);
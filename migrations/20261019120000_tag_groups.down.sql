@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE tag
+    DROP COLUMN color,
+    DROP COLUMN group_id;
+DROP TABLE tag_group;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+CREATE TABLE tag_group (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    name text NOT NULL
+);
+
+ALTER TABLE tag
+    ADD COLUMN group_id uuid,
+    ADD COLUMN color text NOT NULL DEFAULT '#94a3b8',
+    ADD CONSTRAINT fk_tag_group
+        FOREIGN KEY(group_id)
+            REFERENCES tag_group(id)
+                ON DELETE SET NULL,
+    ADD CONSTRAINT color_hex
+        CHECK (color ~ '^#[0-9a-fA-F]{6}$');
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE item DROP COLUMN attributes;
+DROP TABLE category_attribute;
+DROP TYPE attribute_kind;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TYPE attribute_kind AS ENUM ('text', 'number', 'enum', 'boolean');
+
+CREATE TABLE category_attribute (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    category_id uuid NOT NULL,
+    name text NOT NULL,
+    kind attribute_kind NOT NULL,
+    unit text,
+    options text[] NOT NULL DEFAULT '{}',
+    CONSTRAINT fk_category
+        FOREIGN KEY(category_id)
+            REFERENCES category(id)
+                ON DELETE CASCADE
+);
+
+ALTER TABLE item ADD COLUMN attributes jsonb NOT NULL DEFAULT '{}';
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE item_attachment (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    item_id uuid NOT NULL,
+    file_name text NOT NULL,
+    content_type text NOT NULL,
+    size bigint NOT NULL,
+    stored_name text NOT NULL,
+    thumbnail_name text,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_item
+        FOREIGN KEY(item_id)
+            REFERENCES item(id)
+                ON DELETE CASCADE
+);
+
+CREATE INDEX item_attachment_item_id_idx ON item_attachment (item_id);
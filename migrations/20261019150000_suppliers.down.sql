@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE item_objects DROP COLUMN purchase_order_line_id;
+DROP TABLE purchase_order_line;
+DROP TABLE purchase_order;
+DROP TABLE supplier;
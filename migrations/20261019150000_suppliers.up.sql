@@ -0,0 +1,38 @@
+-- Add up migration script here
+CREATE TABLE supplier (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    name text NOT NULL,
+    contact text
+);
+
+CREATE TABLE purchase_order (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    supplier_id uuid NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_supplier
+        FOREIGN KEY(supplier_id)
+            REFERENCES supplier(id)
+);
+
+CREATE TABLE purchase_order_line (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    order_id uuid NOT NULL,
+    item_id uuid NOT NULL,
+    quantity integer NOT NULL CHECK (quantity > 0),
+    received_quantity integer NOT NULL DEFAULT 0 CHECK (received_quantity <= quantity),
+    CONSTRAINT fk_order
+        FOREIGN KEY(order_id)
+            REFERENCES purchase_order(id)
+                ON DELETE CASCADE,
+    CONSTRAINT fk_item
+        FOREIGN KEY(item_id)
+            REFERENCES item(id)
+                ON DELETE CASCADE
+);
+
+ALTER TABLE item_objects
+    ADD COLUMN purchase_order_line_id uuid,
+    ADD CONSTRAINT fk_purchase_order_line
+        FOREIGN KEY(purchase_order_line_id)
+            REFERENCES purchase_order_line(id)
+                ON DELETE SET NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE object_transfer;
+ALTER TABLE item_objects DROP COLUMN location_id;
+DROP TABLE location;
@@ -0,0 +1,36 @@
+-- Add up migration script here
+CREATE TABLE location (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    name text NOT NULL UNIQUE
+);
+
+ALTER TABLE item_objects
+    ADD COLUMN location_id uuid,
+    ADD CONSTRAINT fk_location
+        FOREIGN KEY(location_id)
+            REFERENCES location(id)
+                ON DELETE SET NULL;
+
+CREATE INDEX item_objects_location_id_idx ON item_objects (location_id);
+
+CREATE TABLE object_transfer (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    object_id uuid NOT NULL,
+    from_location_id uuid,
+    to_location_id uuid,
+    transferred_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_object
+        FOREIGN KEY(object_id)
+            REFERENCES item_objects(id)
+                ON DELETE CASCADE,
+    CONSTRAINT fk_from_location
+        FOREIGN KEY(from_location_id)
+            REFERENCES location(id)
+                ON DELETE SET NULL,
+    CONSTRAINT fk_to_location
+        FOREIGN KEY(to_location_id)
+            REFERENCES location(id)
+                ON DELETE SET NULL
+);
+
+CREATE INDEX object_transfer_object_id_idx ON object_transfer (object_id);
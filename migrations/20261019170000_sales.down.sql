@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE sale_line;
+DROP TABLE sale;
+ALTER TABLE item_objects DROP COLUMN sold_at;
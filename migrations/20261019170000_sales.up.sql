@@ -0,0 +1,27 @@
+-- Add up migration script here
+ALTER TABLE item_objects ADD COLUMN sold_at timestamptz;
+
+CREATE TABLE sale (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+-- Item name and code are copied, so that receipts stay intact after the item is removed
+CREATE TABLE sale_line (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    sale_id uuid NOT NULL,
+    object_id uuid UNIQUE,
+    item_name text NOT NULL,
+    item_code text,
+    price bigint NOT NULL CHECK (price >= 0),
+    CONSTRAINT fk_sale
+        FOREIGN KEY(sale_id)
+            REFERENCES sale(id)
+                ON DELETE CASCADE,
+    CONSTRAINT fk_object
+        FOREIGN KEY(object_id)
+            REFERENCES item_objects(id)
+                ON DELETE SET NULL
+);
+
+CREATE INDEX sale_created_at_idx ON sale (created_at);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE stock_take_scan;
+DROP TABLE stock_take;
@@ -0,0 +1,30 @@
+-- Add up migration script here
+CREATE TABLE stock_take (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    category_id uuid,
+    location_id uuid,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    approved_at timestamptz,
+    CHECK (category_id IS NOT NULL OR location_id IS NOT NULL),
+    CONSTRAINT fk_category
+        FOREIGN KEY(category_id)
+            REFERENCES category(id)
+                ON DELETE CASCADE,
+    CONSTRAINT fk_location
+        FOREIGN KEY(location_id)
+            REFERENCES location(id)
+                ON DELETE CASCADE
+);
+
+CREATE TABLE stock_take_scan (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    stock_take_id uuid NOT NULL,
+    item_code text NOT NULL,
+    scanned_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT fk_stock_take
+        FOREIGN KEY(stock_take_id)
+            REFERENCES stock_take(id)
+                ON DELETE CASCADE
+);
+
+CREATE INDEX stock_take_scan_stock_take_id_idx ON stock_take_scan (stock_take_id);
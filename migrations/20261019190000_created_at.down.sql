@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE item_objects DROP COLUMN created_at;
+ALTER TABLE item DROP COLUMN created_at;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TABLE item ADD COLUMN created_at timestamptz NOT NULL DEFAULT now();
+ALTER TABLE item_objects ADD COLUMN created_at timestamptz NOT NULL DEFAULT now();
+
+CREATE INDEX item_created_at_idx ON item (created_at);
+CREATE INDEX item_objects_created_at_idx ON item_objects (created_at);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE category_slug_history;
+ALTER TABLE category DROP COLUMN slug;
+DROP FUNCTION slugify;
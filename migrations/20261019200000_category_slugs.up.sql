@@ -0,0 +1,51 @@
+-- Add up migration script here
+CREATE FUNCTION slugify(value text) RETURNS text
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT COALESCE(
+        NULLIF(
+            trim(BOTH '-' FROM regexp_replace(
+                translate(
+                    replace(replace(replace(replace(replace(replace(replace(replace(replace(replace(replace(
+                        lower(value),
+                        'щ', 'shch'), 'ж', 'zh'), 'х', 'kh'), 'ц', 'ts'), 'ч', 'ch'), 'ш', 'sh'),
+                        'є', 'ie'), 'ю', 'iu'), 'я', 'ia'), 'ї', 'i'), 'й', 'i'),
+                    'абвгґдезиіклмнопрстуфыэёьъ''’',
+                    'abvhgdezyiklmnoprstufyee'
+                ),
+                '[^a-z0-9]+', '-', 'g'
+            )),
+            ''
+        ),
+        'category'
+    )
+$$;
+
+ALTER TABLE category ADD COLUMN slug text;
+
+-- Duplicate names of the existing categories get numbered slugs
+UPDATE category
+SET slug = numbered.slug
+FROM (
+    SELECT
+        id,
+        CASE row_number() OVER (PARTITION BY slugify(name) ORDER BY id)
+            WHEN 1 THEN slugify(name)
+            ELSE slugify(name) || '-' || row_number() OVER (PARTITION BY slugify(name) ORDER BY id)
+        END AS slug
+    FROM category
+) AS numbered
+WHERE category.id = numbered.id;
+
+ALTER TABLE category
+    ALTER COLUMN slug SET NOT NULL,
+    ADD CONSTRAINT category_slug_unique UNIQUE (slug);
+
+-- Former slugs of renamed categories, so that old links still resolve
+CREATE TABLE category_slug_history (
+    slug text PRIMARY KEY,
+    category_id uuid NOT NULL,
+    CONSTRAINT fk_category
+        FOREIGN KEY(category_id)
+            REFERENCES category(id)
+                ON DELETE CASCADE
+);
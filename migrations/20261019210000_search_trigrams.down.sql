@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX item_objects_item_code_trgm_idx;
+DROP INDEX tag_name_trgm_idx;
+DROP INDEX item_name_trgm_idx;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX item_name_trgm_idx ON item USING GIN (name gin_trgm_ops);
+CREATE INDEX tag_name_trgm_idx ON tag USING GIN (name gin_trgm_ops);
+CREATE INDEX item_objects_item_code_trgm_idx ON item_objects USING GIN (item_code gin_trgm_ops);
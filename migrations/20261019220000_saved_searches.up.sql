@@ -0,0 +1,13 @@
+-- Add up migration script here
+-- Users are anonymous, identified by a cookie issued to their browser
+CREATE TABLE saved_search (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    user_id uuid NOT NULL,
+    name text NOT NULL,
+    href text NOT NULL,
+    is_default boolean NOT NULL DEFAULT false,
+    UNIQUE (user_id, name)
+);
+
+-- At most one default search per user
+CREATE UNIQUE INDEX saved_search_default_idx ON saved_search (user_id) WHERE is_default;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TYPE api_token_scope AS ENUM ('read', 'objects_write', 'admin');
+
+-- Long-lived tokens of the machine clients, such as scripts and scanning devices
+CREATE TABLE api_token (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    name text NOT NULL,
+    -- SHA-256 of the secret, which itself is only shown once, when the token is created
+    secret_hash text NOT NULL UNIQUE,
+    scope api_token_scope NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    last_used_at timestamptz
+);
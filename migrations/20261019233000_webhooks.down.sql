@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE webhook_delivery;
+DROP TYPE webhook_delivery_status;
+DROP TABLE webhook;
+DROP TYPE webhook_event_type;
@@ -0,0 +1,43 @@
+-- Add up migration script here
+CREATE TYPE webhook_event_type AS ENUM (
+    'ping',
+    'category.created',
+    'category.updated',
+    'category.deleted',
+    'tag.created',
+    'tag.updated',
+    'tag.deleted',
+    'item.created',
+    'item.updated',
+    'item.deleted',
+    'stock.changed'
+);
+
+CREATE TABLE webhook (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    url text NOT NULL CHECK (url ~ '^https?://'),
+    event_types webhook_event_type[] NOT NULL,
+    -- Key of the HMAC signatures of the deliveries
+    secret text NOT NULL CHECK (secret <> ''),
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'delivered', 'failed');
+
+-- Events are queued here together with the changes, and kept as the delivery log
+CREATE TABLE webhook_delivery (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    webhook_id uuid NOT NULL REFERENCES webhook (id) ON DELETE CASCADE,
+    event_type webhook_event_type NOT NULL,
+    payload jsonb NOT NULL,
+    status webhook_delivery_status NOT NULL DEFAULT 'pending',
+    attempts integer NOT NULL DEFAULT 0,
+    next_attempt_at timestamptz NOT NULL DEFAULT now(),
+    last_status_code integer,
+    last_error text,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    delivered_at timestamptz
+);
+
+CREATE INDEX webhook_delivery_due_idx ON webhook_delivery (next_attempt_at) WHERE status = 'pending';
+CREATE INDEX webhook_delivery_webhook_idx ON webhook_delivery (webhook_id, created_at);
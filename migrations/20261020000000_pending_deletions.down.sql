@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP FUNCTION removal_pending(jsonb);
+DROP FUNCTION removal_pending(text, uuid);
+DROP TABLE pending_deletion;
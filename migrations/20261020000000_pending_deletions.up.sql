@@ -0,0 +1,39 @@
+-- Add up migration script here
+-- Removals made by the remove server functions wait here, so that they can be undone
+CREATE TABLE pending_deletion (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    deletion jsonb NOT NULL,
+    delete_at timestamptz NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX pending_deletion_due_idx ON pending_deletion (delete_at);
+
+-- Whether the entity waits for its removal, such entities are hidden and can't be changed, until the removal is
+-- carried out or undone. The kind is the one of the removal, e.g. 'tag' or 'supplier', objects of a removed item
+-- wait with it.
+CREATE FUNCTION removal_pending(kind text, entity_id uuid) RETURNS boolean AS $$
+    SELECT
+        EXISTS (
+            SELECT 1
+            FROM pending_deletion, jsonb_each(pending_deletion.deletion) AS field
+            WHERE pending_deletion.deletion->>'kind' IN (kind, kind || 's') AND field.value @> to_jsonb(entity_id)
+        )
+        OR kind = 'item_object' AND EXISTS (
+            SELECT 1
+            FROM item_objects, pending_deletion, jsonb_each(pending_deletion.deletion) AS field
+            WHERE
+                item_objects.id = entity_id
+                AND pending_deletion.deletion->>'kind' IN ('item', 'items')
+                AND field.value @> to_jsonb(item_objects.item_id)
+        )
+$$ LANGUAGE sql STABLE;
+
+-- Whether the removal waits, for removals of links like an item tag, which have no id of their own.
+CREATE FUNCTION removal_pending(deletion jsonb) RETURNS boolean AS $$
+    SELECT EXISTS (
+        SELECT 1
+        FROM pending_deletion
+        WHERE pending_deletion.deletion @> $1
+    )
+$$ LANGUAGE sql STABLE;
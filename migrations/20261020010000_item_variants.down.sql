@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE item_objects DROP COLUMN variant_id;
+DROP TABLE item_variant;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+-- Variants of a product, e.g. its sizes and colours, each with its own objects
+CREATE TABLE item_variant (
+    id uuid DEFAULT gen_random_uuid() PRIMARY KEY,
+    item_id uuid NOT NULL,
+    name text NOT NULL,
+    attributes jsonb NOT NULL DEFAULT '{}',
+    UNIQUE (item_id, name),
+    -- Lets the objects reference the variant together with their own item
+    UNIQUE (id, item_id),
+    CONSTRAINT fk_item
+        FOREIGN KEY(item_id)
+            REFERENCES item(id)
+                ON DELETE CASCADE
+);
+
+ALTER TABLE item_objects ADD COLUMN variant_id uuid;
+
+-- A variant with objects can't be removed, and its objects always belong to its item.
+-- Deferred, so that a merge of items can move the variants and the objects one after another.
+ALTER TABLE item_objects ADD CONSTRAINT fk_variant
+    FOREIGN KEY(variant_id, item_id)
+        REFERENCES item_variant(id, item_id)
+            DEFERRABLE INITIALLY DEFERRED;
+
+CREATE INDEX item_objects_variant_id_idx ON item_objects (variant_id);
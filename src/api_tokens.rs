@@ -0,0 +1,151 @@
+use std::{env, fs::{OpenOptions, Permissions}, io::Write, os::unix::fs::{OpenOptionsExt, PermissionsExt}, path::{Path, PathBuf}};
+
+use actix_web::{FromRequest, cookie::{Cookie, SameSite}, http::header::{AUTHORIZATION, HeaderValue, SET_COOKIE}};
+use futures::future::LocalBoxFuture;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::{data::api_tokens::{ApiToken, ApiTokenScope}, db::{Repository, DbError, api_tokens::ApiTokenDB}};
+
+/// Makes the secrets recognizable, e.g. by secret scanners.
+const SECRET_PREFIX: &str = "wdb_";
+/// Cookie of a browser signed in with a token, it holds the token's secret.
+const SESSION_COOKIE: &str = "web_db_session";
+/// File of the first admin token's secret, unless `ADMIN_TOKEN_FILE` names another one.
+const DEFAULT_ADMIN_TOKEN_FILE: &str = "admin_token";
+
+/// A new random secret of a token.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{SECRET_PREFIX}{}", hex::encode(bytes))
+}
+
+/// The secrets are random enough, so a plain hash of them is safe to store.
+pub fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Who makes the request: a machine client with a token in the `Authorization: Bearer` header,
+/// a browser signed in with a token, or an anonymous browser.
+///
+/// Anonymous browsers can only read, the others can do what their token's scope allows.
+/// A request with an unknown token in the header is refused right away.
+pub enum ApiAccess {
+    Anonymous,
+    Token(ApiToken),
+}
+
+impl ApiAccess {
+    pub fn require(&self, scope: ApiTokenScope) -> Result<(), DbError> {
+        match self {
+            ApiAccess::Anonymous if scope != ApiTokenScope::Read => Err(DbError::SignInRequired),
+            ApiAccess::Token(token) if !token.scope.allows(scope) => Err(DbError::Forbidden),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Signs the browser in with the token's secret, or out, if there is none.
+///
+/// The cookie isn't sent with the requests from other sites, so they can't act on the browser's behalf.
+/// Must be called during a server function, where the response options are provided.
+pub fn set_session(secret: Option<&str>) {
+    let mut cookie = Cookie::build(SESSION_COOKIE, secret.unwrap_or_default().to_string())
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .permanent()
+        .finish();
+    if secret.is_none() {
+        cookie.make_removal();
+    }
+
+    let response = leptos::expect_context::<leptos_actix::ResponseOptions>();
+    // PANIC: the secrets are prefixed hex, which is always a valid header value
+    response.append_header(SET_COOKIE, HeaderValue::from_str(&cookie.to_string()).unwrap());
+}
+
+/// Writes the secret to a file, which only the server's user can read, an existing file is overwritten.
+fn write_secret_file(path: &Path, secret: &str) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // The mode only applies to a new file
+    file.set_permissions(Permissions::from_mode(0o600))?;
+    writeln!(file, "{secret}")
+}
+
+/// Makes sure there is a token to sign in with.
+///
+/// The secret of the first one is written to the file from `ADMIN_TOKEN_FILE`, rather than to the output,
+/// which may end up in shared logs.
+pub async fn ensure_admin_token(db: &Repository) -> Result<(), DbError> {
+    let tokens = db.get_api_tokens().await?;
+    if tokens.iter().any(|token| token.scope == ApiTokenScope::Admin) {
+        return Ok(());
+    }
+
+    let secret = generate_secret();
+    let path = PathBuf::from(env::var("ADMIN_TOKEN_FILE").unwrap_or(DEFAULT_ADMIN_TOKEN_FILE.to_string()));
+    write_secret_file(&path, &secret).expect("Could not write the admin token file");
+    db.add_api_token("Початковий адміністратор", ApiTokenScope::Admin, &hash_secret(&secret)).await?;
+    println!("created an admin API token, its secret is in {}, sign in with it and revoke it once there are others", path.display());
+    Ok(())
+}
+
+impl FromRequest for ApiAccess {
+    type Error = DbError;
+    type Future = LocalBoxFuture<'static, Result<ApiAccess, DbError>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let secret = req.headers().get(AUTHORIZATION).map(|header| {
+            header.to_str().ok()
+                .and_then(|header| header.strip_prefix("Bearer "))
+                .map(|secret| secret.trim().to_string())
+        });
+        let session = req.cookie(SESSION_COOKIE).map(|cookie| cookie.value().to_string());
+        let db = req.app_data::<Repository>().expect("Repository was not found").clone();
+
+        Box::pin(async move {
+            let secret = match (secret, session) {
+                (Some(secret), _) => secret.ok_or(DbError::Unauthorized)?,
+                (None, Some(session)) => {
+                    // A session of a revoked token just stops being one, the browser can still read
+                    let token = db.use_api_token(&hash_secret(&session)).await?;
+                    return Ok(token.map_or(ApiAccess::Anonymous, ApiAccess::Token));
+                },
+                (None, None) => return Ok(ApiAccess::Anonymous),
+            };
+
+            db.use_api_token(&hash_secret(&secret)).await?
+                .map(ApiAccess::Token)
+                .ok_or(DbError::Unauthorized)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn token(scope: ApiTokenScope) -> ApiAccess {
+        ApiAccess::Token(ApiToken { id: Uuid::new_v4().into(), name: "test".to_string(), scope, created_at: Utc::now(), last_used_at: None })
+    }
+
+    #[test]
+    fn anonymous_browsers_only_read() {
+        assert!(ApiAccess::Anonymous.require(ApiTokenScope::Read).is_ok());
+        assert!(matches!(ApiAccess::Anonymous.require(ApiTokenScope::ObjectsWrite), Err(DbError::SignInRequired)));
+        assert!(matches!(ApiAccess::Anonymous.require(ApiTokenScope::Admin), Err(DbError::SignInRequired)));
+    }
+
+    #[test]
+    fn tokens_allow_their_scope_and_below() {
+        assert!(token(ApiTokenScope::ObjectsWrite).require(ApiTokenScope::Read).is_ok());
+        assert!(token(ApiTokenScope::ObjectsWrite).require(ApiTokenScope::ObjectsWrite).is_ok());
+        assert!(matches!(token(ApiTokenScope::ObjectsWrite).require(ApiTokenScope::Admin), Err(DbError::Forbidden)));
+        assert!(token(ApiTokenScope::Admin).require(ApiTokenScope::Admin).is_ok());
+    }
+}
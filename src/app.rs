@@ -1,13 +1,21 @@
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
-use crate::ui::{categories::CategoriesBlock, ui_blocks::{TopBlock, MainBlock}, state::AdminState, search::SearchBlock};
+use crate::server_funcs::{categories::resolve_category_slug, saved_searches::get_default_saved_search};
+use crate::ui::{categories::CategoriesBlock, ui_blocks::{TopBlock, MainBlock}, state::{AdminState, ChosenCategory, SearchQuery}, search::SearchBlock, scan::ScanBlock, purchasing::PurchasingBlock, sales::{PosBlock, Receipt}, stock_takes::{StockTakesBlock, StockTakeSession}, dashboard::Dashboard, api_tokens::ApiTokensBlock, webhooks::WebhooksBlock, merges::DuplicatesBlock, live::LiveEvents, toasts::{Toasts, ToastsView}, keybindings::{Keybindings, CommandPalette}};
 
 #[component]
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
 
+    let (admin_state, admin_state_setter) = create_signal(AdminState::default());
+    provide_context(admin_state);
+    provide_context(admin_state_setter);
+    LiveEvents::provide();
+    Toasts::provide();
+    Keybindings::provide();
+
     view! {
         // injects a stylesheet into the document <head>
         // id=leptos means cargo-leptos will hot-reload this stylesheet
@@ -21,24 +29,181 @@ pub fn App() -> impl IntoView {
             <main>
                 <Routes>
                     <Route path="" view=HomePage/>
+                    <Route path="/c/:slug" view=HomePage/>
+                    <Route path="/purchasing" view=PurchasingPage/>
+                    <Route path="/pos" view=PosPage/>
+                    <Route path="/sales/:id" view=ReceiptPage/>
+                    <Route path="/stock-takes" view=StockTakesPage/>
+                    <Route path="/stock-takes/:id" view=StockTakeSessionPage/>
+                    <Route path="/dashboard" view=DashboardPage/>
+                    <Route path="/api-tokens" view=ApiTokensPage/>
+                    <Route path="/webhooks" view=WebhooksPage/>
+                    <Route path="/duplicates" view=DuplicatesPage/>
                     <Route path="/*any" view=NotFound/>
                 </Routes>
             </main>
+            // inside of the router, so that the pending removals are carried out on navigation
+            <ToastsView />
+            <CommandPalette />
         </Router>
     }
 }
 
 /// Renders the home page of your application.
+///
+/// The chosen category comes from the slug in the path, which is resolved on the server before the
+/// page is sent, and former slugs of renamed categories are redirected to the current ones.
 #[component]
 fn HomePage() -> impl IntoView {
-    let (admin_state, admin_state_setter) = create_signal(AdminState::default());
-    provide_context(admin_state);
+    let params = use_params_map();
+    let category = create_blocking_resource(
+        move || params.with(|params| params.get("slug").cloned()),
+        |slug| async move {
+            match slug {
+                Some(slug) => resolve_category_slug(slug).await.map(Some),
+                None => Ok(None),
+            }
+        }
+    );
+
+    // Memoized, so that refetching the same category doesn't search items again
+    let chosen_category = create_memo(move |_| {
+        category().and_then(Result::ok).flatten().map(|resolved| resolved.category)
+    });
+    provide_context(ChosenCategory(chosen_category));
+
+    // The bare home page opens the user's default search instead, unless the filters were cleared on it
+    let location = use_location();
+    let default_search = create_blocking_resource(
+        move || {
+            location.pathname.with(|path| path == "/")
+                && location.search.with(String::is_empty)
+                && !location.state.with(SearchQuery::is_set_on_home_page)
+        },
+        |opens_default| async move {
+            if opens_default {
+                get_default_saved_search().await
+            } else {
+                Ok(None)
+            }
+        }
+    );
+
+    // Blocks aren't rendered until the category is known, and aren't re-rendered when it changes
+    let resolved = create_memo(move |_| {
+        matches!(category(), Some(Ok(_))) && matches!(default_search(), Some(Ok(None) | Err(_)))
+    });
+
+    let default_redirect = move || {
+        default_search().and_then(|default_search| default_search.ok()).flatten().map(|default_search| view! {
+            <Redirect path=default_search.href options=NavigateOptions { replace: true, ..Default::default() } />
+        })
+    };
+
+    let slug_state = move || {
+        category().map(|category| match category {
+            Ok(Some(resolved)) if resolved.outdated => {
+                let mut search_query = SearchQuery::use_query_untracked()();
+                search_query.category = Some(resolved.category);
+                view! {
+                    <Redirect path=search_query.href() options=NavigateOptions { replace: true, ..Default::default() } />
+                }.into_view()
+            },
+            Ok(_) => ().into_view(),
+            Err(_) => view! { <NotFound /> }.into_view(),
+        })
+    };
+
+    view! {
+        <TopBlock />
+        <Transition fallback=|| ()>
+            {default_redirect}
+            {slug_state}
+            <Show when=resolved fallback=|| ()>
+                <CategoriesBlock />
+                <SearchBlock />
+                <ScanBlock />
+                <MainBlock />
+            </Show>
+        </Transition>
+    }
+}
 
+/// Suppliers and purchase orders, with receiving of the ordered items.
+#[component]
+fn PurchasingPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <PurchasingBlock />
+    }
+}
+
+/// Point of sale, where objects are put into a cart and sold.
+#[component]
+fn PosPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <PosBlock />
+    }
+}
+
+#[component]
+fn ReceiptPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <Receipt />
+    }
+}
+
+#[component]
+fn StockTakesPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <StockTakesBlock />
+    }
+}
+
+#[component]
+fn StockTakeSessionPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <StockTakeSession />
+    }
+}
+
+/// Overview of the whole database.
+#[component]
+fn DashboardPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <Dashboard />
+    }
+}
+
+/// Tokens of the scripts and devices using the API.
+#[component]
+fn ApiTokensPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <ApiTokensBlock />
+    }
+}
+
+/// Subscriptions of the external systems to the changes.
+#[component]
+fn WebhooksPage() -> impl IntoView {
+    view! {
+        <TopBlock />
+        <WebhooksBlock />
+    }
+}
+
+/// Tags and items entered twice, merged into one.
+#[component]
+fn DuplicatesPage() -> impl IntoView {
     view! {
-        <TopBlock admin_state_setter />
-        <CategoriesBlock />
-        <SearchBlock />
-        <MainBlock />
+        <TopBlock />
+        <DuplicatesBlock />
     }
 }
 
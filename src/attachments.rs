@@ -0,0 +1,186 @@
+use std::{env, path::{Path, PathBuf}, convert::Infallible, future::{Ready, ready}, collections::HashSet, time::{Duration, SystemTime}};
+
+use actix_web::{FromRequest, web, HttpResponse, error::{ErrorPayloadTooLarge, ErrorUnsupportedMediaType, ErrorInternalServerError}};
+use futures::StreamExt;
+
+use crate::{data::{attachments::{ItemAttachment, UploadQuery}, item::ItemId, api_tokens::ApiTokenScope}, db::{Repository, attachments::AttachmentDB}, api_tokens::ApiAccess};
+
+/// Default limit on the size of an uploaded file, 10 MiB.
+const DEFAULT_MAX_SIZE: usize = 10 * 1024 * 1024;
+/// Thumbnails fit into a square of this size.
+const THUMBNAIL_SIZE: u32 = 256;
+/// How often the files, which no attachment refers to, are removed.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Younger files are kept, they may belong to an upload, which isn't recorded yet.
+const MIN_UNREFERENCED_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Directory on the local disk, where the attachments are stored.
+#[derive(Clone)]
+pub struct AttachmentStorage {
+    dir: PathBuf,
+    max_size: usize,
+}
+
+impl AttachmentStorage {
+    /// Reads `ATTACHMENTS_DIR` and `ATTACHMENTS_MAX_SIZE` (in bytes) and creates the directory.
+    pub fn from_env() -> Self {
+        let dir = PathBuf::from(env::var("ATTACHMENTS_DIR").unwrap_or("attachments".to_string()));
+        let max_size = env::var("ATTACHMENTS_MAX_SIZE")
+            .map(|max_size| max_size.parse().expect("ATTACHMENTS_MAX_SIZE to be a number of bytes"))
+            .unwrap_or(DEFAULT_MAX_SIZE);
+
+        std::fs::create_dir_all(&dir).expect("Could not create the attachments directory");
+
+        AttachmentStorage {
+            dir,
+            max_size,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Names of the stored files, which are older than `min_age`.
+    fn stored_names_older_than(&self, min_age: Duration) -> std::io::Result<Vec<String>> {
+        let now = SystemTime::now();
+        let mut names = vec![];
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let modified = entry.metadata()?.modified()?;
+            if now.duration_since(modified).is_ok_and(|age| age >= min_age) {
+                names.extend(entry.file_name().into_string());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Removes the files of an attachment, missing files are ignored.
+    pub fn remove_files(&self, attachment: &ItemAttachment) {
+        let _ = std::fs::remove_file(self.dir.join(&attachment.stored_name));
+        if let Some(thumbnail_name) = &attachment.thumbnail_name {
+            let _ = std::fs::remove_file(self.dir.join(thumbnail_name));
+        }
+    }
+}
+
+impl FromRequest for AttachmentStorage {
+    type Error = Infallible;
+    type Future = Ready<Result<AttachmentStorage, Infallible>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(Ok(req.app_data::<Self>().expect("AttachmentStorage was not found").clone()))
+    }
+}
+
+/// Content types, which are allowed to be attached.
+fn is_allowed(mime_type: &str) -> bool {
+    matches!(
+        mime_type,
+        "image/jpeg" | "image/png" | "image/gif" | "image/webp" | "application/pdf"
+    )
+}
+
+/// Stores the request body as an attachment of the item.
+///
+/// The content type is sniffed from the contents, and images get a thumbnail.
+#[actix_web::post("/upload/{item_id}")]
+pub async fn upload_attachment(
+    item_id: web::Path<ItemId>,
+    query: web::Query<UploadQuery>,
+    mut payload: web::Payload,
+    storage: AttachmentStorage,
+    db: Repository,
+    access: ApiAccess,
+) -> actix_web::Result<HttpResponse> {
+    access.require(ApiTokenScope::Admin)?;
+
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > storage.max_size {
+            return Err(ErrorPayloadTooLarge(format!(
+                "Файл більший за {} МіБ",
+                storage.max_size / 1024 / 1024
+            )));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = body.freeze();
+
+    let Some(kind) = infer::get(&body).filter(|kind| is_allowed(kind.mime_type())) else {
+        return Err(ErrorUnsupportedMediaType("Дозволені лише зображення та PDF"));
+    };
+
+    let file_id = uuid::Uuid::new_v4();
+    let stored_name = format!("{file_id}.{}", kind.extension());
+    let thumbnail_name = kind.mime_type().starts_with("image/").then(|| format!("{file_id}_thumb.png"));
+
+    {
+        let dir = storage.dir.clone();
+        let stored_name = stored_name.clone();
+        let thumbnail_name = thumbnail_name.clone();
+        let body = body.clone();
+
+        web::block(move || -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            std::fs::write(dir.join(&stored_name), &body)?;
+            if let Some(thumbnail_name) = thumbnail_name {
+                image::load_from_memory(&body)?
+                    .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+                    .save_with_format(dir.join(thumbnail_name), image::ImageFormat::Png)?;
+            }
+            Ok(())
+        })
+        .await?
+        .map_err(ErrorInternalServerError)?;
+    }
+
+    let attachment = db.add_item_attachment(
+        item_id.into_inner(),
+        &query.name,
+        kind.mime_type(),
+        body.len() as i64,
+        &stored_name,
+        thumbnail_name.as_deref(),
+    ).await;
+
+    match attachment {
+        Ok(attachment) => Ok(HttpResponse::Created().json(attachment)),
+        Err(err) => {
+            // The item could have been removed meanwhile, don't leave the files behind
+            let _ = std::fs::remove_file(storage.dir.join(&stored_name));
+            if let Some(thumbnail_name) = thumbnail_name {
+                let _ = std::fs::remove_file(storage.dir.join(thumbnail_name));
+            }
+            Err(err.into())
+        }
+    }
+}
+
+/// Removes the files, which no attachment refers to anymore.
+///
+/// Attachment records go away with their item, e.g. when it's removed through the REST API or merged,
+/// so their files are found here rather than on every path, which removes items.
+async fn sweep_unreferenced_files(db: &Repository, storage: &AttachmentStorage) -> Result<(), Box<dyn std::error::Error>> {
+    // Listed before the records are read, so that a file recorded meanwhile is never taken for unreferenced
+    let stored = storage.stored_names_older_than(MIN_UNREFERENCED_AGE)?;
+    let referenced = db.get_stored_names().await?.into_iter().collect::<HashSet<_>>();
+
+    for name in stored.iter().filter(|name| !referenced.contains(*name)) {
+        let _ = std::fs::remove_file(storage.dir.join(name));
+    }
+    Ok(())
+}
+
+/// Removes the unreferenced attachment files from time to time, for as long as the server runs.
+pub async fn run_cleanup_worker(db: Repository, storage: AttachmentStorage) {
+    let mut interval = actix_web::rt::time::interval(SWEEP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = sweep_unreferenced_files(&db, &storage).await {
+            log::error!("Could not remove the unreferenced attachment files: {error:?}");
+        }
+    }
+}
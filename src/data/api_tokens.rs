@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct ApiTokenId(pub Uuid);
+
+/// What a token allows, every scope includes the ones before it.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(type_name = "api_token_scope", rename_all = "snake_case"))]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    Read,
+    /// Adding, selling, moving and counting objects, as scanning devices do.
+    ObjectsWrite,
+    Admin,
+}
+
+impl ApiTokenScope {
+    pub const ALL: [ApiTokenScope; 3] = [ApiTokenScope::Read, ApiTokenScope::ObjectsWrite, ApiTokenScope::Admin];
+
+    pub fn allows(self, required: ApiTokenScope) -> bool {
+        self >= required
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ApiTokenScope::Read => "Лише читання",
+            ApiTokenScope::ObjectsWrite => "Зміна об'єктів",
+            ApiTokenScope::Admin => "Адміністрування",
+        }
+    }
+}
+
+/// A token of a machine client, its secret is stored only as a hash.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApiToken {
+    pub id: ApiTokenId,
+    pub name: String,
+    pub scope: ApiTokenScope,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A just created token with its secret, which can't be shown again.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CreatedApiToken {
+    pub token: ApiToken,
+    pub secret: String,
+}
@@ -0,0 +1,51 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::item::ItemId;
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct ItemAttachmentId(pub Uuid);
+
+/// A file attached to an item, stored on the server's disk.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ItemAttachment {
+    pub id: ItemAttachmentId,
+    pub item_id: ItemId,
+    /// Name of the file as it was uploaded.
+    pub file_name: String,
+    /// Content type, sniffed from the file contents.
+    pub content_type: String,
+    pub size: i64,
+    /// Name of the file in the attachments directory.
+    pub stored_name: String,
+    /// Name of the thumbnail in the attachments directory, only images have one.
+    pub thumbnail_name: Option<String>,
+}
+
+/// Path, under which the attachments directory is served.
+pub const ATTACHMENTS_PATH: &str = "/attachments";
+/// Path of the upload endpoint, followed by the item id.
+pub const UPLOAD_PATH: &str = "/upload";
+
+/// Query string of an upload request, the file itself is the request body.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UploadQuery {
+    /// Original name of the uploaded file.
+    pub name: String,
+}
+
+impl ItemAttachment {
+    pub fn url(&self) -> String {
+        format!("{ATTACHMENTS_PATH}/{}", self.stored_name)
+    }
+
+    pub fn thumbnail_url(&self) -> Option<String> {
+        self.thumbnail_name.as_ref().map(|thumbnail_name| format!("{ATTACHMENTS_PATH}/{thumbnail_name}"))
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+}
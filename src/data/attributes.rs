@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::categories::CategoryId;
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
+pub struct CategoryAttributeId(pub Uuid);
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(type_name = "attribute_kind", rename_all = "lowercase"))]
+#[serde(rename_all = "lowercase")]
+pub enum AttributeKind {
+    Text,
+    /// A number, with an optional unit from [`CategoryAttribute::unit`].
+    Number,
+    /// One of [`CategoryAttribute::options`].
+    Enum,
+    Boolean,
+}
+
+/// Definition of a property, which items of a category can have.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CategoryAttribute {
+    pub id: CategoryAttributeId,
+    pub category_id: CategoryId,
+    pub name: String,
+    pub kind: AttributeKind,
+    pub unit: Option<String>,
+    pub options: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum AttributeValue {
+    Boolean(bool),
+    Number(f64),
+    Text(String),
+}
+
+/// Values of item's attributes, stored as JSON.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(transparent)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ItemAttributes(pub HashMap<CategoryAttributeId, AttributeValue>);
+
+/// Raw attribute value as entered by the user, parsed and validated on the server.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AttributeInput {
+    pub id: CategoryAttributeId,
+    pub value: String,
+}
+
+/// Search condition on an attribute. Unset fields don't restrict the search.
+///
+/// `value` is matched as a substring for text attributes and exactly for the rest,
+/// `min` and `max` apply to number attributes only.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AttributeFilter {
+    pub id: CategoryAttributeId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+impl CategoryAttribute {
+    /// Parses user input according to the attribute kind, `None` if the input doesn't fit it.
+    pub fn parse_value(&self, input: &str) -> Option<AttributeValue> {
+        let input = input.trim();
+        match self.kind {
+            AttributeKind::Text => Some(AttributeValue::Text(input.to_string())),
+            AttributeKind::Number => input.replace(',', ".").parse().ok().map(AttributeValue::Number),
+            AttributeKind::Enum => self.options.iter()
+                .any(|option| option == input)
+                .then(|| AttributeValue::Text(input.to_string())),
+            AttributeKind::Boolean => input.parse().ok().map(AttributeValue::Boolean),
+        }
+    }
+
+    /// Value in a human-readable form, with the unit for numbers.
+    pub fn display_value(&self, value: &AttributeValue) -> String {
+        match (value, &self.unit) {
+            (AttributeValue::Boolean(true), _) => "так".to_string(),
+            (AttributeValue::Boolean(false), _) => "ні".to_string(),
+            (AttributeValue::Number(number), Some(unit)) => format!("{number} {unit}"),
+            (AttributeValue::Number(number), None) => number.to_string(),
+            (AttributeValue::Text(text), _) => text.clone(),
+        }
+    }
+}
+
+impl AttributeValue {
+    /// Value as it would be entered into an input.
+    pub fn to_input(&self) -> String {
+        match self {
+            AttributeValue::Boolean(value) => value.to_string(),
+            AttributeValue::Number(value) => value.to_string(),
+            AttributeValue::Text(value) => value.clone(),
+        }
+    }
+}
+
+/// Why the entered values can't be set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeInputError {
+    /// The attribute isn't one of the item's category.
+    UnknownAttribute,
+    /// The value doesn't fit the attribute with this name.
+    InvalidValue(String),
+}
+
+impl ItemAttributes {
+    /// Parses the entered values of the `attributes`, the empty ones are left unset.
+    pub fn parse(attributes: &[CategoryAttribute], values: &[AttributeInput]) -> Result<ItemAttributes, AttributeInputError> {
+        let mut parsed = HashMap::new();
+        for input in values.iter().filter(|input| !input.value.trim().is_empty()) {
+            let attribute = attributes.iter()
+                .find(|attribute| attribute.id == input.id)
+                .ok_or(AttributeInputError::UnknownAttribute)?;
+            let value = attribute.parse_value(&input.value)
+                .ok_or_else(|| AttributeInputError::InvalidValue(attribute.name.clone()))?;
+            parsed.insert(attribute.id, value);
+        }
+
+        Ok(ItemAttributes(parsed))
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<sqlx::types::Json<ItemAttributes>> for ItemAttributes {
+    fn from(value: sqlx::types::Json<ItemAttributes>) -> Self {
+        value.0
+    }
+}
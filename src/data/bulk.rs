@@ -0,0 +1,133 @@
+use serde::{Serialize, Deserialize};
+
+use super::item::{Item, ItemId};
+
+/// Why an item of a bulk change wasn't changed, the others are changed anyway.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BulkFailure {
+    pub item_id: ItemId,
+    pub reason: String,
+}
+
+/// Outcome of a change of many items, each of which succeeds or fails by itself.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BulkOutcome<T> {
+    pub succeeded: Vec<T>,
+    pub failed: Vec<BulkFailure>,
+}
+
+impl<T> BulkOutcome<T> {
+    pub fn new() -> Self {
+        BulkOutcome {
+            succeeded: vec![],
+            failed: vec![],
+        }
+    }
+
+    /// The distinct reasons of the failures, if there are any.
+    pub fn failure_reasons(&self) -> Option<String> {
+        let mut reasons: Vec<&str> = vec![];
+        for failure in &self.failed {
+            if !reasons.contains(&failure.reason.as_str()) {
+                reasons.push(&failure.reason);
+            }
+        }
+
+        (!reasons.is_empty()).then(|| format!("{} з {} — {}", self.failed.len(), self.failed.len() + self.succeeded.len(), reasons.join("; ")))
+    }
+}
+
+impl<T> Default for BulkOutcome<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The items as CSV with a header row, to be opened in a spreadsheet.
+pub fn items_csv(items: &[Item]) -> String {
+    let mut csv = String::from("id,name,category,tags,in_stock,variants,item_codes\n");
+
+    for item in items {
+        let tags = item.tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>().join("; ");
+        let variants = item.variant_stock().into_iter()
+            .filter_map(|(variant, count)| variant.map(|variant| format!("{}: {count}", variant.name)))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let item_codes = item.objects.iter().filter_map(|object| object.item_code.as_deref()).collect::<Vec<_>>().join("; ");
+        let row = [
+            item.id.0.to_string(),
+            item.name.clone(),
+            item.category.name.clone(),
+            tags,
+            item.objects.len().to_string(),
+            variants,
+            item_codes,
+        ];
+
+        csv.push_str(&row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::data::{categories::{Category, CategoryId}, item::{ItemObject, ItemObjectId, Tag, TagId}};
+
+    use super::*;
+
+    fn item(name: &str, tags: &[&str], item_codes: &[&str]) -> Item {
+        Item {
+            id: ItemId(Uuid::nil()),
+            name: name.to_string(),
+            category: Category { id: CategoryId(Uuid::nil()), name: "Одяг".to_string(), parent_id: None, slug: "odiah".to_string() },
+            tags: tags.iter().map(|tag| Tag { id: TagId(Uuid::new_v4()), name: tag.to_string(), group_id: None, color: "#000000".to_string() }).collect(),
+            objects: item_codes.iter().map(|item_code| ItemObject {
+                id: ItemObjectId(Uuid::new_v4()),
+                item_code: Some(item_code.to_string()),
+                location_id: None,
+                variant_id: None,
+            }).collect(),
+            attributes: Default::default(),
+            variants: vec![],
+        }
+    }
+
+    #[test]
+    fn csv_has_a_row_per_item() {
+        let csv = items_csv(&[item("Светр", &["зима", "вовна"], &["A1", "A2"])]);
+
+        assert_eq!(csv, format!(
+            "id,name,category,tags,in_stock,variants,item_codes\n{},Светр,Одяг,зима; вовна,2,,A1; A2\n",
+            Uuid::nil(),
+        ));
+    }
+
+    #[test]
+    fn csv_quotes_the_special_characters() {
+        let csv = items_csv(&[item("Светр \"Зимовий\", сірий", &[], &[])]);
+
+        assert!(csv.ends_with(",\"Светр \"\"Зимовий\"\", сірий\",Одяг,,0,,\n"));
+    }
+
+    #[test]
+    fn failure_reasons_are_distinct() {
+        let failure = |reason: &str| BulkFailure { item_id: ItemId(Uuid::new_v4()), reason: reason.to_string() };
+        let mut outcome = BulkOutcome { succeeded: vec![1], failed: vec![] };
+        assert_eq!(outcome.failure_reasons(), None);
+
+        outcome.failed = vec![failure("не знайдено"), failure("не знайдено"), failure("використовується")];
+        assert_eq!(outcome.failure_reasons().as_deref(), Some("3 з 4 — не знайдено; використовується"));
+    }
+}
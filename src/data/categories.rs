@@ -3,12 +3,202 @@ use serde::{Deserialize, Serialize};
 use derive_more::{From, Into, FromStr};
 
 #[derive(Clone, Copy, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
-#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
 pub struct CategoryId(pub Uuid);
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(feature = "ssr", derive(sqlx::Type))]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
 pub struct Category {
     pub id: CategoryId,
     pub name: String,
-}
\ No newline at end of file
+    pub parent_id: Option<CategoryId>,
+    /// Unique latin part of the category's address, made from its name.
+    pub slug: String,
+}
+
+/// A category found by its slug.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResolvedSlug {
+    pub category: Category,
+    /// Whether the slug is a former one of a renamed category, so it should be redirected to the current one.
+    pub outdated: bool,
+}
+
+/// A category together with all of its sub-categories.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CategoryTree {
+    pub category: Category,
+    pub children: Vec<CategoryTree>,
+}
+
+impl CategoryTree {
+    /// Builds a forest out of categories, ordered so that parents come before their children.
+    pub fn from_flat(categories: Vec<Category>) -> Vec<CategoryTree> {
+        let mut trees = vec![];
+        for category in categories {
+            Self::insert(&mut trees, category);
+        }
+        trees
+    }
+
+    /// Inserts a category under its parent, or as a root if it has none.
+    ///
+    /// A category whose parent isn't in the forest is inserted as a root.
+    pub fn insert(trees: &mut Vec<CategoryTree>, category: Category) {
+        let parent = category.parent_id
+            .and_then(|parent_id| Self::find_mut(trees, &parent_id));
+
+        let node = CategoryTree { category, children: vec![] };
+        match parent {
+            Some(parent) => parent.children.push(node),
+            None => trees.push(node),
+        }
+    }
+
+    /// Removes a category (with its sub-categories) from the forest.
+    pub fn remove(trees: &mut Vec<CategoryTree>, category_id: &CategoryId) {
+        trees.retain(|tree| &tree.category.id != category_id);
+        for tree in trees.iter_mut() {
+            Self::remove(&mut tree.children, category_id);
+        }
+    }
+
+    pub fn find_mut<'a>(trees: &'a mut [CategoryTree], category_id: &CategoryId) -> Option<&'a mut CategoryTree> {
+        for tree in trees {
+            if &tree.category.id == category_id {
+                return Some(tree);
+            }
+            if let Some(found) = Self::find_mut(&mut tree.children, category_id) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// All categories of the forest in depth-first order, paired with their depth.
+    pub fn flatten(trees: &[CategoryTree]) -> Vec<(usize, Category)> {
+        fn walk(trees: &[CategoryTree], depth: usize, out: &mut Vec<(usize, Category)>) {
+            for tree in trees {
+                out.push((depth, tree.category.clone()));
+                walk(&tree.children, depth + 1, out);
+            }
+        }
+
+        let mut out = vec![];
+        walk(trees, 0, &mut out);
+        out
+    }
+}
+
+/// A workaround module for the [`sqlx::Type`]/[`sqlx::Decode`] derive, which breaks on the
+/// `Option` field because of compiler bug.
+///
+/// Related issue: https://github.com/launchbadge/sqlx/issues/1031
+#[cfg(feature = "ssr")]
+mod derive_workaround {
+    use super::{Category, CategoryId};
+
+    impl ::sqlx::encode::Encode<'_, ::sqlx::Postgres> for Category
+    where
+        CategoryId: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+        CategoryId: ::sqlx::types::Type<::sqlx::Postgres>,
+        String: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+        String: ::sqlx::types::Type<::sqlx::Postgres>,
+        Option<CategoryId>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+        Option<CategoryId>: ::sqlx::types::Type<::sqlx::Postgres>,
+    {
+        fn encode_by_ref(
+            &self,
+            buf: &mut ::sqlx::postgres::PgArgumentBuffer,
+        ) -> ::sqlx::encode::IsNull {
+            let mut encoder = ::sqlx::postgres::types::PgRecordEncoder::new(buf);
+            encoder.encode(self.id);
+            encoder.encode(&self.name);
+            encoder.encode(self.parent_id);
+            encoder.encode(&self.slug);
+            encoder.finish();
+            ::sqlx::encode::IsNull::No
+        }
+        fn size_hint(&self) -> ::std::primitive::usize {
+            4usize * (4 + 4)
+                + <CategoryId as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.id)
+                + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.name)
+                + <Option<CategoryId> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.parent_id)
+                + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.slug)
+        }
+    }
+
+    impl<'r> ::sqlx::decode::Decode<'r, ::sqlx::Postgres> for Category {
+        fn decode(
+            value: ::sqlx::postgres::PgValueRef<'r>,
+        ) -> ::std::result::Result<
+            Self,
+            ::std::boxed::Box<
+                dyn ::std::error::Error + 'static + ::std::marker::Send + ::std::marker::Sync,
+            >,
+        > {
+            let mut decoder = ::sqlx::postgres::types::PgRecordDecoder::new(value)?;
+            let id = decoder.try_decode::<CategoryId>()?;
+            let name = decoder.try_decode::<String>()?;
+            let parent_id = decoder.try_decode::<Option<CategoryId>>()?;
+            let slug = decoder.try_decode::<String>()?;
+            ::std::result::Result::Ok(Category {
+                id,
+                name,
+                parent_id,
+                slug,
+            })
+        }
+    }
+
+    impl ::sqlx::Type<::sqlx::Postgres> for Category {
+        fn type_info() -> ::sqlx::postgres::PgTypeInfo {
+            ::sqlx::postgres::PgTypeInfo::with_name("Category")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+
+    fn category(name: &str, parent: Option<&Category>) -> Category {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        let id = CategoryId(Uuid::from_u64_pair(0, NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+        Category { id, name: name.to_string(), parent_id: parent.map(|parent| parent.id), slug: name.to_lowercase() }
+    }
+
+    fn names(trees: &[CategoryTree]) -> Vec<(usize, String)> {
+        CategoryTree::flatten(trees).into_iter().map(|(depth, category)| (depth, category.name)).collect()
+    }
+
+    #[test]
+    fn nests_children_under_their_parents() {
+        let clothes = category("Clothes", None);
+        let shirts = category("Shirts", Some(&clothes));
+        let polos = category("Polos", Some(&shirts));
+        let shoes = category("Shoes", None);
+
+        let trees = CategoryTree::from_flat(vec![clothes, shirts, shoes, polos]);
+
+        assert_eq!(trees.len(), 2);
+        assert_eq!(names(&trees), vec![
+            (0, "Clothes".to_string()),
+            (1, "Shirts".to_string()),
+            (2, "Polos".to_string()),
+            (0, "Shoes".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn makes_categories_without_their_parent_roots() {
+        let hidden = category("Hidden", None);
+        let orphan = category("Orphan", Some(&hidden));
+
+        let trees = CategoryTree::from_flat(vec![orphan]);
+
+        assert_eq!(names(&trees), vec![(0, "Orphan".to_string())]);
+    }
+}
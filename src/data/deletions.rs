@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::{
+    api_tokens::ApiTokenScope,
+    attachments::ItemAttachmentId,
+    attributes::CategoryAttributeId,
+    categories::CategoryId,
+    item::{ItemId, ItemObjectId, ItemVariantId, TagId, TagGroupId},
+    locations::LocationId,
+    saved_searches::{SavedSearchId, UserId},
+    stock_takes::StockTakeId,
+    suppliers::{SupplierId, PurchaseOrderId, PurchaseOrderLineId},
+    webhooks::WebhookId,
+};
+
+/// A removal can be undone for this long, then it's carried out.
+pub const UNDO_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
+pub struct PendingDeletionId(pub Uuid);
+
+/// What a remove server function removes, it's stored until the removal is carried out.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Deletion {
+    Item { item_id: ItemId },
+    Items { item_ids: Vec<ItemId> },
+    ItemObject { item_object_id: ItemObjectId },
+    ItemTag { item_id: ItemId, tag_id: TagId },
+    ItemAttachment { attachment_id: ItemAttachmentId },
+    ItemVariant { variant_id: ItemVariantId },
+    Tag { tag_id: TagId },
+    TagGroup { group_id: TagGroupId },
+    Category { category_id: CategoryId },
+    CategoryAttribute { attribute_id: CategoryAttributeId },
+    Location { location_id: LocationId },
+    Supplier { supplier_id: SupplierId },
+    PurchaseOrder { order_id: PurchaseOrderId },
+    PurchaseOrderLine { line_id: PurchaseOrderLineId },
+    StockTake { stock_take_id: StockTakeId },
+    SavedSearch { user_id: UserId, saved_search_id: SavedSearchId },
+    Webhook { webhook_id: WebhookId },
+}
+
+impl Deletion {
+    /// Scope needed to undo the removal, the same as to make it.
+    pub fn scope(&self) -> ApiTokenScope {
+        match self {
+            Deletion::ItemObject { .. } => ApiTokenScope::ObjectsWrite,
+            Deletion::SavedSearch { .. } => ApiTokenScope::Read,
+            _ => ApiTokenScope::Admin,
+        }
+    }
+}
+
+/// A removal, which isn't carried out yet and can be undone.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PendingDeletion {
+    pub id: PendingDeletionId,
+    pub delete_at: DateTime<Utc>,
+}
@@ -2,62 +2,201 @@ use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use derive_more::{From, FromStr, Into, Display};
 
-use super::categories::Category;
+use super::{categories::Category, attributes::ItemAttributes, locations::LocationId};
 
 #[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
-#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
 pub struct TagId(pub Uuid);
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(feature = "ssr", derive(sqlx::Type))]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
 pub struct Tag {
     pub id: TagId,
     pub name: String,
+    pub group_id: Option<TagGroupId>,
+    /// Display colour in `#rrggbb` form.
+    pub color: String,
+}
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
+pub struct TagGroupId(pub Uuid);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TagGroup {
+    pub id: TagGroupId,
+    pub name: String,
+}
+
+/// Tags of a single group, or the tags without a group if `group` is `None`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GroupedTags {
+    pub group: Option<TagGroup>,
+    pub tags: Vec<Tag>,
+}
+
+impl GroupedTags {
+    /// Groups tags, keeping every group even if it has no tags. Ungrouped tags come first.
+    pub fn group(groups: Vec<TagGroup>, tags: Vec<Tag>) -> Vec<GroupedTags> {
+        let mut grouped: Vec<GroupedTags> = std::iter::once(None)
+            .chain(groups.into_iter().map(Some))
+            .map(|group| GroupedTags { group, tags: vec![] })
+            .collect();
+
+        for tag in tags {
+            Self::insert(&mut grouped, tag);
+        }
+
+        grouped
+    }
+
+    pub fn all_tags(grouped: &[GroupedTags]) -> impl Iterator<Item = &Tag> {
+        grouped.iter().flat_map(|group| group.tags.iter())
+    }
+
+    /// Adds a tag to its group, or to the ungrouped tags if its group isn't known.
+    pub fn insert(grouped: &mut [GroupedTags], tag: Tag) {
+        let group_idx = grouped.iter()
+            .position(|group| group.group.as_ref().map(|group| group.id) == tag.group_id)
+            .or_else(|| grouped.iter().position(|group| group.group.is_none()));
+
+        if let Some(idx) = group_idx {
+            grouped[idx].tags.push(tag);
+        }
+    }
+
+    pub fn remove(grouped: &mut [GroupedTags], tag_id: &TagId) -> Option<Tag> {
+        grouped.iter_mut().find_map(|group| {
+            let idx = group.tags.iter().position(|tag| &tag.id == tag_id)?;
+            Some(group.tags.remove(idx))
+        })
+    }
 }
 
 #[derive(Clone, Copy, Debug, From, Into, Deserialize, Serialize, PartialEq)]
-#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
 pub struct ItemObjectId(pub Uuid);
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
-#[cfg_attr(feature = "ssr", derive(sqlx::Type))]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema))]
 pub struct ItemObject {
     pub id: ItemObjectId,
     pub item_code: Option<String>,
+    pub location_id: Option<LocationId>,
+    /// Variant of the item, which the object is, if the item has variants.
+    pub variant_id: Option<ItemVariantId>,
+}
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
+pub struct ItemVariantId(pub Uuid);
+
+/// A size, colour or other kind of a product, which is stocked separately.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ItemVariant {
+    pub id: ItemVariantId,
+    pub name: String,
+    /// Values of the attributes, which tell the variants apart.
+    pub attributes: ItemAttributes,
 }
 
 #[derive(Clone, Copy, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
-#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
 pub struct ItemId(pub Uuid);
 
 pub struct ItemIncomplete {
     pub id: ItemId,
     pub name: String,
-    pub category: Category
+    pub category: Category,
+    pub attributes: ItemAttributes,
 }
 
 //#[derive(sqlx::Type)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
 pub struct Item {
     pub id: ItemId,
     pub name: String,
     pub category: Category,
     pub tags: Vec<Tag>,
     pub objects: Vec<ItemObject>,
+    pub attributes: ItemAttributes,
+    pub variants: Vec<ItemVariant>,
+}
+
+impl Item {
+    /// Objects in stock of each variant, then of the item itself, if it has objects without a variant.
+    pub fn variant_stock(&self) -> Vec<(Option<&ItemVariant>, usize)> {
+        let mut stock: Vec<(Option<&ItemVariant>, usize)> = self.variants.iter()
+            .map(|variant| (Some(variant), self.objects.iter().filter(|object| object.variant_id == Some(variant.id)).count()))
+            .collect();
+
+        let without_variant = self.objects.iter()
+            .filter(|object| !self.variants.iter().any(|variant| object.variant_id == Some(variant.id)))
+            .count();
+        if without_variant > 0 {
+            stock.push((None, without_variant));
+        }
+
+        stock
+    }
+
+    /// Groups items by their categories, which are sorted by name.
+    pub fn group_by_category(items: Vec<Item>) -> Vec<(Category, Vec<Item>)> {
+        let mut grouped: Vec<(Category, Vec<Item>)> = vec![];
+
+        for item in items {
+            match grouped.iter_mut().find(|(category, _)| category.id == item.category.id) {
+                Some((_, items)) => items.push(item),
+                None => grouped.push((item.category.clone(), vec![item])),
+            }
+        }
+
+        grouped.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+        grouped
+    }
+}
+
+/// Result of looking up an [`ItemObject`] by its code: the owning item and the matched object.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ItemObjectLookup {
+    pub item: Item,
+    pub object_id: ItemObjectId,
+}
+
+/// A hint shown while typing a search query.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Suggestion {
+    Item {
+        item_id: ItemId,
+        item_name: String,
+        category: Category,
+    },
+    Tag(Tag),
+    Object {
+        object_id: ItemObjectId,
+        item_code: String,
+        item_name: String,
+        category: Category,
+    },
 }
 
 /// A workaround module for the [`sqlx::Type`]/[`sqlx::Decode`] derive, which breaks because of compiler bug.
 /// 
 /// Related issue: https://github.com/launchbadge/sqlx/issues/1031
 /// 
-/// Also an impl of [`sqlx::postgres::PgHasArrayType`] for Tag and ItemObject.
+/// Also an impl of [`sqlx::postgres::PgHasArrayType`] for Tag and ItemObject, and the impls for Tag,
+/// which break the same way on its `Option` field.
 #[cfg(feature = "ssr")]
 mod derive_workaround {
     use sqlx::postgres::PgHasArrayType;
 
-    use crate::data::categories::Category;
+    use sqlx::types::Json;
+
+    use crate::data::{categories::Category, attributes::ItemAttributes};
 
-    use super::{Item, ItemId, ItemObject, Tag};
+    use super::{Item, ItemId, ItemObject, ItemVariant, Tag, TagId, TagGroupId};
 
     impl PgHasArrayType for Tag {
         fn array_type_info() -> sqlx::postgres::PgTypeInfo {
@@ -71,6 +210,57 @@ mod derive_workaround {
         }
     }
 
+    impl ::sqlx::encode::Encode<'_, ::sqlx::Postgres> for Tag {
+        fn encode_by_ref(
+            &self,
+            buf: &mut ::sqlx::postgres::PgArgumentBuffer,
+        ) -> ::sqlx::encode::IsNull {
+            let mut encoder = ::sqlx::postgres::types::PgRecordEncoder::new(buf);
+            encoder.encode(self.id);
+            encoder.encode(&self.name);
+            encoder.encode(self.group_id);
+            encoder.encode(&self.color);
+            encoder.finish();
+            ::sqlx::encode::IsNull::No
+        }
+        fn size_hint(&self) -> ::std::primitive::usize {
+            4usize * (4 + 4)
+                + <TagId as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.id)
+                + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.name)
+                + <Option<TagGroupId> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.group_id)
+                + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.color)
+        }
+    }
+
+    impl<'r> ::sqlx::decode::Decode<'r, ::sqlx::Postgres> for Tag {
+        fn decode(
+            value: ::sqlx::postgres::PgValueRef<'r>,
+        ) -> ::std::result::Result<
+            Self,
+            ::std::boxed::Box<
+                dyn ::std::error::Error + 'static + ::std::marker::Send + ::std::marker::Sync,
+            >,
+        > {
+            let mut decoder = ::sqlx::postgres::types::PgRecordDecoder::new(value)?;
+            let id = decoder.try_decode::<TagId>()?;
+            let name = decoder.try_decode::<String>()?;
+            let group_id = decoder.try_decode::<Option<TagGroupId>>()?;
+            let color = decoder.try_decode::<String>()?;
+            ::std::result::Result::Ok(Tag {
+                id,
+                name,
+                group_id,
+                color,
+            })
+        }
+    }
+
+    impl ::sqlx::Type<::sqlx::Postgres> for Tag {
+        fn type_info() -> ::sqlx::postgres::PgTypeInfo {
+            ::sqlx::postgres::PgTypeInfo::with_name("Tag")
+        }
+    }
+
     impl ::sqlx::encode::Encode<'_, ::sqlx::Postgres> for Item
     where
         ItemId: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
@@ -83,22 +273,28 @@ mod derive_workaround {
         Vec<Tag>: ::sqlx::types::Type<::sqlx::Postgres>,
         Vec<ItemObject>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
         Vec<ItemObject>: ::sqlx::types::Type<::sqlx::Postgres>,
+        Json<ItemAttributes>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+        Json<ItemAttributes>: ::sqlx::types::Type<::sqlx::Postgres>,
+        Json<Vec<ItemVariant>>: for<'q> ::sqlx::encode::Encode<'q, ::sqlx::Postgres>,
+        Json<Vec<ItemVariant>>: ::sqlx::types::Type<::sqlx::Postgres>,
     {
         fn encode_by_ref(
             &self,
             buf: &mut ::sqlx::postgres::PgArgumentBuffer,
         ) -> ::sqlx::encode::IsNull {
             let mut encoder = ::sqlx::postgres::types::PgRecordEncoder::new(buf);
-            encoder.encode(&self.id);
+            encoder.encode(self.id);
             encoder.encode(&self.name);
             encoder.encode(&self.category);
             encoder.encode(&self.tags);
             encoder.encode(&self.objects);
+            encoder.encode(Json(&self.attributes));
+            encoder.encode(Json(&self.variants));
             encoder.finish();
             ::sqlx::encode::IsNull::No
         }
         fn size_hint(&self) -> ::std::primitive::usize {
-            5usize * (4 + 4)
+            7usize * (4 + 4)
                 + <ItemId as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.id)
                 + <String as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.name)
                 + <Category as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(&self.category)
@@ -106,6 +302,12 @@ mod derive_workaround {
                 + <Vec<ItemObject> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(
                     &self.objects,
                 )
+                + <Json<&ItemAttributes> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(
+                    &Json(&self.attributes),
+                )
+                + <Json<&Vec<ItemVariant>> as ::sqlx::encode::Encode<::sqlx::Postgres>>::size_hint(
+                    &Json(&self.variants),
+                )
         }
     }
     
@@ -121,6 +323,10 @@ mod derive_workaround {
         Vec<Tag>: ::sqlx::types::Type<::sqlx::Postgres>,
         Vec<ItemObject>: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
         Vec<ItemObject>: ::sqlx::types::Type<::sqlx::Postgres>,
+        Json<ItemAttributes>: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
+        Json<ItemAttributes>: ::sqlx::types::Type<::sqlx::Postgres>,
+        Json<Vec<ItemVariant>>: ::sqlx::decode::Decode<'r, ::sqlx::Postgres>,
+        Json<Vec<ItemVariant>>: ::sqlx::types::Type<::sqlx::Postgres>,
     {
         fn decode(
             value: ::sqlx::postgres::PgValueRef<'r>,
@@ -136,12 +342,16 @@ mod derive_workaround {
             let category = decoder.try_decode::<Category>()?;
             let tags = decoder.try_decode::<Vec<Tag>>()?;
             let objects = decoder.try_decode::<Vec<ItemObject>>()?;
+            let attributes = decoder.try_decode::<Json<ItemAttributes>>()?.0;
+            let variants = decoder.try_decode::<Json<Vec<ItemVariant>>>()?.0;
             ::std::result::Result::Ok(Item {
                 id,
                 name,
                 category,
                 tags,
                 objects,
+                attributes,
+                variants,
             })
         }
     }
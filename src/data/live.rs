@@ -0,0 +1,116 @@
+use serde::{Serialize, Deserialize};
+
+use super::{categories::{Category, CategoryId, CategoryTree}, item::{Item, ItemId, ItemObject, ItemObjectId, Tag, TagId, TagGroup, TagGroupId, GroupedTags}};
+
+/// Stream of the [`LiveEvent`]s, as server-sent events.
+pub const LIVE_EVENTS_PATH: &str = "/events";
+
+/// A change made by anyone, sent to every open page, so that it doesn't have to be reloaded.
+///
+/// The page, which made the change, gets it too, so applying a change twice must be harmless.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum LiveEvent {
+    CategoryAdded(Category),
+    CategoryRenamed(Category),
+    CategoryRemoved(CategoryId),
+    TagAdded(Tag),
+    TagUpdated(Tag),
+    TagRemoved(TagId),
+    TagGroupAdded(TagGroup),
+    TagGroupRemoved(TagGroupId),
+    ItemAdded(Item),
+    /// Tags, attributes or objects of an item were changed.
+    ItemUpdated(Item),
+    ItemRemoved(ItemId),
+    /// Objects were removed or sold.
+    ObjectsRemoved(Vec<ItemObjectId>),
+    ObjectsMoved(Vec<ItemObject>),
+    /// The page has fallen behind and lost some events, so everything has to be reloaded.
+    Missed,
+}
+
+impl LiveEvent {
+    pub fn apply_to_categories(&self, trees: &mut Vec<CategoryTree>) {
+        match self {
+            LiveEvent::CategoryAdded(category) | LiveEvent::CategoryRenamed(category) => {
+                match CategoryTree::find_mut(trees, &category.id) {
+                    Some(tree) => tree.category = category.clone(),
+                    None => CategoryTree::insert(trees, category.clone()),
+                }
+            },
+            LiveEvent::CategoryRemoved(category_id) => CategoryTree::remove(trees, category_id),
+            _ => {},
+        }
+    }
+
+    pub fn apply_to_tags(&self, grouped: &mut Vec<GroupedTags>) {
+        match self {
+            LiveEvent::TagAdded(tag) | LiveEvent::TagUpdated(tag) => {
+                GroupedTags::remove(grouped, &tag.id);
+                GroupedTags::insert(grouped, tag.clone());
+            },
+            LiveEvent::TagRemoved(tag_id) => {
+                GroupedTags::remove(grouped, tag_id);
+            },
+            LiveEvent::TagGroupAdded(group) => {
+                if !grouped.iter().any(|grouped| grouped.group.as_ref().is_some_and(|known| known.id == group.id)) {
+                    grouped.push(GroupedTags { group: Some(group.clone()), tags: vec![] });
+                }
+            },
+            LiveEvent::TagGroupRemoved(group_id) => {
+                let idx = grouped.iter()
+                    .position(|grouped| grouped.group.as_ref().map(|group| &group.id) == Some(group_id));
+                // Tags of the removed group become ungrouped, same as in the database
+                if let Some(idx) = idx {
+                    for tag in grouped.remove(idx).tags {
+                        GroupedTags::insert(grouped, Tag { group_id: None, ..tag });
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Applies the change to the found items.
+    ///
+    /// New items aren't added, because only the caller knows whether they match its search.
+    pub fn apply_to_items(&self, items: &mut Vec<Item>) {
+        match self {
+            LiveEvent::CategoryRenamed(category) => {
+                for item in items.iter_mut().filter(|item| item.category.id == category.id) {
+                    item.category = category.clone();
+                }
+            },
+            LiveEvent::CategoryRemoved(category_id) => items.retain(|item| &item.category.id != category_id),
+            LiveEvent::TagUpdated(tag) => {
+                for item_tag in items.iter_mut().flat_map(|item| item.tags.iter_mut()).filter(|item_tag| item_tag.id == tag.id) {
+                    *item_tag = tag.clone();
+                }
+            },
+            LiveEvent::TagRemoved(tag_id) => {
+                for item in items.iter_mut() {
+                    item.tags.retain(|tag| &tag.id != tag_id);
+                }
+            },
+            LiveEvent::ItemUpdated(updated) => {
+                if let Some(item) = items.iter_mut().find(|item| item.id == updated.id) {
+                    *item = updated.clone();
+                }
+            },
+            LiveEvent::ItemRemoved(item_id) => items.retain(|item| &item.id != item_id),
+            LiveEvent::ObjectsRemoved(object_ids) => {
+                for item in items.iter_mut() {
+                    item.objects.retain(|object| !object_ids.contains(&object.id));
+                }
+            },
+            LiveEvent::ObjectsMoved(moved_objects) => {
+                for object in items.iter_mut().flat_map(|item| item.objects.iter_mut()) {
+                    if let Some(moved_object) = moved_objects.iter().find(|moved_object| moved_object.id == object.id) {
+                        *object = moved_object.clone();
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::item::{ItemObject, ItemObjectId};
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type, utoipa::ToSchema), sqlx(transparent))]
+pub struct LocationId(pub Uuid);
+
+/// A store or a warehouse, where objects are kept.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Location {
+    pub id: LocationId,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct ObjectTransferId(pub Uuid);
+
+/// A move of an object between locations. Locations are `None` for objects, that had no location,
+/// or if the location was removed since.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ObjectTransfer {
+    pub id: ObjectTransferId,
+    pub object_id: ItemObjectId,
+    pub from_location: Option<String>,
+    pub to_location: Option<String>,
+    pub transferred_at: DateTime<Utc>,
+}
+
+/// Outcome of a transfer, sold, removed or unknown objects are skipped, the others are moved anyway.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransferOutcome {
+    pub moved: Vec<ItemObject>,
+    pub skipped: Vec<ItemObjectId>,
+}
+
+impl Location {
+    /// Number of objects at every location, in the order of `locations`, followed by the number
+    /// of objects without a location. Locations without objects are left out.
+    pub fn stock_counts<'a>(locations: &'a [Location], objects: &[ItemObject]) -> Vec<(Option<&'a Location>, usize)> {
+        let count_at = |location_id: Option<LocationId>| {
+            objects.iter().filter(|object| object.location_id == location_id).count()
+        };
+
+        locations.iter()
+            .map(|location| (Some(location), count_at(Some(location.id))))
+            .chain(std::iter::once((None, count_at(None))))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+}
@@ -0,0 +1,32 @@
+use serde::{Serialize, Deserialize};
+
+use super::item::{ItemId, TagId};
+
+/// Two tags with similar names, e.g. differing only in case, which are likely the same tag.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DuplicateTags {
+    pub first_id: TagId,
+    pub first_name: String,
+    /// Items with the tag, the one with more of them is usually the one to keep.
+    pub first_items: i64,
+    pub second_id: TagId,
+    pub second_name: String,
+    pub second_items: i64,
+    /// Trigram similarity of the names, from 0 to 1.
+    pub similarity: f32,
+}
+
+/// Two items with similar names, which are likely the same product entered twice.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DuplicateItems {
+    pub first_id: ItemId,
+    pub first_name: String,
+    pub first_category: String,
+    /// Objects in stock, the item with more of them is usually the one to keep.
+    pub first_in_stock: i64,
+    pub second_id: ItemId,
+    pub second_name: String,
+    pub second_category: String,
+    pub second_in_stock: i64,
+    pub similarity: f32,
+}
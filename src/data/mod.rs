@@ -1,2 +1,17 @@
 pub mod item;
-pub mod categories;
\ No newline at end of file
+pub mod categories;
+pub mod attributes;
+pub mod attachments;
+pub mod suppliers;
+pub mod locations;
+pub mod sales;
+pub mod stock_takes;
+pub mod stats;pub mod search;
+pub mod saved_searches;
+pub mod api_tokens;
+pub mod webhooks;
+pub mod deletions;
+pub mod bulk;
+pub mod merges;
+pub mod variants;
+pub mod live;
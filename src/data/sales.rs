@@ -0,0 +1,92 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::item::{ItemId, ItemObjectId};
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct SaleId(pub Uuid);
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct SaleLineId(pub Uuid);
+
+/// An object in stock, which can be put into a cart.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SaleObject {
+    pub object_id: ItemObjectId,
+    pub item_code: Option<String>,
+    pub item_id: ItemId,
+    pub item_name: String,
+    /// Price of the item's last sale, in kopecks.
+    pub last_price: Option<i64>,
+}
+
+/// An object in a cart with the price it's sold for, in kopecks.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CartLine {
+    pub object_id: ItemObjectId,
+    pub price: i64,
+}
+
+/// A sold object. `object_id` is `None` if the object was removed since.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SaleLine {
+    pub id: SaleLineId,
+    pub object_id: Option<ItemObjectId>,
+    pub item_name: String,
+    pub item_code: Option<String>,
+    pub price: i64,
+}
+
+pub struct SaleIncomplete {
+    pub id: SaleId,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Sale {
+    pub id: SaleId,
+    pub created_at: DateTime<Utc>,
+    pub lines: Vec<SaleLine>,
+}
+
+impl Sale {
+    pub fn total(&self) -> i64 {
+        self.lines.iter().map(|line| line.price).sum()
+    }
+}
+
+/// The shop's days start at midnight in this zone, whatever the zones of the server and the database are.
+pub const SHOP_TIME_ZONE: &str = "Europe/Kyiv";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SalesSummary {
+    pub day: NaiveDate,
+    pub sales_count: i64,
+    pub objects_sold: i64,
+    /// In kopecks.
+    pub total: i64,
+}
+
+/// Price in kopecks as hryvnias, e.g. `1250` as `12.50`.
+pub fn format_price(price: i64) -> String {
+    format!("{}.{:02}", price / 100, price % 100)
+}
+
+/// Parses hryvnias with up to two decimal places into kopecks. Both `.` and `,` are accepted as a separator.
+pub fn parse_price(input: &str) -> Option<i64> {
+    let input = input.trim().replace(',', ".");
+    let (hryvnias, kopecks) = input.split_once('.').unwrap_or((&input, ""));
+
+    if hryvnias.is_empty() || kopecks.len() > 2 || !kopecks.chars().all(|digit| digit.is_ascii_digit()) {
+        return None;
+    }
+
+    let hryvnias: i64 = hryvnias.parse().ok().filter(|hryvnias| *hryvnias >= 0)?;
+    let kopecks: i64 = format!("{kopecks:0<2}").parse().ok()?;
+
+    hryvnias.checked_mul(100)?.checked_add(kopecks)
+}
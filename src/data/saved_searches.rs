@@ -0,0 +1,57 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+/// Anonymous user, identified by a cookie in their browser.
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct UserId(pub Uuid);
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct SavedSearchId(pub Uuid);
+
+/// A named search query of a user, kept as a link to the home page with its filters.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SavedSearch {
+    pub id: SavedSearchId,
+    pub name: String,
+    pub href: String,
+    /// Whether the search is opened instead of the bare home page.
+    pub is_default: bool,
+}
+
+impl SavedSearch {
+    /// Whether the link leads to the home page, with or without a category, and so is safe to open.
+    /// Other links, e.g. to other sites or with `javascript:`, aren't saved.
+    pub fn is_home_link(href: &str) -> bool {
+        let path = href.split_once('?').map_or(href, |(path, _)| path);
+        let is_home_path = match path.strip_prefix("/c/") {
+            Some(slug) => !slug.is_empty() && slug.bytes().all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-'),
+            None => path == "/",
+        };
+        is_home_path && !href.chars().any(|char| char.is_control() || char.is_whitespace() || char == '\\')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SavedSearch;
+
+    #[test]
+    fn home_links_are_accepted() {
+        for href in ["/", "/?q=lamp", "/c/lampy", "/c/lampy-2?subcategories=true&filter_tags[0]=x"] {
+            assert!(SavedSearch::is_home_link(href), "{href}");
+        }
+    }
+
+    #[test]
+    fn other_links_are_refused() {
+        for href in [
+            "", "//evil.example", "https://evil.example/", "javascript:alert(1)", "/\\evil.example",
+            "/c/", "/c/../admin", "/c/Lampy", "/pos", "/c/lampy/extra", " /", "/?q=a b",
+        ] {
+            assert!(!SavedSearch::is_home_link(href), "{href}");
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Serialize, Deserialize};
+
+use super::item::Item;
+
+/// Pairs of the same keys on the Latin (QWERTY) and the Ukrainian (ЙЦУКЕН) keyboard layouts.
+const KEYBOARD_LAYOUT: &[(char, char)] = &[
+    ('`', 'ґ'), ('q', 'й'), ('w', 'ц'), ('e', 'у'), ('r', 'к'), ('t', 'е'), ('y', 'н'), ('u', 'г'),
+    ('i', 'ш'), ('o', 'щ'), ('p', 'з'), ('[', 'х'), (']', 'ї'), ('a', 'ф'), ('s', 'і'), ('d', 'в'),
+    ('f', 'а'), ('g', 'п'), ('h', 'р'), ('j', 'о'), ('k', 'л'), ('l', 'д'), (';', 'ж'), ('\'', 'є'),
+    ('z', 'я'), ('x', 'ч'), ('c', 'с'), ('v', 'м'), ('b', 'и'), ('n', 'т'), ('m', 'ь'), (',', 'б'),
+    ('.', 'ю'),
+];
+
+/// Retypes the text as if it was typed with the other keyboard layout, e.g. "vfufpby" becomes "магазин".
+pub fn switch_keyboard_layout(text: &str) -> String {
+    text.chars().map(|typed| {
+        let lowercase = typed.to_lowercase().next().unwrap_or(typed);
+        let switched = KEYBOARD_LAYOUT.iter().find_map(|&(latin, cyrillic)| {
+            if lowercase == latin {
+                Some(cyrillic)
+            } else if lowercase == cyrillic {
+                Some(latin)
+            } else {
+                None
+            }
+        });
+
+        match switched {
+            Some(switched) if typed.is_uppercase() => switched.to_uppercase().next().unwrap_or(switched),
+            Some(switched) => switched,
+            None => typed,
+        }
+    }).collect()
+}
+
+/// Found items, with the exact matches first, followed by the corrected ones, if there were few exact matches.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchResults {
+    pub items: Vec<Item>,
+    /// Corrected query, which the corrected matches were found by.
+    pub did_you_mean: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches_between_the_layouts() {
+        assert_eq!(switch_keyboard_layout("vfufpby"), "магазин");
+        assert_eq!(switch_keyboard_layout("магазин"), "vfufpby");
+        assert_eq!(switch_keyboard_layout("ghbdsn"), "привіт");
+    }
+
+    #[test]
+    fn keeps_the_case() {
+        assert_eq!(switch_keyboard_layout("Vfufpby"), "Магазин");
+        assert_eq!(switch_keyboard_layout("ЇЖАК"), "];FR");
+    }
+
+    #[test]
+    fn keeps_the_keys_outside_of_the_layout() {
+        assert_eq!(switch_keyboard_layout("vfufpby 42!"), "магазин 42!");
+        assert_eq!(switch_keyboard_layout("42 €"), "42 €");
+    }
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use super::{categories::Category, item::{ItemId, ItemObjectId, TagId}};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CategoryItemsCount {
+    pub category: Category,
+    pub items_count: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TagUsage {
+    pub tag_id: TagId,
+    pub tag_name: String,
+    pub color: String,
+    pub items_count: i64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ItemSummary {
+    pub item_id: ItemId,
+    pub item_name: String,
+    pub category: Category,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ObjectSummary {
+    pub object_id: ItemObjectId,
+    pub item_code: Option<String>,
+    pub item_name: String,
+    pub category: Category,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Overview of the whole database. Sold objects aren't counted.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Stats {
+    pub categories_count: i64,
+    pub items_count: i64,
+    pub objects_count: i64,
+    pub tags_count: i64,
+    pub items_per_category: Vec<CategoryItemsCount>,
+    pub top_tags: Vec<TagUsage>,
+    pub recent_items: Vec<ItemSummary>,
+    pub recent_objects: Vec<ObjectSummary>,
+    pub items_without_objects: Vec<ItemSummary>,
+}
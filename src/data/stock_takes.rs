@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::{categories::CategoryId, locations::LocationId, item::{ItemId, ItemObjectId}};
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct StockTakeId(pub Uuid);
+
+/// A count of the objects of a category (with its sub-categories), at a location, or both.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StockTake {
+    pub id: StockTakeId,
+    pub category_id: Option<CategoryId>,
+    pub category_name: Option<String>,
+    pub location_id: Option<LocationId>,
+    pub location_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub scans_count: i64,
+}
+
+impl StockTake {
+    /// What is being counted, e.g. "Телефони, Склад".
+    pub fn scope(&self) -> String {
+        [self.category_name.as_deref(), self.location_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// An object in the counted scope, which wasn't scanned.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MissingObject {
+    pub object_id: ItemObjectId,
+    pub item_code: Option<String>,
+    pub item_id: ItemId,
+    pub item_name: String,
+}
+
+/// A scanned code, which doesn't belong to any object in the counted scope.
+///
+/// `item_name` is set if the code belongs to an object outside of the scope.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UnexpectedScan {
+    pub item_code: String,
+    pub item_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DuplicateScan {
+    pub item_code: String,
+    pub count: i64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct StockTakeReport {
+    pub missing: Vec<MissingObject>,
+    pub unexpected: Vec<UnexpectedScan>,
+    pub duplicates: Vec<DuplicateScan>,
+}
+
+/// A fix of a discrepancy, applied when the stock-take is approved.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum StockTakeCorrection {
+    /// Removes an object, that wasn't found.
+    RemoveObject(ItemObjectId),
+    /// Adds an object with an unexpected code to an item.
+    AddObject { item_id: ItemId, item_code: String },
+}
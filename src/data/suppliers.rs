@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::item::ItemId;
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct SupplierId(pub Uuid);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Supplier {
+    pub id: SupplierId,
+    pub name: String,
+    pub contact: Option<String>,
+}
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct PurchaseOrderId(pub Uuid);
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct PurchaseOrderLineId(pub Uuid);
+
+/// An ordered quantity of an item. Objects of the item are created as the line is received.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PurchaseOrderLine {
+    pub id: PurchaseOrderLineId,
+    pub order_id: PurchaseOrderId,
+    pub item_id: ItemId,
+    pub item_name: String,
+    pub quantity: i32,
+    pub received_quantity: i32,
+}
+
+impl PurchaseOrderLine {
+    pub fn remaining(&self) -> i32 {
+        self.quantity - self.received_quantity
+    }
+}
+
+pub struct PurchaseOrderIncomplete {
+    pub id: PurchaseOrderId,
+    pub supplier: Supplier,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PurchaseOrder {
+    pub id: PurchaseOrderId,
+    pub supplier: Supplier,
+    pub created_at: DateTime<Utc>,
+    pub lines: Vec<PurchaseOrderLine>,
+}
+
+impl PurchaseOrder {
+    pub fn is_received(&self) -> bool {
+        self.lines.iter().all(|line| line.remaining() == 0)
+    }
+}
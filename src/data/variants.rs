@@ -0,0 +1,43 @@
+use serde::{Serialize, Deserialize};
+
+use super::attributes::{AttributeValue, CategoryAttributeId, ItemAttributes};
+
+/// Options of a choice attribute, e.g. the sizes, which the variants are made in.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct VariantAxis {
+    pub attribute_id: CategoryAttributeId,
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+/// A variant to be made from a combination of the options.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct VariantCombination {
+    /// The options joined in the order of the axes, e.g. "M / червоний".
+    pub name: String,
+    pub attributes: ItemAttributes,
+}
+
+impl VariantAxis {
+    /// Every combination of one option of each axis. Axes without options are skipped.
+    pub fn combinations(axes: &[VariantAxis]) -> Vec<VariantCombination> {
+        let mut combinations = vec![VariantCombination { name: String::new(), attributes: ItemAttributes::default() }];
+
+        for axis in axes.iter().filter(|axis| !axis.options.is_empty()) {
+            combinations = combinations.into_iter().flat_map(|combination| {
+                axis.options.iter().map(move |option| {
+                    let mut combination = combination.clone();
+                    if !combination.name.is_empty() {
+                        combination.name.push_str(" / ");
+                    }
+                    combination.name.push_str(option);
+                    combination.attributes.0.insert(axis.attribute_id, AttributeValue::Text(option.clone()));
+                    combination
+                })
+            }).collect();
+        }
+
+        combinations.retain(|combination| !combination.name.is_empty());
+        combinations
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use derive_more::{From, FromStr, Into, Display};
+
+use super::{categories::{Category, CategoryId}, item::{Item, ItemId, Tag, TagId}};
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct WebhookId(pub Uuid);
+
+#[derive(Clone, Copy, Display, Debug, From, FromStr, Into, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(transparent))]
+pub struct WebhookDeliveryId(pub Uuid);
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(type_name = "webhook_event_type"))]
+pub enum WebhookEventType {
+    /// Sent only by the test of a webhook.
+    #[serde(rename = "ping")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "ping"))]
+    Ping,
+    #[serde(rename = "category.created")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "category.created"))]
+    CategoryCreated,
+    #[serde(rename = "category.updated")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "category.updated"))]
+    CategoryUpdated,
+    #[serde(rename = "category.deleted")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "category.deleted"))]
+    CategoryDeleted,
+    #[serde(rename = "tag.created")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "tag.created"))]
+    TagCreated,
+    #[serde(rename = "tag.updated")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "tag.updated"))]
+    TagUpdated,
+    #[serde(rename = "tag.deleted")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "tag.deleted"))]
+    TagDeleted,
+    #[serde(rename = "item.created")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "item.created"))]
+    ItemCreated,
+    /// Tags or attributes of an item were changed.
+    #[serde(rename = "item.updated")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "item.updated"))]
+    ItemUpdated,
+    #[serde(rename = "item.deleted")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "item.deleted"))]
+    ItemDeleted,
+    /// Objects of an item were added, removed or sold.
+    #[serde(rename = "stock.changed")]
+    #[cfg_attr(feature = "ssr", sqlx(rename = "stock.changed"))]
+    StockChanged,
+}
+
+impl WebhookEventType {
+    /// The types, which can be subscribed to.
+    pub const SUBSCRIBABLE: [WebhookEventType; 10] = [
+        WebhookEventType::CategoryCreated,
+        WebhookEventType::CategoryUpdated,
+        WebhookEventType::CategoryDeleted,
+        WebhookEventType::TagCreated,
+        WebhookEventType::TagUpdated,
+        WebhookEventType::TagDeleted,
+        WebhookEventType::ItemCreated,
+        WebhookEventType::ItemUpdated,
+        WebhookEventType::ItemDeleted,
+        WebhookEventType::StockChanged,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEventType::Ping => "ping",
+            WebhookEventType::CategoryCreated => "category.created",
+            WebhookEventType::CategoryUpdated => "category.updated",
+            WebhookEventType::CategoryDeleted => "category.deleted",
+            WebhookEventType::TagCreated => "tag.created",
+            WebhookEventType::TagUpdated => "tag.updated",
+            WebhookEventType::TagDeleted => "tag.deleted",
+            WebhookEventType::ItemCreated => "item.created",
+            WebhookEventType::ItemUpdated => "item.updated",
+            WebhookEventType::ItemDeleted => "item.deleted",
+            WebhookEventType::StockChanged => "stock.changed",
+        }
+    }
+}
+
+/// A change, which is sent to the subscribed webhooks as `{"type": ..., "data": ...}`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum WebhookEvent {
+    #[serde(rename = "ping")]
+    Ping,
+    #[serde(rename = "category.created")]
+    CategoryCreated(Category),
+    #[serde(rename = "category.updated")]
+    CategoryUpdated(Category),
+    #[serde(rename = "category.deleted")]
+    CategoryDeleted { id: CategoryId },
+    #[serde(rename = "tag.created")]
+    TagCreated(Tag),
+    #[serde(rename = "tag.updated")]
+    TagUpdated(Tag),
+    #[serde(rename = "tag.deleted")]
+    TagDeleted { id: TagId },
+    #[serde(rename = "item.created")]
+    ItemCreated(Item),
+    #[serde(rename = "item.updated")]
+    ItemUpdated(Item),
+    #[serde(rename = "item.deleted")]
+    ItemDeleted { id: ItemId },
+    /// Number of the objects of an item, which are in stock.
+    #[serde(rename = "stock.changed")]
+    StockChanged { item_id: ItemId, in_stock: i64 },
+}
+
+impl WebhookEvent {
+    pub fn event_type(&self) -> WebhookEventType {
+        match self {
+            WebhookEvent::Ping => WebhookEventType::Ping,
+            WebhookEvent::CategoryCreated(_) => WebhookEventType::CategoryCreated,
+            WebhookEvent::CategoryUpdated(_) => WebhookEventType::CategoryUpdated,
+            WebhookEvent::CategoryDeleted { .. } => WebhookEventType::CategoryDeleted,
+            WebhookEvent::TagCreated(_) => WebhookEventType::TagCreated,
+            WebhookEvent::TagUpdated(_) => WebhookEventType::TagUpdated,
+            WebhookEvent::TagDeleted { .. } => WebhookEventType::TagDeleted,
+            WebhookEvent::ItemCreated(_) => WebhookEventType::ItemCreated,
+            WebhookEvent::ItemUpdated(_) => WebhookEventType::ItemUpdated,
+            WebhookEvent::ItemDeleted { .. } => WebhookEventType::ItemDeleted,
+            WebhookEvent::StockChanged { .. } => WebhookEventType::StockChanged,
+        }
+    }
+}
+
+/// A subscription of an external system to the changes, its secret isn't shown after it's added.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Webhook {
+    pub id: WebhookId,
+    pub url: String,
+    pub event_types: Vec<WebhookEventType>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type), sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase"))]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    /// Waiting for the first attempt or a retry.
+    Pending,
+    Delivered,
+    /// All of the attempts have failed.
+    Failed,
+}
+
+/// An event sent, or to be sent, to a webhook, with the outcome of the last attempt.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebhookDelivery {
+    pub id: WebhookDeliveryId,
+    pub webhook_id: WebhookId,
+    pub event_type: WebhookEventType,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// A workaround module for the arrays of [`WebhookEventType`], which sqlx can't derive.
+#[cfg(feature = "ssr")]
+mod derive_workaround {
+    use sqlx::postgres::PgHasArrayType;
+
+    use super::WebhookEventType;
+
+    impl PgHasArrayType for WebhookEventType {
+        fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::with_name("_webhook_event_type")
+        }
+    }
+}
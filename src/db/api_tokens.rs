@@ -0,0 +1,73 @@
+use crate::data::api_tokens::{ApiToken, ApiTokenId, ApiTokenScope};
+
+use super::{ResultDb, Repository};
+
+#[async_trait::async_trait]
+pub trait ApiTokenDB {
+    async fn get_api_tokens(&self) -> ResultDb<Vec<ApiToken>>;
+    async fn add_api_token(&self, name: &str, scope: ApiTokenScope, secret_hash: &str) -> ResultDb<ApiToken>;
+    async fn revoke_api_token(&self, api_token_id: ApiTokenId) -> ResultDb<()>;
+    /// Finds the token with the secret's hash, marking it as used.
+    async fn use_api_token(&self, secret_hash: &str) -> ResultDb<Option<ApiToken>>;
+}
+
+#[async_trait::async_trait]
+impl ApiTokenDB for Repository {
+    async fn get_api_tokens(&self) -> ResultDb<Vec<ApiToken>> {
+        Ok(sqlx::query_as!(
+            ApiToken,
+            r#"
+                SELECT id, name, scope as "scope: ApiTokenScope", created_at, last_used_at
+                FROM api_token
+                ORDER BY created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_api_token(&self, name: &str, scope: ApiTokenScope, secret_hash: &str) -> ResultDb<ApiToken> {
+        Ok(sqlx::query_as!(
+            ApiToken,
+            r#"
+                INSERT INTO api_token (name, scope, secret_hash)
+                VALUES ($1, $2, $3)
+                RETURNING id, name, scope as "scope: ApiTokenScope", created_at, last_used_at
+            "#,
+            name,
+            scope as _,
+            secret_hash
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn revoke_api_token(&self, api_token_id: ApiTokenId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM api_token
+                WHERE id = $1
+            ",
+            api_token_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn use_api_token(&self, secret_hash: &str) -> ResultDb<Option<ApiToken>> {
+        Ok(sqlx::query_as!(
+            ApiToken,
+            r#"
+                UPDATE api_token
+                SET last_used_at = now()
+                WHERE secret_hash = $1
+                RETURNING id, name, scope as "scope: ApiTokenScope", created_at, last_used_at
+            "#,
+            secret_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+}
@@ -0,0 +1,98 @@
+use crate::data::{attachments::{ItemAttachment, ItemAttachmentId}, item::ItemId};
+
+use super::{ResultDb, Repository, DbError};
+
+#[async_trait::async_trait]
+pub trait AttachmentDB {
+    async fn get_item_attachments(&self, item_id: ItemId) -> ResultDb<Vec<ItemAttachment>>;
+    async fn add_item_attachment(
+        &self,
+        item_id: ItemId,
+        file_name: &str,
+        content_type: &str,
+        size: i64,
+        stored_name: &str,
+        thumbnail_name: Option<&str>,
+    ) -> ResultDb<ItemAttachment>;
+    /// Removes the attachment record, returning it so the files can be removed too.
+    async fn remove_item_attachment(&self, attachment_id: ItemAttachmentId) -> ResultDb<ItemAttachment>;
+    /// Names of the files of all the attachments, the thumbnails' included.
+    async fn get_stored_names(&self) -> ResultDb<Vec<String>>;
+}
+
+#[async_trait::async_trait]
+impl AttachmentDB for Repository {
+    async fn get_item_attachments(&self, item_id: ItemId) -> ResultDb<Vec<ItemAttachment>> {
+        Ok(sqlx::query_as!(
+            ItemAttachment,
+            "
+                SELECT id, item_id, file_name, content_type, size, stored_name, thumbnail_name
+                FROM item_attachment
+                WHERE item_id = $1 AND NOT removal_pending('item_attachment', id)
+                ORDER BY created_at
+            ",
+            item_id as _
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_item_attachment(
+        &self,
+        item_id: ItemId,
+        file_name: &str,
+        content_type: &str,
+        size: i64,
+        stored_name: &str,
+        thumbnail_name: Option<&str>,
+    ) -> ResultDb<ItemAttachment> {
+        sqlx::query_as!(
+            ItemAttachment,
+            "
+                INSERT INTO item_attachment (item_id, file_name, content_type, size, stored_name, thumbnail_name)
+                SELECT $1, $2, $3, $4, $5, $6
+                WHERE NOT removal_pending('item', $1)
+                RETURNING id, item_id, file_name, content_type, size, stored_name, thumbnail_name
+            ",
+            item_id as _,
+            file_name,
+            content_type,
+            size,
+            stored_name,
+            thumbnail_name
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn remove_item_attachment(&self, attachment_id: ItemAttachmentId) -> ResultDb<ItemAttachment> {
+        sqlx::query_as!(
+            ItemAttachment,
+            "
+                DELETE FROM item_attachment
+                WHERE id = $1
+                RETURNING id, item_id, file_name, content_type, size, stored_name, thumbnail_name
+            ",
+            attachment_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn get_stored_names(&self) -> ResultDb<Vec<String>> {
+        Ok(sqlx::query_scalar!(
+            r#"
+                SELECT stored_name as "stored_name!"
+                FROM item_attachment
+                UNION ALL
+                SELECT thumbnail_name as "stored_name!"
+                FROM item_attachment
+                WHERE thumbnail_name IS NOT NULL
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
@@ -0,0 +1,127 @@
+use sqlx::types::Json;
+
+use crate::data::{attributes::{CategoryAttribute, CategoryAttributeId, AttributeKind, AttributeInput, AttributeInputError, ItemAttributes}, categories::CategoryId, item::ItemId, webhooks::WebhookEvent};
+
+use super::{ResultDb, Repository, DbError, item::ItemsDB, webhooks::enqueue_event};
+
+#[async_trait::async_trait]
+pub trait AttributeDB {
+    async fn get_attributes(&self) -> ResultDb<Vec<CategoryAttribute>>;
+    async fn add_category_attribute(
+        &self,
+        category_id: CategoryId,
+        name: &str,
+        kind: AttributeKind,
+        unit: Option<&str>,
+        options: &[String],
+    ) -> ResultDb<CategoryAttribute>;
+    async fn remove_category_attribute(&self, attribute_id: CategoryAttributeId) -> ResultDb<()>;
+    /// Validates the values against the attributes of item's category and replaces item's attributes.
+    ///
+    /// Empty values are left out.
+    async fn set_item_attributes(&self, item_id: ItemId, values: &[AttributeInput]) -> ResultDb<ItemAttributes>;
+}
+
+#[async_trait::async_trait]
+impl AttributeDB for Repository {
+    async fn get_attributes(&self) -> ResultDb<Vec<CategoryAttribute>> {
+        Ok(sqlx::query_as!(
+            CategoryAttribute,
+            r#"
+                SELECT id, category_id, name, kind as "kind: AttributeKind", unit, options
+                FROM category_attribute
+                WHERE NOT removal_pending('category_attribute', id)
+                ORDER BY name
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_category_attribute(
+        &self,
+        category_id: CategoryId,
+        name: &str,
+        kind: AttributeKind,
+        unit: Option<&str>,
+        options: &[String],
+    ) -> ResultDb<CategoryAttribute> {
+        sqlx::query_as!(
+            CategoryAttribute,
+            r#"
+                INSERT INTO category_attribute (category_id, name, kind, unit, options)
+                SELECT $1, $2, $3, $4, $5
+                WHERE NOT removal_pending('category', $1)
+                RETURNING id, category_id, name, kind as "kind: AttributeKind", unit, options
+            "#,
+            category_id as _,
+            name,
+            kind as _,
+            unit,
+            options
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn remove_category_attribute(&self, attribute_id: CategoryAttributeId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM category_attribute
+                WHERE id = $1
+            ",
+            attribute_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_item_attributes(&self, item_id: ItemId, values: &[AttributeInput]) -> ResultDb<ItemAttributes> {
+        let attributes = sqlx::query_as!(
+            CategoryAttribute,
+            r#"
+                SELECT
+                    category_attribute.id,
+                    category_attribute.category_id,
+                    category_attribute.name,
+                    category_attribute.kind as "kind: AttributeKind",
+                    category_attribute.unit,
+                    category_attribute.options
+                FROM category_attribute
+                INNER JOIN item ON item.category_id = category_attribute.category_id
+                WHERE item.id = $1 AND NOT removal_pending('category_attribute', category_attribute.id)
+            "#,
+            item_id as _
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let parsed = ItemAttributes::parse(&attributes, values).map_err(|error| match error {
+            AttributeInputError::UnknownAttribute => DbError::ItemNotFound,
+            AttributeInputError::InvalidValue(name) => DbError::InvalidAttribute(name),
+        })?;
+
+        let updated = sqlx::query!(
+            "
+                UPDATE item
+                SET attributes = $2
+                WHERE id = $1 AND NOT removal_pending('item', id)
+            ",
+            item_id as _,
+            Json(&parsed) as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(DbError::ItemNotFound);
+        }
+
+        enqueue_event(&self.pool, &WebhookEvent::ItemUpdated(self.get_item(item_id).await?)).await?;
+
+        Ok(parsed)
+    }
+}
@@ -1,12 +1,102 @@
-use crate::data::categories::{Category, CategoryId};
+use sqlx::PgExecutor;
 
-use super::{ResultDb, Repository};
+use crate::data::{categories::{Category, CategoryId, CategoryTree, ResolvedSlug}, webhooks::WebhookEvent};
+
+use super::{ResultDb, Repository, DbError, Precondition, check_precondition, webhooks::enqueue_event};
+
+/// Locks the category for the rest of the transaction and checks the change's condition on it, if there is one.
+pub(super) async fn lock_category<'e>(executor: impl PgExecutor<'e>, category_id: CategoryId, precondition: Option<&dyn Precondition<Category>>) -> ResultDb<()> {
+    let current = sqlx::query_as!(
+        Category,
+        r#"
+            SELECT id, name, parent_id as "parent_id: CategoryId", slug
+            FROM category
+            WHERE id = $1 AND NOT removal_pending('category', id)
+            FOR UPDATE
+        "#,
+        category_id as _
+    )
+    .fetch_optional(executor)
+    .await?
+    .ok_or(DbError::ItemNotFound)?;
+
+    check_precondition(precondition, &current)
+}
+
+/// Fails with [`DbError::HasSubcategories`], if the category has any, they'd fail its removal on the foreign key anyway.
+///
+/// Takes an executor, so that it can be a part of a bigger transaction.
+pub(super) async fn check_no_subcategories<'e>(executor: impl PgExecutor<'e>, category_id: CategoryId) -> ResultDb<()> {
+    let has_subcategories = sqlx::query_scalar!(
+        r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM category
+                WHERE parent_id = $1
+            ) as "has_subcategories!"
+        "#,
+        category_id as _
+    )
+    .fetch_one(executor)
+    .await?;
+
+    if has_subcategories {
+        return Err(DbError::HasSubcategories);
+    }
+    Ok(())
+}
+
+/// Makes a slug out of the category name, which isn't taken by other categories, current or former.
+///
+/// Taken slugs get a number appended, starting with 2.
+async fn unique_slug<'e>(executor: impl PgExecutor<'e>, category_name: &str, category_id: Option<CategoryId>) -> ResultDb<String> {
+    Ok(sqlx::query_scalar!(
+        r#"
+            SELECT candidate as "candidate!"
+            FROM
+                generate_series(
+                    1,
+                    (SELECT COUNT(*) FROM category) + (SELECT COUNT(*) FROM category_slug_history) + 1
+                ) AS number,
+                LATERAL (
+                    SELECT CASE number WHEN 1 THEN slugify($1) ELSE slugify($1) || '-' || number END AS candidate
+                ) AS candidates
+            WHERE
+                NOT EXISTS (
+                    SELECT 1
+                    FROM category
+                    WHERE category.slug = candidate AND category.id IS DISTINCT FROM $2
+                )
+            AND
+                NOT EXISTS (
+                    SELECT 1
+                    FROM category_slug_history
+                    WHERE category_slug_history.slug = candidate AND category_slug_history.category_id IS DISTINCT FROM $2
+                )
+            ORDER BY number
+            LIMIT 1
+        "#,
+        category_name,
+        category_id as _
+    )
+    .fetch_one(executor)
+    .await?)
+}
 
 #[async_trait::async_trait]
 pub trait CategoryDB {
     async fn get_categories(&self) -> ResultDb<Vec<Category>>;
-    async fn add_category(&self, category_name: &str) -> ResultDb<Category>;
-    async fn remove_category(&self, category_id: CategoryId) -> ResultDb<()>;
+    async fn get_category(&self, category_id: CategoryId) -> ResultDb<Category>;
+    async fn get_category_tree(&self) -> ResultDb<Vec<CategoryTree>>;
+    /// Path from the root category down to the given one, inclusive.
+    async fn get_category_path(&self, category_id: CategoryId) -> ResultDb<Vec<Category>>;
+    /// Finds a category by its current slug, or by a former one, if it was renamed.
+    async fn resolve_category_slug(&self, slug: &str) -> ResultDb<ResolvedSlug>;
+    async fn add_category(&self, category_name: &str, parent_id: Option<CategoryId>) -> ResultDb<Category>;
+    /// Renames a category, its former slug is kept to redirect the old links.
+    async fn rename_category(&self, category_id: CategoryId, category_name: &str, precondition: Option<&dyn Precondition<Category>>) -> ResultDb<Category>;
+    /// Removes the category, unless it has sub-categories or items.
+    async fn remove_category(&self, category_id: CategoryId, precondition: Option<&dyn Precondition<Category>>) -> ResultDb<()>;
 }
 
 #[async_trait::async_trait]
@@ -14,40 +104,269 @@ impl CategoryDB for Repository {
     async fn get_categories(&self) -> ResultDb<Vec<Category>> {
         Ok(sqlx::query_as!(
             Category,
-            "
-                SELECT id, name
+            r#"
+                SELECT id, name, parent_id as "parent_id: CategoryId", slug
                 FROM category
-            "
+                WHERE NOT removal_pending('category', id)
+            "#
         )
         .fetch_all(&self.pool)
         .await?)
     }
 
-    async fn add_category(&self, category_name: &str) -> ResultDb<Category> {
+    async fn get_category(&self, category_id: CategoryId) -> ResultDb<Category> {
+        sqlx::query_as!(
+            Category,
+            r#"
+                SELECT id, name, parent_id as "parent_id: CategoryId", slug
+                FROM category
+                WHERE id = $1 AND NOT removal_pending('category', id)
+            "#,
+            category_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn get_category_tree(&self) -> ResultDb<Vec<CategoryTree>> {
+        let categories = sqlx::query_as!(
+            Category,
+            r#"
+                WITH RECURSIVE category_tree AS (
+                    SELECT id, name, parent_id, slug, ARRAY[name] AS path
+                    FROM category
+                    WHERE parent_id IS NULL AND NOT removal_pending('category', id)
+
+                    UNION ALL
+
+                    SELECT category.id, category.name, category.parent_id, category.slug, category_tree.path || category.name
+                    FROM category
+                    INNER JOIN category_tree ON category.parent_id = category_tree.id
+                    WHERE NOT removal_pending('category', category.id)
+                )
+
+                SELECT id as "id!", name as "name!", parent_id as "parent_id: CategoryId", slug as "slug!"
+                FROM category_tree
+                ORDER BY path
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(CategoryTree::from_flat(categories))
+    }
+
+    async fn get_category_path(&self, category_id: CategoryId) -> ResultDb<Vec<Category>> {
         Ok(sqlx::query_as!(
             Category,
-            "
-                INSERT INTO category (name)
-                VALUES ($1)
-                RETURNING id, name
-            ",
-            category_name
+            r#"
+                WITH RECURSIVE category_path AS (
+                    SELECT id, name, parent_id, slug, 0 AS depth
+                    FROM category
+                    WHERE id = $1 AND NOT removal_pending('category', id)
+
+                    UNION ALL
+
+                    SELECT category.id, category.name, category.parent_id, category.slug, category_path.depth + 1
+                    FROM category
+                    INNER JOIN category_path ON category.id = category_path.parent_id
+                )
+
+                SELECT id as "id!", name as "name!", parent_id as "parent_id: CategoryId", slug as "slug!"
+                FROM category_path
+                ORDER BY depth DESC
+            "#,
+            category_id as _
         )
-        .fetch_one(&self.pool)
+        .fetch_all(&self.pool)
         .await?)
     }
 
-    async fn remove_category(&self, category_id: CategoryId) -> ResultDb<()> {
+    async fn resolve_category_slug(&self, slug: &str) -> ResultDb<ResolvedSlug> {
+        sqlx::query!(
+            r#"
+                SELECT
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    category.slug <> $1 as "outdated!"
+                FROM
+                    category
+                WHERE
+                    (
+                        category.slug = $1
+                        OR category.id = (SELECT category_id FROM category_slug_history WHERE slug = $1)
+                    )
+                AND
+                    NOT removal_pending('category', category.id)
+                ORDER BY "outdated!"
+                LIMIT 1
+            "#,
+            slug
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|found| ResolvedSlug { category: found.category, outdated: found.outdated })
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn add_category(&self, category_name: &str, parent_id: Option<CategoryId>) -> ResultDb<Category> {
+        let mut transaction = self.pool.begin().await?;
+
+        if let Some(parent_id) = parent_id {
+            lock_category(&mut *transaction, parent_id, None).await?;
+        }
+
+        let slug = unique_slug(&mut *transaction, category_name, None).await?;
+        let category = sqlx::query_as!(
+            Category,
+            r#"
+                INSERT INTO category (name, parent_id, slug)
+                VALUES ($1, $2, $3)
+                RETURNING id, name, parent_id as "parent_id: CategoryId", slug
+            "#,
+            category_name,
+            parent_id as _,
+            slug
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        enqueue_event(&mut *transaction, &WebhookEvent::CategoryCreated(category.clone())).await?;
+
+        transaction.commit().await?;
+
+        Ok(category)
+    }
+
+    async fn rename_category(&self, category_id: CategoryId, category_name: &str, precondition: Option<&dyn Precondition<Category>>) -> ResultDb<Category> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_category(&mut *transaction, category_id, precondition).await?;
+
+        let slug = unique_slug(&mut *transaction, category_name, Some(category_id)).await?;
+
+        // The former slug becomes a redirect, and the new one stops being one, if the category is renamed back
         sqlx::query!(
+            "
+                INSERT INTO category_slug_history (slug, category_id)
+                SELECT slug, id
+                FROM category
+                WHERE id = $1 AND slug <> $2
+                ON CONFLICT (slug) DO NOTHING
+            ",
+            category_id as _,
+            slug
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                DELETE FROM category_slug_history
+                WHERE slug = $1
+            ",
+            slug
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let category = sqlx::query_as!(
+            Category,
+            r#"
+                UPDATE category
+                SET name = $2, slug = $3
+                WHERE id = $1 AND NOT removal_pending('category', id)
+                RETURNING id, name, parent_id as "parent_id: CategoryId", slug
+            "#,
+            category_id as _,
+            category_name,
+            slug
+        )
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        enqueue_event(&mut *transaction, &WebhookEvent::CategoryUpdated(category.clone())).await?;
+
+        transaction.commit().await?;
+
+        Ok(category)
+    }
+
+    async fn remove_category(&self, category_id: CategoryId, precondition: Option<&dyn Precondition<Category>>) -> ResultDb<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_category(&mut *transaction, category_id, precondition).await?;
+        check_no_subcategories(&mut *transaction, category_id).await?;
+
+        let removed = sqlx::query!(
             "
                 DELETE FROM category
                 WHERE id = $1
             ",
             category_id as _
         )
-        .execute(&self.pool)
+        .execute(&mut *transaction)
         .await?;
 
+        if removed.rows_affected() == 0 {
+            return Err(DbError::ItemNotFound);
+        }
+
+        enqueue_event(&mut *transaction, &WebhookEvent::CategoryDeleted { id: category_id }).await?;
+
+        transaction.commit().await?;
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::db::fixtures::with_category;
+
+    #[sqlx::test]
+    async fn category_with_subcategories_is_kept(pool: PgPool) {
+        let (db, parent) = with_category(pool).await;
+        let child = db.add_category("Светри", Some(parent.id)).await.unwrap();
+
+        assert!(matches!(db.remove_category(parent.id, None).await, Err(DbError::HasSubcategories)));
+
+        db.remove_category(child.id, None).await.unwrap();
+        db.remove_category(parent.id, None).await.unwrap();
+        assert!(db.get_categories().await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn slugs_are_unique(pool: PgPool) {
+        let (db, first) = with_category(pool).await;
+        let second = db.add_category("Одяг", None).await.unwrap();
+
+        assert!(!first.slug.is_empty());
+        assert_eq!(second.slug, format!("{}-2", first.slug));
+    }
+
+    #[sqlx::test]
+    async fn former_slugs_redirect(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let renamed = db.rename_category(category.id, "Взуття", None).await.unwrap();
+        assert_ne!(renamed.slug, category.slug);
+
+        let resolved = db.resolve_category_slug(&category.slug).await.unwrap();
+        assert_eq!(resolved.category, renamed);
+        assert!(resolved.outdated);
+        assert!(!db.resolve_category_slug(&renamed.slug).await.unwrap().outdated);
+
+        // Another category can't take the former slug, while it redirects
+        let other = db.add_category("Одяг", None).await.unwrap();
+        assert_ne!(other.slug, category.slug);
+
+        // Renamed back, the category gets its slug back
+        let renamed_back = db.rename_category(category.id, "Одяг", None).await.unwrap();
+        assert_eq!(renamed_back.slug, category.slug);
+        assert!(!db.resolve_category_slug(&category.slug).await.unwrap().outdated);
+    }
+}
@@ -0,0 +1,158 @@
+use sqlx::types::Json;
+
+use crate::data::deletions::{Deletion, PendingDeletion, PendingDeletionId};
+
+use super::{
+    ResultDb, Repository, DbError, AnyPrecondition,
+    categories::{lock_category, check_no_subcategories},
+    item::{lock_item, lock_tag, lock_item_object},
+};
+
+#[async_trait::async_trait]
+pub trait DeletionDB {
+    /// Queues the removal to be carried out after `delay_secs`, if the removed entity is there and isn't being removed already.
+    ///
+    /// The condition is checked on the entity, if it can be locked, i.e. it's a category, tag, item or item object.
+    async fn add_pending_deletion(&self, deletion: &Deletion, delay_secs: f64, precondition: Option<&dyn AnyPrecondition>) -> ResultDb<PendingDeletion>;
+    async fn get_pending_deletion(&self, deletion_id: PendingDeletionId) -> ResultDb<Deletion>;
+    /// Takes the removal off the queue, either to undo it, or to carry it out right away.
+    async fn take_pending_deletion(&self, deletion_id: PendingDeletionId) -> ResultDb<Deletion>;
+    /// Takes the removals, which can't be undone anymore, off the queue.
+    async fn take_due_deletions(&self, limit: i64) -> ResultDb<Vec<Deletion>>;
+}
+
+#[async_trait::async_trait]
+impl DeletionDB for Repository {
+    async fn add_pending_deletion(&self, deletion: &Deletion, delay_secs: f64, precondition: Option<&dyn AnyPrecondition>) -> ResultDb<PendingDeletion> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Locked until the removal is queued, so that it's neither changed after the check, nor sold meanwhile
+        match *deletion {
+            Deletion::Category { category_id } => {
+                lock_category(&mut *transaction, category_id, precondition.map(|precondition| precondition.for_category())).await?;
+                // Refused right away, rather than once the undo window passes
+                check_no_subcategories(&mut *transaction, category_id).await?;
+            },
+            Deletion::Tag { tag_id } => lock_tag(&mut *transaction, tag_id, precondition.map(|precondition| precondition.for_tag())).await?,
+            Deletion::Item { item_id } | Deletion::ItemTag { item_id, .. } => lock_item(&mut transaction, self, item_id, precondition.map(|precondition| precondition.for_item())).await?,
+            Deletion::ItemObject { item_object_id } => lock_item_object(&mut *transaction, item_object_id, precondition.map(|precondition| precondition.for_item_object())).await?,
+            _ => {},
+        }
+
+        let pending = sqlx::query_as!(
+            PendingDeletion,
+            "
+                INSERT INTO pending_deletion (deletion, delete_at)
+                VALUES ($1, now() + make_interval(secs => $2))
+                RETURNING id, delete_at
+            ",
+            Json(deletion) as _,
+            delay_secs
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(pending)
+    }
+
+    async fn get_pending_deletion(&self, deletion_id: PendingDeletionId) -> ResultDb<Deletion> {
+        let deletion = sqlx::query_scalar!(
+            r#"
+                SELECT deletion as "deletion: Json<Deletion>"
+                FROM pending_deletion
+                WHERE id = $1
+            "#,
+            deletion_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        Ok(deletion.0)
+    }
+
+    async fn take_pending_deletion(&self, deletion_id: PendingDeletionId) -> ResultDb<Deletion> {
+        let deletion = sqlx::query_scalar!(
+            r#"
+                DELETE FROM pending_deletion
+                WHERE id = $1
+                RETURNING deletion as "deletion: Json<Deletion>"
+            "#,
+            deletion_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        Ok(deletion.0)
+    }
+
+    async fn take_due_deletions(&self, limit: i64) -> ResultDb<Vec<Deletion>> {
+        // Skipping the locked rows lets several servers share the queue
+        let deletions = sqlx::query_scalar!(
+            r#"
+                WITH due AS (
+                    SELECT id
+                    FROM pending_deletion
+                    WHERE delete_at <= now()
+                    ORDER BY delete_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+
+                DELETE FROM pending_deletion
+                USING due
+                WHERE pending_deletion.id = due.id
+                RETURNING pending_deletion.deletion as "deletion: Json<Deletion>"
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deletions.into_iter().map(|deletion| deletion.0).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        data::sales::CartLine,
+        db::{fixtures::with_category, item::ItemsDB, sales::SaleDB},
+    };
+
+    #[sqlx::test]
+    async fn pending_item_is_hidden_until_undone(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let item = db.add_item("Светр", category.id).await.unwrap();
+
+        let pending = db.add_pending_deletion(&Deletion::Item { item_id: item.id }, 60.0, None).await.unwrap();
+        assert!(matches!(db.get_item(item.id).await, Err(DbError::ItemNotFound)));
+        assert!(db.list_items(None, None, None, None).await.unwrap().is_empty());
+        // Neither changed, nor queued for removal twice
+        assert!(matches!(db.update_item(item.id, "Кофта", category.id, None).await, Err(DbError::ItemNotFound)));
+        assert!(matches!(
+            db.add_pending_deletion(&Deletion::Item { item_id: item.id }, 60.0, None).await,
+            Err(DbError::ItemNotFound)
+        ));
+
+        db.take_pending_deletion(pending.id).await.unwrap();
+        assert_eq!(db.get_item(item.id).await.unwrap().name, "Светр");
+    }
+
+    #[sqlx::test]
+    async fn pending_object_is_not_sold(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let item = db.add_item("Светр", category.id).await.unwrap();
+        let object = db.add_item_object(item.id, "SW-1", None, None).await.unwrap();
+
+        db.add_pending_deletion(&Deletion::ItemObject { item_object_id: object.id }, 60.0, None).await.unwrap();
+        let cart = [CartLine { object_id: object.id, price: 100 }];
+        assert!(matches!(db.add_sale(&cart).await, Err(DbError::AlreadySold)));
+    }
+}
@@ -1,13 +1,20 @@
-use futures::{StreamExt, TryStreamExt};
+use futures::{future::BoxFuture, StreamExt, TryStreamExt};
+use sqlx::{types::Json, Connection, PgConnection, PgExecutor};
 
-use crate::data::{item::{TagId, Item, Tag, ItemId, ItemObject, ItemIncomplete, ItemObjectId}, categories::Category};
+use crate::data::{item::{TagId, Item, Tag, ItemId, ItemObject, ItemVariant, ItemVariantId, ItemIncomplete, ItemObjectId, ItemObjectLookup, TagGroup, TagGroupId, GroupedTags, Suggestion}, categories::{Category, CategoryId}, attributes::{AttributeFilter, ItemAttributes}, suppliers::PurchaseOrderLineId, locations::LocationId, search::{SearchResults, switch_keyboard_layout}, webhooks::WebhookEvent, bulk::{BulkOutcome, BulkFailure}};
 
-use super::{ResultDb, Repository};
+use super::{ResultDb, Repository, DbError, Precondition, check_precondition, categories::lock_category, webhooks::{enqueue_event, enqueue_stock_changed}};
+
+/// Fewer exact matches than this make the search look for corrected ones.
+const FEW_MATCHES: usize = 3;
+/// Minimal word similarity of an item's name to a misspelled query.
+const MIN_SIMILARITY: f64 = 0.4;
 
 impl ItemIncomplete {
     pub async fn fetch_related(self, repo: &impl ItemsDB) -> ResultDb<Item> {
         let objects = repo.get_item_objects(self.id).await?;
         let tags = repo.get_item_tags(self.id).await?;
+        let variants = repo.get_item_variants(self.id).await?;
 
         Ok(Item {
             id: self.id,
@@ -15,97 +22,574 @@ impl ItemIncomplete {
             category: self.category,
             tags,
             objects,
+            attributes: self.attributes,
+            variants,
         })
     }
 }
 
+/// Inserts an item object at a location, recording the purchase order line it was received from, if any.
+/// The variant, if set, must be one of the item's.
+///
+/// Takes an executor, so that it can be a part of a bigger transaction.
+pub(super) async fn insert_item_object<'e>(
+    executor: impl PgExecutor<'e>,
+    item_id: ItemId,
+    item_code: &str,
+    location_id: Option<LocationId>,
+    variant_id: Option<ItemVariantId>,
+    purchase_order_line_id: Option<PurchaseOrderLineId>,
+) -> ResultDb<ItemObject> {
+    Ok(sqlx::query_as!(
+        ItemObject,
+        r#"
+            INSERT INTO item_objects (item_code, item_id, location_id, variant_id, purchase_order_line_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, item_code, location_id as "location_id: LocationId", variant_id as "variant_id: ItemVariantId"
+        "#,
+        item_code,
+        item_id as _,
+        location_id as _,
+        variant_id as _,
+        purchase_order_line_id as _
+    )
+    .fetch_one(executor)
+    .await?)
+}
+
+/// Removes an item object, which is in stock, returns the item it was of.
+///
+/// Takes an executor, so that it can be a part of a bigger transaction.
+pub(super) async fn delete_item_object<'e>(executor: impl PgExecutor<'e>, item_object_id: ItemObjectId) -> ResultDb<ItemId> {
+    sqlx::query_scalar!(
+        r#"
+            DELETE FROM item_objects
+            WHERE id = $1 AND sold_at IS NULL
+            RETURNING item_id as "item_id: ItemId"
+        "#,
+        item_object_id as _
+    )
+    .fetch_optional(executor)
+    .await?
+    .ok_or(DbError::ItemNotFound)
+}
+
+/// Locks the item for the rest of the transaction and checks the change's condition on it, if there is one.
+pub(super) async fn lock_item(conn: &mut PgConnection, repo: &Repository, item_id: ItemId, precondition: Option<&dyn Precondition<Item>>) -> ResultDb<()> {
+    sqlx::query!(
+        "
+            SELECT id
+            FROM item
+            WHERE id = $1 AND NOT removal_pending('item', id)
+            FOR UPDATE
+        ",
+        item_id as _
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(DbError::ItemNotFound)?;
+
+    // The lock keeps the item, its tags and objects from changing, so it can be read outside of the transaction
+    match precondition {
+        Some(_) => check_precondition(precondition, &repo.get_item(item_id).await?),
+        None => Ok(()),
+    }
+}
+
+/// Locks the tag for the rest of the transaction and checks the change's condition on it, if there is one.
+pub(super) async fn lock_tag<'e>(executor: impl PgExecutor<'e>, tag_id: TagId, precondition: Option<&dyn Precondition<Tag>>) -> ResultDb<()> {
+    let current = sqlx::query_as!(
+        Tag,
+        r#"
+            SELECT id, name, group_id as "group_id: TagGroupId", color
+            FROM tag
+            WHERE id = $1 AND NOT removal_pending('tag', id)
+            FOR UPDATE
+        "#,
+        tag_id as _
+    )
+    .fetch_optional(executor)
+    .await?
+    .ok_or(DbError::ItemNotFound)?;
+
+    check_precondition(precondition, &current)
+}
+
+/// Locks the item object for the rest of the transaction and checks the change's condition on it, if there is one.
+pub(super) async fn lock_item_object<'e>(executor: impl PgExecutor<'e>, item_object_id: ItemObjectId, precondition: Option<&dyn Precondition<ItemObject>>) -> ResultDb<()> {
+    let current = sqlx::query_as!(
+        ItemObject,
+        r#"
+            SELECT id, item_code, location_id as "location_id: LocationId", variant_id as "variant_id: ItemVariantId"
+            FROM item_objects
+            WHERE id = $1 AND sold_at IS NULL AND NOT removal_pending('item_object', id)
+            FOR UPDATE
+        "#,
+        item_object_id as _
+    )
+    .fetch_optional(executor)
+    .await?
+    .ok_or(DbError::ItemNotFound)?;
+
+    check_precondition(precondition, &current)
+}
+
+/// Filters of [`ItemsDB::search_items`], which are applied the same way to the exact and the corrected matches.
+struct ItemFilters<'a> {
+    tags_filtered: &'a [TagId],
+    category: Option<CategoryId>,
+    include_subcategories: bool,
+    attribute_filters: &'a [AttributeFilter],
+    location: Option<LocationId>,
+    tag: Option<TagId>,
+}
+
+/// Items containing the query in their names, or similar enough to it, if `min_similarity` is set.
+async fn find_items<'e>(
+    executor: impl PgExecutor<'e>,
+    query: &str,
+    filters: &ItemFilters<'_>,
+    min_similarity: Option<f64>,
+) -> ResultDb<Vec<ItemIncomplete>> {
+    Ok(sqlx::query_as!(
+        ItemIncomplete,
+        r#"
+            WITH RECURSIVE chosen_categories AS (
+                SELECT category.id
+                FROM category
+                WHERE category.id = $3
+
+                UNION
+
+                SELECT category.id
+                FROM category
+                INNER JOIN chosen_categories ON category.parent_id = chosen_categories.id
+                WHERE $4
+            ),
+
+            items_ids_with_tags AS (
+                SELECT
+                    item_tag.item_id
+                FROM
+                    item_tag
+                WHERE
+                    item_tag.tag_id = ANY($2)
+                    AND NOT removal_pending(jsonb_build_object('kind', 'item_tag', 'item_id', item_tag.item_id, 'tag_id', item_tag.tag_id))
+            )
+            
+            SELECT
+                item.id, item.name,
+                (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                item.attributes as "attributes: Json<ItemAttributes>"
+            FROM
+                item
+
+            INNER JOIN
+                category ON category.id = item.category_id
+
+            WHERE
+                NOT removal_pending('item', item.id)
+            AND
+                (
+                    item.name LIKE '%' || $1 || '%'
+                    OR ($8::float8 IS NOT NULL AND word_similarity($1, item.name) >= $8)
+                )
+            AND
+                NOT item.id in (SELECT item_id from items_ids_with_tags)
+            AND
+                ($3::uuid IS NULL OR category.id IN (SELECT id FROM chosen_categories))
+            AND
+                NOT EXISTS (
+                    SELECT 1
+                    FROM
+                        jsonb_to_recordset($5::jsonb) AS filter(id uuid, value text, min float8, max float8)
+                    INNER JOIN
+                        category_attribute ON category_attribute.id = filter.id
+                    WHERE
+                        NOT COALESCE(
+                            (
+                                filter.value IS NULL
+                                OR CASE category_attribute.kind
+                                    WHEN 'text' THEN (item.attributes ->> filter.id::text) ILIKE '%' || filter.value || '%'
+                                    ELSE item.attributes ->> filter.id::text = filter.value
+                                END
+                            )
+                            AND (
+                                filter.min IS NULL
+                                OR CASE category_attribute.kind
+                                    WHEN 'number' THEN (item.attributes ->> filter.id::text)::float8 >= filter.min
+                                    ELSE false
+                                END
+                            )
+                            AND (
+                                filter.max IS NULL
+                                OR CASE category_attribute.kind
+                                    WHEN 'number' THEN (item.attributes ->> filter.id::text)::float8 <= filter.max
+                                    ELSE false
+                                END
+                            ),
+                            false
+                        )
+                )
+            AND (
+                $6::uuid IS NULL
+                OR EXISTS (
+                    SELECT 1
+                    FROM item_objects
+                    WHERE
+                        item_objects.item_id = item.id AND item_objects.location_id = $6 AND item_objects.sold_at IS NULL
+                        AND NOT removal_pending('item_object', item_objects.id)
+                )
+            )
+            AND (
+                $7::uuid IS NULL
+                OR EXISTS (
+                    SELECT 1
+                    FROM item_tag
+                    WHERE
+                        item_tag.item_id = item.id AND item_tag.tag_id = $7
+                        AND NOT removal_pending(jsonb_build_object('kind', 'item_tag', 'item_id', item_tag.item_id, 'tag_id', item_tag.tag_id))
+                )
+            )
+            ORDER BY word_similarity($1, item.name) DESC
+        "#,
+        query,
+        filters.tags_filtered as _,
+        filters.category as _,
+        filters.include_subcategories,
+        Json(filters.attribute_filters) as _,
+        filters.location as _,
+        filters.tag as _,
+        min_similarity
+    )
+    .fetch_all(executor)
+    .await?)
+}
+
+async fn select_item_objects<'e>(executor: impl PgExecutor<'e>, item_id: ItemId) -> ResultDb<Vec<ItemObject>> {
+    Ok(sqlx::query_as!(
+        ItemObject,
+        r#"
+            SELECT item_objects.id, item_objects.item_code, item_objects.location_id as "location_id: LocationId", item_objects.variant_id as "variant_id: ItemVariantId"
+            FROM item_objects
+            WHERE item_objects.item_id = $1 AND item_objects.sold_at IS NULL AND NOT removal_pending('item_object', item_objects.id)
+        "#,
+        item_id as _
+    )
+    .fetch_all(executor)
+    .await?)
+}
+
+async fn select_item_tags<'e>(executor: impl PgExecutor<'e>, item_id: ItemId) -> ResultDb<Vec<Tag>> {
+    Ok(sqlx::query_as!(
+        Tag,
+        r#"
+            SELECT tag.id, tag.name, tag.group_id as "group_id: TagGroupId", tag.color
+            FROM tag
+            LEFT JOIN item_tag ON item_tag.tag_id = tag.id
+            WHERE
+                item_tag.item_id = $1
+                AND NOT removal_pending('tag', tag.id)
+                AND NOT removal_pending(jsonb_build_object('kind', 'item_tag', 'item_id', item_tag.item_id, 'tag_id', item_tag.tag_id))
+        "#,
+        item_id as _
+    )
+    .fetch_all(executor)
+    .await?)
+}
+
+async fn select_item_variants<'e>(executor: impl PgExecutor<'e>, item_id: ItemId) -> ResultDb<Vec<ItemVariant>> {
+    Ok(sqlx::query_as!(
+        ItemVariant,
+        r#"
+            SELECT id, name, attributes as "attributes: Json<ItemAttributes>"
+            FROM item_variant
+            WHERE item_id = $1 AND NOT removal_pending('item_variant', id)
+            ORDER BY name
+        "#,
+        item_id as _
+    )
+    .fetch_all(executor)
+    .await?)
+}
+
+/// The item as the connection sees it, so that a transaction sees its own changes.
+pub(super) async fn select_item(conn: &mut PgConnection, item_id: ItemId) -> ResultDb<Item> {
+    let item = sqlx::query_as!(
+        ItemIncomplete,
+        r#"
+            SELECT
+                item.id,
+                item.name,
+                (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                item.attributes as "attributes: Json<ItemAttributes>"
+            FROM
+                item
+            INNER JOIN
+                category ON category.id = item.category_id
+            WHERE
+                item.id = $1 AND NOT removal_pending('item', item.id)
+        "#,
+        item_id as _
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(DbError::ItemNotFound)?;
+
+    Ok(Item {
+        id: item.id,
+        name: item.name,
+        category: item.category,
+        tags: select_item_tags(&mut *conn, item_id).await?,
+        objects: select_item_objects(&mut *conn, item_id).await?,
+        attributes: item.attributes,
+        variants: select_item_variants(&mut *conn, item_id).await?,
+    })
+}
+
+/// Changes every item in a savepoint of its own, so that a failed item doesn't roll back the others.
+async fn change_each<T>(
+    conn: &mut PgConnection,
+    item_ids: &[ItemId],
+    change: impl for<'c> Fn(&'c mut PgConnection, ItemId) -> BoxFuture<'c, ResultDb<T>>,
+) -> ResultDb<BulkOutcome<T>> {
+    let mut outcome = BulkOutcome::new();
+
+    for &item_id in item_ids {
+        let mut savepoint = conn.begin().await?;
+        match change(&mut savepoint, item_id).await {
+            Ok(changed) => {
+                savepoint.commit().await?;
+                outcome.succeeded.push(changed);
+            },
+            Err(error) => {
+                savepoint.rollback().await?;
+                outcome.failed.push(BulkFailure { item_id, reason: error.referenced_as_in_use().to_string() });
+            },
+        }
+    }
+
+    Ok(outcome)
+}
+
 #[async_trait::async_trait]
 pub trait ItemsDB {
-    async fn search_items(&self, query: Option<&str>, tags_filtered: &[String], category: &str) -> ResultDb<Vec<Item>>;
-    async fn add_tag(&self, tag_name: &str) -> ResultDb<Tag>;
-    async fn add_item(&self, item_name: &str, item_category: &str) -> ResultDb<Item>;
-    async fn add_item_object(&self, item_id: ItemId, item_code: &str) -> ResultDb<ItemObject>;
-    async fn get_tags(&self) -> ResultDb<Vec<Tag>>;
-    async fn remove_tag(&self, tag_id: TagId) -> ResultDb<()>;
+    /// Searches items of a category, optionally including the items of all its sub-categories,
+    /// or of all the categories if it's not set.
+    ///
+    /// Items must match all of the `attribute_filters`, have objects at the `location` and have the
+    /// `tag`, if they are set.
+    ///
+    /// If there are few matches of the query, it's corrected for the wrong keyboard layout or typos,
+    /// and the corrected matches are added after the exact ones.
+    #[allow(clippy::too_many_arguments)]
+    async fn search_items(
+        &self,
+        query: Option<&str>,
+        tags_filtered: &[TagId],
+        category: Option<CategoryId>,
+        include_subcategories: bool,
+        attribute_filters: &[AttributeFilter],
+        location: Option<LocationId>,
+        tag: Option<TagId>,
+    ) -> ResultDb<SearchResults>;
+    /// Items containing the query in their names, of a category with its sub-categories, with objects at the
+    /// `location` and with the `tag`, if they are set. Unlike [`ItemsDB::search_items`], the query isn't corrected.
+    async fn list_items(&self, query: Option<&str>, category: Option<CategoryId>, location: Option<LocationId>, tag: Option<TagId>) -> ResultDb<Vec<Item>>;
+    /// Item names, tag names and object codes containing the `prefix`, the ones starting with it come first.
+    async fn suggest(&self, prefix: &str, limit: i64) -> ResultDb<Vec<Suggestion>>;
+    async fn get_item(&self, item_id: ItemId) -> ResultDb<Item>;
+    async fn get_item_object(&self, item_object_id: ItemObjectId) -> ResultDb<ItemObject>;
+    async fn get_tag(&self, tag_id: TagId) -> ResultDb<Tag>;
+    async fn add_tag(&self, tag_name: &str, group_id: Option<TagGroupId>, color: &str) -> ResultDb<Tag>;
+    async fn update_tag(&self, tag_id: TagId, tag_name: &str, group_id: Option<TagGroupId>, color: &str, precondition: Option<&dyn Precondition<Tag>>) -> ResultDb<Tag>;
+    async fn add_item(&self, item_name: &str, category_id: CategoryId) -> ResultDb<Item>;
+    /// Renames the item or moves it to another category, the values of the old category's attributes are kept.
+    async fn update_item(&self, item_id: ItemId, item_name: &str, category_id: CategoryId, precondition: Option<&dyn Precondition<Item>>) -> ResultDb<Item>;
+    async fn add_item_object(&self, item_id: ItemId, item_code: &str, location_id: Option<LocationId>, variant_id: Option<ItemVariantId>) -> ResultDb<ItemObject>;
+    async fn find_by_item_code(&self, item_code: &str) -> ResultDb<ItemObjectLookup>;
+    async fn get_tags(&self) -> ResultDb<Vec<GroupedTags>>;
+    async fn remove_tag(&self, tag_id: TagId, precondition: Option<&dyn Precondition<Tag>>) -> ResultDb<()>;
+    async fn add_tag_group(&self, group_name: &str) -> ResultDb<TagGroup>;
+    /// Removes a group, its tags become ungrouped.
+    async fn remove_tag_group(&self, group_id: TagGroupId) -> ResultDb<()>;
+    /// Objects of an item, which are in stock, sold objects are left out.
     async fn get_item_objects(&self, item_id: ItemId) -> ResultDb<Vec<ItemObject>>;
     async fn get_item_tags(&self, item_id: ItemId) -> ResultDb<Vec<Tag>>;
-    async fn remove_item(&self, item_id: ItemId) -> ResultDb<()>;
-    async fn remove_item_object(&self, item_object_id: ItemObjectId) -> ResultDb<()>;
+    async fn get_item_variants(&self, item_id: ItemId) -> ResultDb<Vec<ItemVariant>>;
+    async fn remove_item(&self, item_id: ItemId, precondition: Option<&dyn Precondition<Item>>) -> ResultDb<()>;
+    /// Removes an item object, which is in stock, sold objects stay in the sales.
+    async fn remove_item_object(&self, item_object_id: ItemObjectId, precondition: Option<&dyn Precondition<ItemObject>>) -> ResultDb<()>;
     async fn add_item_tag(&self, item_id: ItemId, tag_id: TagId) -> ResultDb<()>;
     async fn remove_item_tag(&self, item_id: ItemId, tag_id: TagId) -> ResultDb<()>;
+    /// Adds the tag to each of the items, the ones, which have it already, are left as they are.
+    async fn tag_items(&self, item_ids: &[ItemId], tag_id: TagId) -> ResultDb<BulkOutcome<Item>>;
+    async fn untag_items(&self, item_ids: &[ItemId], tag_id: TagId) -> ResultDb<BulkOutcome<Item>>;
+    /// Moves the items to the category, the values of the old category's attributes are kept.
+    async fn move_items(&self, item_ids: &[ItemId], category_id: CategoryId) -> ResultDb<BulkOutcome<Item>>;
+    async fn remove_items(&self, item_ids: &[ItemId]) -> ResultDb<BulkOutcome<ItemId>>;
+    /// The items in the order of `item_ids`, all as of the same moment.
+    async fn get_items(&self, item_ids: &[ItemId]) -> ResultDb<BulkOutcome<Item>>;
 }
 
 #[async_trait::async_trait]
 impl ItemsDB for Repository {
-    async fn search_items(&self, query: Option<&str>, tags_filtered: &[String], category: &str) -> ResultDb<Vec<Item>> {
-        Ok(futures::stream::iter(
-            sqlx::query_as!(
-                ItemIncomplete,
-                r#"
-                    WITH items_ids_with_tags AS (
-                        SELECT
-                            item_tag.item_id
-                        FROM
-                            item_tag
-                        LEFT JOIN
-                            tag ON tag.id = item_tag.tag_id
-                        WHERE
-                            tag.name in (SELECT unnest($2::text[]))
-                    )
-                    
-                    SELECT
-                        item.id, item.name,
-                        (category.id,  category.name) as "category!: Category"
-                    FROM
-                        item
+    async fn search_items(
+        &self,
+        query: Option<&str>,
+        tags_filtered: &[TagId],
+        category: Option<CategoryId>,
+        include_subcategories: bool,
+        attribute_filters: &[AttributeFilter],
+        location: Option<LocationId>,
+        tag: Option<TagId>,
+    ) -> ResultDb<SearchResults> {
+        let filters = ItemFilters { tags_filtered, category, include_subcategories, attribute_filters, location, tag };
+        let query = query.unwrap_or("");
 
-                    INNER JOIN
-                        category ON category.id = item.category_id
+        let mut found = find_items(&self.pool, query, &filters, None).await?;
+        let mut did_you_mean = None;
 
-                    WHERE
-                        item.name LIKE '%' || $1 || '%'
-                    AND
-                        NOT item.id in (SELECT item_id from items_ids_with_tags)
-                    AND
-                        category.name = $3
-                "#,
-                query.unwrap_or(""),
-                tags_filtered,
-                category
-            )
-            .fetch_all(&self.pool)
-            .await?
+        if !query.is_empty() && found.len() < FEW_MATCHES {
+            // A query typed with the wrong keyboard layout is the likeliest mistake, otherwise it's a typo
+            let switched = switch_keyboard_layout(query);
+            let switched_matches = if switched != query {
+                find_items(&self.pool, &switched, &filters, None).await?
+            } else {
+                vec![]
+            };
+            let (corrected_query, mut corrected) = if !switched_matches.is_empty() {
+                (Some(switched), switched_matches)
+            } else {
+                (None, find_items(&self.pool, query, &filters, Some(MIN_SIMILARITY)).await?)
+            };
+
+            corrected.retain(|item| !found.iter().any(|found_item| found_item.id == item.id));
+            if let Some(first) = corrected.first() {
+                did_you_mean = Some(corrected_query.unwrap_or_else(|| first.name.clone()));
+            }
+            found.extend(corrected);
+        }
+
+        let items = futures::stream::iter(found)
+            .map(|item| item.fetch_related(self))
+            .buffered(10)
+            .try_collect()
+            .await?;
+
+        Ok(SearchResults { items, did_you_mean })
+    }
+
+    async fn list_items(&self, query: Option<&str>, category: Option<CategoryId>, location: Option<LocationId>, tag: Option<TagId>) -> ResultDb<Vec<Item>> {
+        let filters = ItemFilters { tags_filtered: &[], category, include_subcategories: true, attribute_filters: &[], location, tag };
+        let found = find_items(&self.pool, query.unwrap_or(""), &filters, None).await?;
+
+        futures::stream::iter(found)
+            .map(|item| item.fetch_related(self))
+            .buffered(10)
+            .try_collect()
+            .await
+    }
+
+    async fn get_item(&self, item_id: ItemId) -> ResultDb<Item> {
+        select_item(&mut *self.pool.acquire().await?, item_id).await
+    }
+
+    async fn get_item_object(&self, item_object_id: ItemObjectId) -> ResultDb<ItemObject> {
+        sqlx::query_as!(
+            ItemObject,
+            r#"
+                SELECT item_objects.id, item_objects.item_code, item_objects.location_id as "location_id: LocationId", item_objects.variant_id as "variant_id: ItemVariantId"
+                FROM item_objects
+                WHERE item_objects.id = $1 AND item_objects.sold_at IS NULL AND NOT removal_pending('item_object', item_objects.id)
+            "#,
+            item_object_id as _
         )
-        .map(|item| item.fetch_related(self))
-        .buffer_unordered(10)
-        .try_collect()
-        .await?)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
     }
 
-    async fn add_tag(&self, tag_name: &str) -> ResultDb<Tag> {
-        Ok(sqlx::query_as!(
+    async fn get_tag(&self, tag_id: TagId) -> ResultDb<Tag> {
+        sqlx::query_as!(
             Tag,
-            "
-                INSERT INTO tag (name)
-                VALUES ($1)
-                RETURNING id, name
-            ",
-            tag_name
+            r#"
+                SELECT id, name, group_id as "group_id: TagGroupId", color
+                FROM tag
+                WHERE id = $1 AND NOT removal_pending('tag', id)
+            "#,
+            tag_id as _
         )
-        .fetch_one(&self.pool)
-        .await?)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
     }
 
-    async fn add_item(&self, item_name: &str, item_category: &str) -> ResultDb<Item> {
-        Ok(sqlx::query_as!(
+    async fn add_tag(&self, tag_name: &str, group_id: Option<TagGroupId>, color: &str) -> ResultDb<Tag> {
+        let mut transaction = self.pool.begin().await?;
+
+        let tag = sqlx::query_as!(
+            Tag,
+            r#"
+                INSERT INTO tag (name, group_id, color)
+                VALUES ($1, $2, $3)
+                RETURNING id, name, group_id as "group_id: TagGroupId", color
+            "#,
+            tag_name,
+            group_id as _,
+            color
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        enqueue_event(&mut *transaction, &WebhookEvent::TagCreated(tag.clone())).await?;
+
+        transaction.commit().await?;
+
+        Ok(tag)
+    }
+
+    async fn update_tag(&self, tag_id: TagId, tag_name: &str, group_id: Option<TagGroupId>, color: &str, precondition: Option<&dyn Precondition<Tag>>) -> ResultDb<Tag> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_tag(&mut *transaction, tag_id, precondition).await?;
+
+        let tag = sqlx::query_as!(
+            Tag,
+            r#"
+                UPDATE tag
+                SET name = $2, group_id = $3, color = $4
+                WHERE id = $1 AND NOT removal_pending('tag', id)
+                RETURNING id, name, group_id as "group_id: TagGroupId", color
+            "#,
+            tag_id as _,
+            tag_name,
+            group_id as _,
+            color
+        )
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        enqueue_event(&mut *transaction, &WebhookEvent::TagUpdated(tag.clone())).await?;
+
+        transaction.commit().await?;
+
+        Ok(tag)
+    }
+
+    async fn add_item(&self, item_name: &str, category_id: CategoryId) -> ResultDb<Item> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_category(&mut *transaction, category_id, None).await?;
+
+        let inserted = sqlx::query_as!(
             ItemIncomplete,
             r#"
                 WITH inserted_items AS (
                     INSERT INTO item (name, category_id)
-                    SELECT $1, category.id
-                    FROM category
-                    WHERE category.name = $2
-                    RETURNING item.id, item.name, item.category_id
+                    VALUES ($1, $2)
+                    RETURNING item.id, item.name, item.category_id, item.attributes
                 )
 
                 SELECT
@@ -113,121 +597,369 @@ impl ItemsDB for Repository {
                     inserted_items.name,
                     (
                         category.id,
-                        category.name
-                    ) as "category!: Category"
+                        category.name,
+                        category.parent_id,
+                        category.slug
+                    ) as "category!: Category",
+                    inserted_items.attributes as "attributes: Json<ItemAttributes>"
                 FROM
                     inserted_items
                 INNER JOIN
                     category ON category.id = inserted_items.category_id
             "#,
             item_name,
-            item_category
+            category_id as _
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        // A new item has no tags, objects or variants yet
+        let item = Item {
+            id: inserted.id,
+            name: inserted.name,
+            category: inserted.category,
+            tags: vec![],
+            objects: vec![],
+            attributes: inserted.attributes,
+            variants: vec![],
+        };
+
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemCreated(item.clone())).await?;
+
+        transaction.commit().await?;
+
+        Ok(item)
+    }
+
+    async fn add_item_object(&self, item_id: ItemId, item_code: &str, location_id: Option<LocationId>, variant_id: Option<ItemVariantId>) -> ResultDb<ItemObject> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_item(&mut transaction, self, item_id, None).await?;
+        if let Some(variant_id) = variant_id {
+            // A variant waiting for its removal can't get new objects
+            sqlx::query_scalar!(
+                "
+                    SELECT id FROM item_variant
+                    WHERE id = $1 AND item_id = $2 AND NOT removal_pending('item_variant', id)
+                    FOR SHARE
+                ",
+                variant_id as _,
+                item_id as _
+            )
+            .fetch_optional(&mut *transaction)
+            .await?
+            .ok_or(DbError::ItemNotFound)?;
+        }
+        let object = insert_item_object(&mut *transaction, item_id, item_code, location_id, variant_id, None).await?;
+        enqueue_stock_changed(&mut transaction, &[item_id]).await?;
+
+        transaction.commit().await?;
+
+        Ok(object)
+    }
+
+    async fn find_by_item_code(&self, item_code: &str) -> ResultDb<ItemObjectLookup> {
+        let found = sqlx::query!(
+            r#"
+                SELECT
+                    item_objects.id as "object_id: ItemObjectId",
+                    item.id as "item_id: ItemId",
+                    item.name,
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    item.attributes as "attributes: Json<ItemAttributes>"
+                FROM
+                    item_objects
+                INNER JOIN
+                    item ON item.id = item_objects.item_id
+                INNER JOIN
+                    category ON category.id = item.category_id
+                WHERE
+                    item_objects.item_code = $1 AND item_objects.sold_at IS NULL AND NOT removal_pending('item_object', item_objects.id)
+                LIMIT 1
+            "#,
+            item_code
+        )
+        .fetch_optional(&self.pool)
         .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        let item = ItemIncomplete {
+            id: found.item_id,
+            name: found.name,
+            category: found.category,
+            attributes: found.attributes.0,
+        }
         .fetch_related(self)
-        .await?)
+        .await?;
+
+        Ok(ItemObjectLookup {
+            item,
+            object_id: found.object_id,
+        })
     }
 
-    async fn add_item_object(&self, item_id: ItemId, item_code: &str) -> ResultDb<ItemObject> {
-        Ok(sqlx::query_as!(
-            ItemObject,
+    async fn suggest(&self, prefix: &str, limit: i64) -> ResultDb<Vec<Suggestion>> {
+        // Matches, which start with the prefix, get a bonus to their similarity
+        let items = sqlx::query!(
             r#"
-                INSERT INTO item_objects (item_code, item_id)
-                VALUES ($1, $2)
-                RETURNING id, item_code
+                SELECT
+                    item.id as "item_id: ItemId",
+                    item.name as item_name,
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    ((item.name ILIKE $1 || '%')::int + similarity(item.name, $1))::float8 as "rank!"
+                FROM
+                    item
+                INNER JOIN
+                    category ON category.id = item.category_id
+                WHERE
+                    item.name ILIKE '%' || $1 || '%' AND NOT removal_pending('item', item.id)
+                ORDER BY "rank!" DESC
+                LIMIT $2
             "#,
-            item_code,
-            item_id as _
+            prefix,
+            limit
         )
-        .fetch_one(&self.pool)
-        .await?)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|found| (found.rank, Suggestion::Item { item_id: found.item_id, item_name: found.item_name, category: found.category }));
+
+        let tags = sqlx::query!(
+            r#"
+                SELECT
+                    id as "id: TagId",
+                    name,
+                    group_id as "group_id: TagGroupId",
+                    color,
+                    ((name ILIKE $1 || '%')::int + similarity(name, $1))::float8 as "rank!"
+                FROM
+                    tag
+                WHERE
+                    name ILIKE '%' || $1 || '%' AND NOT removal_pending('tag', id)
+                ORDER BY "rank!" DESC
+                LIMIT $2
+            "#,
+            prefix,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|found| (found.rank, Suggestion::Tag(Tag { id: found.id, name: found.name, group_id: found.group_id, color: found.color })));
+
+        let objects = sqlx::query!(
+            r#"
+                SELECT
+                    item_objects.id as "object_id: ItemObjectId",
+                    item_objects.item_code as "item_code!",
+                    item.name as item_name,
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    ((item_objects.item_code ILIKE $1 || '%')::int + similarity(item_objects.item_code, $1))::float8 as "rank!"
+                FROM
+                    item_objects
+                INNER JOIN
+                    item ON item.id = item_objects.item_id
+                INNER JOIN
+                    category ON category.id = item.category_id
+                WHERE
+                    item_objects.item_code ILIKE '%' || $1 || '%' AND item_objects.sold_at IS NULL
+                    AND NOT removal_pending('item_object', item_objects.id)
+                ORDER BY "rank!" DESC
+                LIMIT $2
+            "#,
+            prefix,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|found| (found.rank, Suggestion::Object {
+            object_id: found.object_id,
+            item_code: found.item_code,
+            item_name: found.item_name,
+            category: found.category,
+        }));
+
+        let mut suggestions: Vec<_> = items.chain(tags).chain(objects).collect();
+        suggestions.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        Ok(suggestions.into_iter()
+            .take(limit.try_into().unwrap_or_default())
+            .map(|(_, suggestion)| suggestion)
+            .collect())
     }
 
-    async fn get_tags(&self) -> ResultDb<Vec<Tag>> {
-        Ok(sqlx::query_as!(
-            Tag,
+    async fn get_tags(&self) -> ResultDb<Vec<GroupedTags>> {
+        let groups = sqlx::query_as!(
+            TagGroup,
             "
                 SELECT id, name
-                FROM tag
+                FROM tag_group
+                WHERE NOT removal_pending('tag_group', id)
+                ORDER BY name
             "
         )
         .fetch_all(&self.pool)
-        .await?)
+        .await?;
+
+        let tags = sqlx::query_as!(
+            Tag,
+            r#"
+                SELECT id, name, group_id as "group_id: TagGroupId", color
+                FROM tag
+                WHERE NOT removal_pending('tag', id)
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(GroupedTags::group(groups, tags))
     }
 
-    async fn remove_tag(&self, tag_id: TagId) -> ResultDb<()> {
-        sqlx::query!(
+    async fn remove_tag(&self, tag_id: TagId, precondition: Option<&dyn Precondition<Tag>>) -> ResultDb<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_tag(&mut *transaction, tag_id, precondition).await?;
+
+        let removed = sqlx::query!(
             "
                 DELETE FROM tag
                 WHERE id = $1
             ",
             tag_id as _
         )
-        .execute(&self.pool)
+        .execute(&mut *transaction)
         .await?;
-        
+
+        if removed.rows_affected() == 0 {
+            return Err(DbError::ItemNotFound);
+        }
+
+        enqueue_event(&mut *transaction, &WebhookEvent::TagDeleted { id: tag_id }).await?;
+
+        transaction.commit().await?;
+
         Ok(())
     }
 
-    async fn get_item_objects(&self, item_id: ItemId) -> ResultDb<Vec<ItemObject>> {
+    async fn add_tag_group(&self, group_name: &str) -> ResultDb<TagGroup> {
         Ok(sqlx::query_as!(
-            ItemObject,
+            TagGroup,
             "
-                SELECT item_objects.id, item_objects.item_code
-                FROM item_objects
-                WHERE item_objects.item_id = $1
+                INSERT INTO tag_group (name)
+                VALUES ($1)
+                RETURNING id, name
             ",
-            item_id as _
+            group_name
         )
-        .fetch_all(&self.pool)
+        .fetch_one(&self.pool)
         .await?)
     }
 
-    async fn get_item_tags(&self, item_id: ItemId) -> ResultDb<Vec<Tag>> {
-        Ok(sqlx::query_as!(
-            Tag,
+    async fn remove_tag_group(&self, group_id: TagGroupId) -> ResultDb<()> {
+        let removed = sqlx::query!(
             "
-                SELECT tag.id, tag.name
-                FROM tag
-                LEFT JOIN item_tag ON item_tag.tag_id = tag.id
-                WHERE item_tag.item_id = $1
+                DELETE FROM tag_group
+                WHERE id = $1
             ",
-            item_id as _
+            group_id as _
         )
-        .fetch_all(&self.pool)
-        .await?)
+        .execute(&self.pool)
+        .await?;
+
+        if removed.rows_affected() == 0 {
+            return Err(DbError::ItemNotFound);
+        }
+
+        Ok(())
     }
 
-    async fn remove_item(&self, item_id: ItemId) -> ResultDb<()> {
-        sqlx::query!(
+    async fn get_item_objects(&self, item_id: ItemId) -> ResultDb<Vec<ItemObject>> {
+        select_item_objects(&self.pool, item_id).await
+    }
+
+    async fn get_item_tags(&self, item_id: ItemId) -> ResultDb<Vec<Tag>> {
+        select_item_tags(&self.pool, item_id).await
+    }
+
+    async fn get_item_variants(&self, item_id: ItemId) -> ResultDb<Vec<ItemVariant>> {
+        select_item_variants(&self.pool, item_id).await
+    }
+
+    async fn update_item(&self, item_id: ItemId, item_name: &str, category_id: CategoryId, precondition: Option<&dyn Precondition<Item>>) -> ResultDb<Item> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_item(&mut transaction, self, item_id, precondition).await?;
+
+        let updated = sqlx::query!(
             "
-                DELETE FROM item
-                WHERE id = $1
+                UPDATE item
+                SET name = $2, category_id = $3
+                WHERE id = $1 AND NOT removal_pending('item', id)
             ",
-            item_id as _
+            item_id as _,
+            item_name,
+            category_id as _
         )
-        .execute(&self.pool)
+        .execute(&mut *transaction)
         .await?;
-        
-        Ok(())
+
+        if updated.rows_affected() == 0 {
+            return Err(DbError::ItemNotFound);
+        }
+
+        transaction.commit().await?;
+
+        let item = self.get_item(item_id).await?;
+        enqueue_event(&self.pool, &WebhookEvent::ItemUpdated(item.clone())).await?;
+
+        Ok(item)
     }
 
-    async fn remove_item_object(&self, item_object_id: ItemObjectId) -> ResultDb<()> {
-        sqlx::query!(
+    async fn remove_item(&self, item_id: ItemId, precondition: Option<&dyn Precondition<Item>>) -> ResultDb<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_item(&mut transaction, self, item_id, precondition).await?;
+
+        let removed = sqlx::query!(
             "
-                DELETE FROM item_objects
+                DELETE FROM item
                 WHERE id = $1
             ",
-            item_object_id as _
+            item_id as _
         )
-        .execute(&self.pool)
+        .execute(&mut *transaction)
         .await?;
 
+        if removed.rows_affected() == 0 {
+            return Err(DbError::ItemNotFound);
+        }
+
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemDeleted { id: item_id }).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_item_object(&self, item_object_id: ItemObjectId, precondition: Option<&dyn Precondition<ItemObject>>) -> ResultDb<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_item_object(&mut *transaction, item_object_id, precondition).await?;
+        let item_id = delete_item_object(&mut *transaction, item_object_id).await?;
+        enqueue_stock_changed(&mut transaction, &[item_id]).await?;
+
+        transaction.commit().await?;
+
         Ok(())
     }
 
     async fn add_item_tag(&self, item_id: ItemId, tag_id: TagId) -> ResultDb<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_item(&mut transaction, self, item_id, None).await?;
+        lock_tag(&mut *transaction, tag_id, None).await?;
+
         sqlx::query!(
             "
                 INSERT INTO item_tag (item_id, tag_id)
@@ -236,14 +968,21 @@ impl ItemsDB for Repository {
             item_id as _,
             tag_id as _
         )
-        .execute(&self.pool)
+        .execute(&mut *transaction)
         .await?;
 
+        let item = select_item(&mut transaction, item_id).await?;
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemUpdated(item)).await?;
+
+        transaction.commit().await?;
+
         Ok(())
     }
 
     async fn remove_item_tag(&self, item_id: ItemId, tag_id: TagId) -> ResultDb<()> {
-        sqlx::query!(
+        let mut transaction = self.pool.begin().await?;
+
+        let removed = sqlx::query!(
             "
                 DELETE FROM item_tag
                 WHERE item_id = $1 AND tag_id = $2
@@ -251,9 +990,209 @@ impl ItemsDB for Repository {
             item_id as _,
             tag_id as _,
         )
-        .execute(&self.pool)
+        .execute(&mut *transaction)
         .await?;
 
+        if removed.rows_affected() == 0 {
+            return Err(DbError::ItemNotFound);
+        }
+
+        let item = select_item(&mut transaction, item_id).await?;
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemUpdated(item)).await?;
+
+        transaction.commit().await?;
+
         Ok(())
     }
+
+    async fn tag_items(&self, item_ids: &[ItemId], tag_id: TagId) -> ResultDb<BulkOutcome<Item>> {
+        // A missing tag would fail every item, so it fails the whole change
+        self.get_tag(tag_id).await?;
+
+        let mut transaction = self.pool.begin().await?;
+
+        let outcome = change_each(&mut transaction, item_ids, |conn, item_id| Box::pin(async move {
+            sqlx::query!(
+                "
+                    INSERT INTO item_tag (item_id, tag_id)
+                    SELECT id, $2
+                    FROM item
+                    WHERE id = $1 AND NOT removal_pending('item', id)
+                    ON CONFLICT DO NOTHING
+                ",
+                item_id as _,
+                tag_id as _
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            let item = select_item(conn, item_id).await?;
+            enqueue_event(&mut *conn, &WebhookEvent::ItemUpdated(item.clone())).await?;
+            Ok(item)
+        })).await?;
+
+        transaction.commit().await?;
+
+        Ok(outcome)
+    }
+
+    async fn untag_items(&self, item_ids: &[ItemId], tag_id: TagId) -> ResultDb<BulkOutcome<Item>> {
+        let mut transaction = self.pool.begin().await?;
+
+        let outcome = change_each(&mut transaction, item_ids, |conn, item_id| Box::pin(async move {
+            sqlx::query!(
+                "
+                    DELETE FROM item_tag
+                    WHERE item_id = $1 AND tag_id = $2
+                ",
+                item_id as _,
+                tag_id as _
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            let item = select_item(conn, item_id).await?;
+            enqueue_event(&mut *conn, &WebhookEvent::ItemUpdated(item.clone())).await?;
+            Ok(item)
+        })).await?;
+
+        transaction.commit().await?;
+
+        Ok(outcome)
+    }
+
+    async fn move_items(&self, item_ids: &[ItemId], category_id: CategoryId) -> ResultDb<BulkOutcome<Item>> {
+        let mut transaction = self.pool.begin().await?;
+
+        // A missing category would fail every item, so it fails the whole change, it can't be removed meanwhile
+        lock_category(&mut *transaction, category_id, None).await?;
+
+        let outcome = change_each(&mut transaction, item_ids, |conn, item_id| Box::pin(async move {
+            sqlx::query!(
+                "
+                    UPDATE item
+                    SET category_id = $2
+                    WHERE id = $1 AND NOT removal_pending('item', id)
+                ",
+                item_id as _,
+                category_id as _
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            let item = select_item(conn, item_id).await?;
+            enqueue_event(&mut *conn, &WebhookEvent::ItemUpdated(item.clone())).await?;
+            Ok(item)
+        })).await?;
+
+        transaction.commit().await?;
+
+        Ok(outcome)
+    }
+
+    async fn remove_items(&self, item_ids: &[ItemId]) -> ResultDb<BulkOutcome<ItemId>> {
+        let mut transaction = self.pool.begin().await?;
+
+        let outcome = change_each(&mut transaction, item_ids, |conn, item_id| Box::pin(async move {
+            let removed = sqlx::query!(
+                "
+                    DELETE FROM item
+                    WHERE id = $1
+                ",
+                item_id as _
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            if removed.rows_affected() == 0 {
+                return Err(DbError::ItemNotFound);
+            }
+            enqueue_event(&mut *conn, &WebhookEvent::ItemDeleted { id: item_id }).await?;
+            Ok(item_id)
+        })).await?;
+
+        transaction.commit().await?;
+
+        Ok(outcome)
+    }
+
+    async fn get_items(&self, item_ids: &[ItemId]) -> ResultDb<BulkOutcome<Item>> {
+        let mut transaction = self.pool.begin().await?;
+
+        let outcome = change_each(&mut transaction, item_ids, |conn, item_id| Box::pin(select_item(conn, item_id))).await?;
+
+        transaction.commit().await?;
+
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{data::deletions::Deletion, db::{fixtures::with_category, categories::CategoryDB, deletions::DeletionDB}};
+
+    /// Condition, which no state satisfies, as an outdated `If-Match`.
+    struct Outdated;
+
+    impl<T> Precondition<T> for Outdated {
+        fn holds(&self, _: &T) -> bool {
+            false
+        }
+    }
+
+    async fn search(db: &Repository, query: &str) -> SearchResults {
+        db.search_items(Some(query), &[], None, false, &[], None, None).await.unwrap()
+    }
+
+    #[sqlx::test]
+    async fn search_falls_back_to_the_other_keyboard_layout(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let item = db.add_item("Светр", category.id).await.unwrap();
+
+        let found = search(&db, "Cdtnh").await;
+        assert_eq!(found.did_you_mean.as_deref(), Some("Светр"));
+        assert_eq!(found.items.iter().map(|item| item.id).collect::<Vec<_>>(), vec![item.id]);
+
+        let found = search(&db, "Светр").await;
+        assert_eq!(found.did_you_mean, None);
+        assert_eq!(found.items.len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn removal_checks_the_precondition_and_the_item(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let item = db.add_item("Светр", category.id).await.unwrap();
+
+        assert!(matches!(db.remove_item(item.id, Some(&Outdated)).await, Err(DbError::PreconditionFailed)));
+        db.get_item(item.id).await.unwrap();
+
+        db.remove_item(item.id, None).await.unwrap();
+        assert!(matches!(db.remove_item(item.id, None).await, Err(DbError::ItemNotFound)));
+    }
+
+    #[sqlx::test]
+    async fn bulk_changes_fail_only_the_failed_items(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let item = db.add_item("Светр", category.id).await.unwrap();
+        let tag = db.add_tag("зима", None, "#000000").await.unwrap();
+        let missing = ItemId(uuid::Uuid::new_v4());
+
+        let outcome = db.tag_items(&[missing, item.id], tag.id).await.unwrap();
+        assert_eq!(outcome.succeeded.iter().map(|item| item.id).collect::<Vec<_>>(), vec![item.id]);
+        assert_eq!(outcome.failed.iter().map(|failure| failure.item_id).collect::<Vec<_>>(), vec![missing]);
+        assert_eq!(db.get_item(item.id).await.unwrap().tags.iter().map(|tag| tag.id).collect::<Vec<_>>(), vec![tag.id]);
+
+        // Items aren't moved to a category, which waits for its removal
+        let removed_category = db.add_category("Взуття", None).await.unwrap();
+        db.add_pending_deletion(&Deletion::Category { category_id: removed_category.id }, 60.0, None).await.unwrap();
+        assert!(matches!(db.move_items(&[item.id], removed_category.id).await, Err(DbError::ItemNotFound)));
+
+        let outcome = db.remove_items(&[item.id, missing]).await.unwrap();
+        assert_eq!(outcome.succeeded, vec![item.id]);
+        assert_eq!(outcome.failed.len(), 1);
+        assert!(matches!(db.get_item(item.id).await, Err(DbError::ItemNotFound)));
+    }
 }
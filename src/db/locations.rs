@@ -0,0 +1,147 @@
+use crate::data::{locations::{Location, LocationId, ObjectTransfer, TransferOutcome}, item::{ItemObject, ItemObjectId, ItemVariantId}};
+
+use super::{ResultDb, Repository, DbError};
+
+#[async_trait::async_trait]
+pub trait LocationDB {
+    async fn get_locations(&self) -> ResultDb<Vec<Location>>;
+    async fn add_location(&self, name: &str) -> ResultDb<Location>;
+    /// Removes a location, its objects are left without a location.
+    async fn remove_location(&self, location_id: LocationId) -> ResultDb<()>;
+    /// Moves objects to a location, recording a transfer for every object, that has actually moved.
+    /// Sold objects and the ones waiting for their removal aren't moved, they are reported as skipped.
+    async fn transfer_objects(&self, object_ids: &[ItemObjectId], to_location_id: LocationId) -> ResultDb<TransferOutcome>;
+    /// Transfers of an object, newest first.
+    async fn get_object_transfers(&self, object_id: ItemObjectId) -> ResultDb<Vec<ObjectTransfer>>;
+}
+
+#[async_trait::async_trait]
+impl LocationDB for Repository {
+    async fn get_locations(&self) -> ResultDb<Vec<Location>> {
+        Ok(sqlx::query_as!(
+            Location,
+            "
+                SELECT id, name
+                FROM location
+                WHERE NOT removal_pending('location', id)
+                ORDER BY name
+            "
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_location(&self, name: &str) -> ResultDb<Location> {
+        Ok(sqlx::query_as!(
+            Location,
+            "
+                INSERT INTO location (name)
+                VALUES ($1)
+                RETURNING id, name
+            ",
+            name
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn remove_location(&self, location_id: LocationId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM location
+                WHERE id = $1
+            ",
+            location_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn transfer_objects(&self, object_ids: &[ItemObjectId], to_location_id: LocationId) -> ResultDb<TransferOutcome> {
+        let mut transaction = self.pool.begin().await?;
+
+        // The location can't be removed meanwhile
+        sqlx::query!(
+            "
+                SELECT id
+                FROM location
+                WHERE id = $1 AND NOT removal_pending('location', id)
+                FOR SHARE
+            ",
+            to_location_id as _
+        )
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        // Rows are locked, so that the recorded source location stays correct until the update
+        sqlx::query!(
+            "
+                INSERT INTO object_transfer (object_id, from_location_id, to_location_id)
+                SELECT item_objects.id, item_objects.location_id, $2
+                FROM item_objects
+                WHERE item_objects.id = ANY($1)
+                    AND item_objects.sold_at IS NULL
+                    AND NOT removal_pending('item_object', item_objects.id)
+                    AND item_objects.location_id IS DISTINCT FROM $2
+                FOR UPDATE
+            ",
+            object_ids as _,
+            to_location_id as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let moved = sqlx::query_as!(
+            ItemObject,
+            r#"
+                UPDATE item_objects
+                SET location_id = $2
+                WHERE id = ANY($1) AND sold_at IS NULL AND NOT removal_pending('item_object', id)
+                RETURNING id, item_code, location_id as "location_id: LocationId", variant_id as "variant_id: ItemVariantId"
+            "#,
+            object_ids as _,
+            to_location_id as _
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        let skipped = object_ids.iter()
+            .filter(|object_id| !moved.iter().any(|object| object.id == **object_id))
+            .copied()
+            .collect();
+
+        Ok(TransferOutcome { moved, skipped })
+    }
+
+    async fn get_object_transfers(&self, object_id: ItemObjectId) -> ResultDb<Vec<ObjectTransfer>> {
+        Ok(sqlx::query_as!(
+            ObjectTransfer,
+            r#"
+                SELECT
+                    object_transfer.id,
+                    object_transfer.object_id,
+                    from_location.name as "from_location?",
+                    to_location.name as "to_location?",
+                    object_transfer.transferred_at
+                FROM
+                    object_transfer
+                LEFT JOIN
+                    location from_location ON from_location.id = object_transfer.from_location_id
+                LEFT JOIN
+                    location to_location ON to_location.id = object_transfer.to_location_id
+                WHERE
+                    object_transfer.object_id = $1
+                ORDER BY
+                    object_transfer.transferred_at DESC
+            "#,
+            object_id as _
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+}
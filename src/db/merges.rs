@@ -0,0 +1,313 @@
+use crate::data::{item::{Item, ItemId, TagId}, merges::{DuplicateTags, DuplicateItems}, webhooks::WebhookEvent};
+
+use super::{ResultDb, Repository, DbError, item::{select_item, lock_item, lock_tag}, webhooks::{enqueue_event, enqueue_stock_changed}};
+
+/// Names less similar than this aren't suggested as duplicates.
+const MIN_DUPLICATE_SIMILARITY: f32 = 0.5;
+
+#[async_trait::async_trait]
+pub trait MergeDB {
+    /// Pairs of tags with similar names, the most similar first.
+    async fn get_duplicate_tags(&self, limit: i64) -> ResultDb<Vec<DuplicateTags>>;
+    /// Pairs of items with similar names, the most similar first.
+    async fn get_duplicate_items(&self, limit: i64) -> ResultDb<Vec<DuplicateItems>>;
+    /// Gives the items of the `source` tag the `target` one and removes the `source`.
+    ///
+    /// Returns the items, which had the `source` tag.
+    async fn merge_tags(&self, source: TagId, target: TagId) -> ResultDb<Vec<Item>>;
+    /// Moves the tags, variants, objects, attachments and order lines of the `source` item to the `target` one
+    /// and removes the `source`. Attribute values of the `target` are kept, the missing ones are taken
+    /// from the `source`.
+    ///
+    /// Returns the merged `target`.
+    async fn merge_items(&self, source: ItemId, target: ItemId) -> ResultDb<Item>;
+}
+
+#[async_trait::async_trait]
+impl MergeDB for Repository {
+    async fn get_duplicate_tags(&self, limit: i64) -> ResultDb<Vec<DuplicateTags>> {
+        Ok(sqlx::query_as!(
+            DuplicateTags,
+            r#"
+                SELECT
+                    first.id as "first_id: TagId",
+                    first.name as first_name,
+                    (SELECT count(*) FROM item_tag WHERE item_tag.tag_id = first.id) as "first_items!",
+                    second.id as "second_id: TagId",
+                    second.name as second_name,
+                    (SELECT count(*) FROM item_tag WHERE item_tag.tag_id = second.id) as "second_items!",
+                    similarity(first.name, second.name) as "similarity!"
+                FROM tag first
+                INNER JOIN tag second ON first.id < second.id AND first.name % second.name
+                WHERE
+                    similarity(first.name, second.name) >= $1
+                    AND NOT removal_pending('tag', first.id) AND NOT removal_pending('tag', second.id)
+                ORDER BY similarity(first.name, second.name) DESC
+                LIMIT $2
+            "#,
+            MIN_DUPLICATE_SIMILARITY,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn get_duplicate_items(&self, limit: i64) -> ResultDb<Vec<DuplicateItems>> {
+        Ok(sqlx::query_as!(
+            DuplicateItems,
+            r#"
+                SELECT
+                    first.id as "first_id: ItemId",
+                    first.name as first_name,
+                    first_category.name as first_category,
+                    (SELECT count(*) FROM item_objects WHERE item_objects.item_id = first.id AND item_objects.sold_at IS NULL) as "first_in_stock!",
+                    second.id as "second_id: ItemId",
+                    second.name as second_name,
+                    second_category.name as second_category,
+                    (SELECT count(*) FROM item_objects WHERE item_objects.item_id = second.id AND item_objects.sold_at IS NULL) as "second_in_stock!",
+                    similarity(first.name, second.name) as "similarity!"
+                FROM item first
+                INNER JOIN item second ON first.id < second.id AND first.name % second.name
+                INNER JOIN category first_category ON first_category.id = first.category_id
+                INNER JOIN category second_category ON second_category.id = second.category_id
+                WHERE
+                    similarity(first.name, second.name) >= $1
+                    AND NOT removal_pending('item', first.id) AND NOT removal_pending('item', second.id)
+                ORDER BY similarity(first.name, second.name) DESC
+                LIMIT $2
+            "#,
+            MIN_DUPLICATE_SIMILARITY,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn merge_tags(&self, source: TagId, target: TagId) -> ResultDb<Vec<Item>> {
+        if source == target {
+            return Err(DbError::MergeIntoItself);
+        }
+
+        let mut transaction = self.pool.begin().await?;
+
+        // Locked, so that neither is changed, removed or tagged with while they're merged
+        lock_tag(&mut *transaction, source, None).await?;
+        lock_tag(&mut *transaction, target, None).await?;
+
+        let item_ids = sqlx::query_scalar!(
+            r#"
+                SELECT item_id as "item_id!: ItemId"
+                FROM item_tag
+                WHERE tag_id = $1
+            "#,
+            source as _
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        // Items having both tags keep their link to the target, the source's links go with the tag
+        sqlx::query!(
+            "
+                INSERT INTO item_tag (item_id, tag_id)
+                SELECT item_id, $2
+                FROM item_tag
+                WHERE tag_id = $1
+                ON CONFLICT DO NOTHING
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                DELETE FROM tag
+                WHERE id = $1
+            ",
+            source as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        enqueue_event(&mut *transaction, &WebhookEvent::TagDeleted { id: source }).await?;
+
+        let mut items = Vec::with_capacity(item_ids.len());
+        for item_id in item_ids {
+            let item = select_item(&mut transaction, item_id).await?;
+            enqueue_event(&mut *transaction, &WebhookEvent::ItemUpdated(item.clone())).await?;
+            items.push(item);
+        }
+
+        transaction.commit().await?;
+
+        Ok(items)
+    }
+
+    async fn merge_items(&self, source: ItemId, target: ItemId) -> ResultDb<Item> {
+        if source == target {
+            return Err(DbError::MergeIntoItself);
+        }
+
+        let mut transaction = self.pool.begin().await?;
+
+        // Locked, so that nothing is added to the source while it's merged
+        lock_item(&mut transaction, self, source, None).await?;
+        lock_item(&mut transaction, self, target, None).await?;
+
+        sqlx::query!(
+            "
+                INSERT INTO item_tag (item_id, tag_id)
+                SELECT $2, tag_id
+                FROM item_tag
+                WHERE item_id = $1
+                ON CONFLICT DO NOTHING
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        // Objects of the source's variants go to the target's variants of the same name, if it has them,
+        // the rest of the variants are moved with their objects
+        sqlx::query!(
+            "
+                UPDATE item_objects
+                SET variant_id = target_variant.id
+                FROM item_variant source_variant, item_variant target_variant
+                WHERE item_objects.variant_id = source_variant.id
+                    AND source_variant.item_id = $1
+                    AND target_variant.item_id = $2
+                    AND target_variant.name = source_variant.name
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                DELETE FROM item_variant source_variant
+                WHERE source_variant.item_id = $1
+                    AND EXISTS (
+                        SELECT 1
+                        FROM item_variant target_variant
+                        WHERE target_variant.item_id = $2 AND target_variant.name = source_variant.name
+                    )
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                UPDATE item_variant
+                SET item_id = $2
+                WHERE item_id = $1
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                UPDATE item_objects
+                SET item_id = $2
+                WHERE item_id = $1
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                UPDATE item_attachment
+                SET item_id = $2
+                WHERE item_id = $1
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                UPDATE purchase_order_line
+                SET item_id = $2
+                WHERE item_id = $1
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                UPDATE item
+                SET attributes = source.attributes || item.attributes
+                FROM item source
+                WHERE item.id = $2 AND source.id = $1
+            ",
+            source as _,
+            target as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        // The source's own tag links are removed with it
+        sqlx::query!(
+            "
+                DELETE FROM item
+                WHERE id = $1
+            ",
+            source as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemDeleted { id: source }).await?;
+        let item = select_item(&mut transaction, target).await?;
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemUpdated(item.clone())).await?;
+        enqueue_stock_changed(&mut transaction, &[target]).await?;
+
+        transaction.commit().await?;
+
+        Ok(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{data::deletions::Deletion, db::{fixtures::with_category, item::ItemsDB, deletions::DeletionDB}};
+
+    #[sqlx::test]
+    async fn tags_are_merged_only_while_both_are_kept(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let item = db.add_item("Светр", category.id).await.unwrap();
+        let source = db.add_tag("зимовий", None, "#000000").await.unwrap();
+        let target = db.add_tag("зима", None, "#000000").await.unwrap();
+        db.add_item_tag(item.id, source.id).await.unwrap();
+
+        let pending = db.add_pending_deletion(&Deletion::Tag { tag_id: target.id }, 60.0, None).await.unwrap();
+        assert!(matches!(db.merge_tags(source.id, target.id).await, Err(DbError::ItemNotFound)));
+        db.take_pending_deletion(pending.id).await.unwrap();
+
+        let merged = db.merge_tags(source.id, target.id).await.unwrap();
+        assert_eq!(merged.iter().map(|item| item.id).collect::<Vec<_>>(), vec![item.id]);
+        assert_eq!(db.get_item(item.id).await.unwrap().tags.iter().map(|tag| tag.id).collect::<Vec<_>>(), vec![target.id]);
+        assert!(matches!(db.get_tag(source.id).await, Err(DbError::ItemNotFound)));
+    }
+}
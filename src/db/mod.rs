@@ -1,12 +1,27 @@
 pub mod categories;
 pub mod item;
+pub mod attributes;
+pub mod attachments;
+pub mod suppliers;
+pub mod locations;
+pub mod sales;
+pub mod stock_takes;
+pub mod stats;
+pub mod saved_searches;
+pub mod api_tokens;
+pub mod webhooks;
+pub mod deletions;
+pub mod merges;
+pub mod variants;
 
 use std::{env, convert::Infallible, future::{Ready, ready}};
-use actix_web::FromRequest;
+use actix_web::{FromRequest, ResponseError, http::StatusCode};
 use derive_more::{Error, From, Display};
 
 use sqlx::postgres::{PgPoolOptions, PgPool};
 
+use crate::data::{categories::Category, item::{Item, ItemObject, Tag}};
+
 #[derive(Clone)]
 pub struct Repository {
     pool: PgPool,
@@ -41,8 +56,125 @@ impl FromRequest for Repository {
 pub enum DbError {
     #[display(fmt = "Шуканий об'єкт не знайдено")]
     ItemNotFound,
+    #[display(fmt = "Некоректне значення атрибута «{}»", _0)]
+    InvalidAttribute(#[error(not(source))] String),
+    #[display(fmt = "Отримано більше, ніж замовлено")]
+    ReceivedTooMany,
+    #[display(fmt = "Об'єкт уже продано або видалено")]
+    AlreadySold,
+    #[display(fmt = "Зберегти можна лише пошук на головній сторінці")]
+    InvalidLink,
+    #[display(fmt = "Кошик порожній")]
+    EmptyCart,
+    #[display(fmt = "Предмет доданий до кошика двічі")]
+    DuplicateInCart,
+    #[display(fmt = "Ціна не може бути від'ємною")]
+    NegativePrice,
+    #[display(fmt = "Облік уже затверджено")]
+    StockTakeApproved,
+    #[display(fmt = "Виправлення не відповідає звіту обліку")]
+    CorrectionNotInReport,
+    #[display(fmt = "Категорія має підкатегорії, спершу видаліть або перенесіть їх")]
+    HasSubcategories,
+    #[display(fmt = "Об'єкт змінено з часу його отримання")]
+    PreconditionFailed,
+    #[display(fmt = "Об'єкт використовується іншими записами")]
+    InUse,
+    #[display(fmt = "Не всі зміни вдалися: {}", _0)]
+    #[from(ignore)]
+    PartlyFailed(#[error(not(source))] String),
+    #[display(fmt = "Не можна об'єднати об'єкт із самим собою")]
+    MergeIntoItself,
+    #[display(fmt = "Недійсний токен доступу")]
+    Unauthorized,
+    #[display(fmt = "Увійдіть із токеном доступу, щоб змінювати дані")]
+    SignInRequired,
+    #[display(fmt = "Токен доступу не дозволяє цю дію")]
+    Forbidden,
     #[display(fmt = "Помилка серверу")]
     DbError(sqlx::Error)
 }
 
-type ResultDb<T> = Result<T, DbError>;
\ No newline at end of file
+impl ResponseError for DbError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DbError::ItemNotFound => StatusCode::NOT_FOUND,
+            DbError::InvalidAttribute(_) | DbError::InvalidLink | DbError::EmptyCart | DbError::DuplicateInCart | DbError::NegativePrice | DbError::CorrectionNotInReport => StatusCode::UNPROCESSABLE_ENTITY,
+            DbError::ReceivedTooMany | DbError::AlreadySold | DbError::StockTakeApproved | DbError::HasSubcategories | DbError::InUse | DbError::PartlyFailed(_) | DbError::MergeIntoItself => StatusCode::CONFLICT,
+            DbError::Unauthorized | DbError::SignInRequired => StatusCode::UNAUTHORIZED,
+            DbError::Forbidden => StatusCode::FORBIDDEN,
+            DbError::PreconditionFailed => StatusCode::PRECONDITION_FAILED,
+            DbError::DbError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl DbError {
+    /// A removal refused by a foreign key, e.g. of a supplier with orders, is reported as such.
+    pub fn referenced_as_in_use(self) -> Self {
+        match self {
+            DbError::DbError(sqlx::Error::Database(error)) if error.is_foreign_key_violation() => DbError::InUse,
+            error => error,
+        }
+    }
+}
+
+type ResultDb<T> = Result<T, DbError>;
+
+/// Condition on the current state of an object, which its change requires, e.g. the client's `If-Match`.
+///
+/// The change checks it in its own transaction, with the object's row locked, so that no concurrent change slips in between.
+pub trait Precondition<T>: Send + Sync {
+    fn holds(&self, current: &T) -> bool;
+}
+
+/// Condition, which holds or not for any lockable object, as the client's `If-Match` does, e.g. for a queued removal.
+pub trait AnyPrecondition: Send + Sync {
+    fn for_category(&self) -> &dyn Precondition<Category>;
+    fn for_tag(&self) -> &dyn Precondition<Tag>;
+    fn for_item(&self) -> &dyn Precondition<Item>;
+    fn for_item_object(&self) -> &dyn Precondition<ItemObject>;
+}
+
+impl<P: Precondition<Category> + Precondition<Tag> + Precondition<Item> + Precondition<ItemObject>> AnyPrecondition for P {
+    fn for_category(&self) -> &dyn Precondition<Category> {
+        self
+    }
+
+    fn for_tag(&self) -> &dyn Precondition<Tag> {
+        self
+    }
+
+    fn for_item(&self) -> &dyn Precondition<Item> {
+        self
+    }
+
+    fn for_item_object(&self) -> &dyn Precondition<ItemObject> {
+        self
+    }
+}
+
+/// Fails the change, unless there is no condition or it holds for the current state.
+fn check_precondition<T>(precondition: Option<&dyn Precondition<T>>, current: &T) -> ResultDb<()> {
+    match precondition {
+        Some(precondition) if !precondition.holds(current) => Err(DbError::PreconditionFailed),
+        _ => Ok(()),
+    }
+}
+
+/// Set-up shared by the database tests.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use sqlx::PgPool;
+
+    use crate::data::categories::Category;
+
+    use super::{Repository, categories::CategoryDB};
+
+    /// A repository over the test's own database, with a category to add items and sub-categories to.
+    pub async fn with_category(pool: PgPool) -> (Repository, Category) {
+        let db = Repository { pool };
+        let category = db.add_category("Одяг", None).await.unwrap();
+        (db, category)
+    }
+}
@@ -0,0 +1,229 @@
+use chrono::NaiveDate;
+
+use crate::data::{sales::{Sale, SaleId, SaleIncomplete, SaleLine, SaleObject, CartLine, SalesSummary}, item::{ItemId, ItemObjectId}};
+
+use super::{ResultDb, Repository, DbError, webhooks::enqueue_stock_changed};
+
+impl SaleIncomplete {
+    pub async fn fetch_related(self, repo: &impl SaleDB) -> ResultDb<Sale> {
+        let lines = repo.get_sale_lines(self.id).await?;
+
+        Ok(Sale {
+            id: self.id,
+            created_at: self.created_at,
+            lines,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+pub trait SaleDB {
+    /// Finds an object in stock by its code.
+    async fn find_sale_object(&self, item_code: &str) -> ResultDb<SaleObject>;
+    /// Objects of an item, which are in stock.
+    async fn get_sale_objects(&self, item_id: ItemId) -> ResultDb<Vec<SaleObject>>;
+    /// Records a sale and marks its objects sold, failing if any of them is sold already.
+    async fn add_sale(&self, cart: &[CartLine]) -> ResultDb<Sale>;
+    async fn get_sale(&self, sale_id: SaleId) -> ResultDb<Sale>;
+    async fn get_sale_lines(&self, sale_id: SaleId) -> ResultDb<Vec<SaleLine>>;
+    /// Totals of the sales made on a day in the time zone, today there if it's not set.
+    async fn get_daily_sales_summary(&self, day: Option<NaiveDate>, time_zone: &str) -> ResultDb<SalesSummary>;
+}
+
+#[async_trait::async_trait]
+impl SaleDB for Repository {
+    async fn find_sale_object(&self, item_code: &str) -> ResultDb<SaleObject> {
+        sqlx::query_as!(
+            SaleObject,
+            r#"
+                SELECT
+                    item_objects.id as object_id,
+                    item_objects.item_code,
+                    item.id as item_id,
+                    item.name as item_name,
+                    (
+                        SELECT sale_line.price
+                        FROM sale_line
+                        INNER JOIN item_objects sold_objects ON sold_objects.id = sale_line.object_id
+                        INNER JOIN sale ON sale.id = sale_line.sale_id
+                        WHERE sold_objects.item_id = item.id
+                        ORDER BY sale.created_at DESC
+                        LIMIT 1
+                    ) as last_price
+                FROM
+                    item_objects
+                INNER JOIN
+                    item ON item.id = item_objects.item_id
+                WHERE
+                    item_objects.item_code = $1 AND item_objects.sold_at IS NULL AND NOT removal_pending('item_object', item_objects.id)
+                LIMIT 1
+            "#,
+            item_code
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn get_sale_objects(&self, item_id: ItemId) -> ResultDb<Vec<SaleObject>> {
+        Ok(sqlx::query_as!(
+            SaleObject,
+            r#"
+                SELECT
+                    item_objects.id as object_id,
+                    item_objects.item_code,
+                    item.id as item_id,
+                    item.name as item_name,
+                    (
+                        SELECT sale_line.price
+                        FROM sale_line
+                        INNER JOIN item_objects sold_objects ON sold_objects.id = sale_line.object_id
+                        INNER JOIN sale ON sale.id = sale_line.sale_id
+                        WHERE sold_objects.item_id = item.id
+                        ORDER BY sale.created_at DESC
+                        LIMIT 1
+                    ) as last_price
+                FROM
+                    item_objects
+                INNER JOIN
+                    item ON item.id = item_objects.item_id
+                WHERE
+                    item.id = $1 AND item_objects.sold_at IS NULL AND NOT removal_pending('item_object', item_objects.id)
+                ORDER BY
+                    item_objects.item_code
+            "#,
+            item_id as _
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_sale(&self, cart: &[CartLine]) -> ResultDb<Sale> {
+        let object_ids = cart.iter().map(|line| line.object_id).collect::<Vec<_>>();
+        let prices = cart.iter().map(|line| line.price).collect::<Vec<_>>();
+
+        if object_ids.is_empty() {
+            return Err(DbError::EmptyCart);
+        }
+        if object_ids.iter().enumerate().any(|(idx, object_id)| object_ids[..idx].contains(object_id)) {
+            return Err(DbError::DuplicateInCart);
+        }
+        if prices.iter().any(|price| *price < 0) {
+            return Err(DbError::NegativePrice);
+        }
+
+        let mut transaction = self.pool.begin().await?;
+
+        // Objects sold meanwhile aren't updated, which fails the whole sale. So are the objects, which wait for their
+        // removal, the removal couldn't be undone after the sale
+        let sold = sqlx::query!(
+            r#"
+                UPDATE item_objects
+                SET sold_at = now()
+                WHERE id = ANY($1) AND sold_at IS NULL AND NOT removal_pending('item_object', id)
+                RETURNING item_id as "item_id: ItemId"
+            "#,
+            &object_ids as _
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        if sold.len() != object_ids.len() {
+            return Err(DbError::AlreadySold);
+        }
+
+        let sale = sqlx::query_as!(
+            SaleIncomplete,
+            "
+                INSERT INTO sale DEFAULT VALUES
+                RETURNING id, created_at
+            "
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            "
+                INSERT INTO sale_line (sale_id, object_id, item_name, item_code, price)
+                SELECT $1, item_objects.id, item.name, item_objects.item_code, cart.price
+                FROM unnest($2::uuid[], $3::bigint[]) AS cart(object_id, price)
+                INNER JOIN item_objects ON item_objects.id = cart.object_id
+                INNER JOIN item ON item.id = item_objects.item_id
+            ",
+            sale.id as _,
+            &object_ids as _,
+            &prices
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let sold_item_ids = sold.into_iter().map(|sold| sold.item_id).collect::<Vec<_>>();
+        enqueue_stock_changed(&mut transaction, &sold_item_ids).await?;
+
+        transaction.commit().await?;
+
+        sale.fetch_related(self).await
+    }
+
+    async fn get_sale(&self, sale_id: SaleId) -> ResultDb<Sale> {
+        sqlx::query_as!(
+            SaleIncomplete,
+            "
+                SELECT id, created_at
+                FROM sale
+                WHERE id = $1
+            ",
+            sale_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)?
+        .fetch_related(self)
+        .await
+    }
+
+    async fn get_sale_lines(&self, sale_id: SaleId) -> ResultDb<Vec<SaleLine>> {
+        Ok(sqlx::query_as!(
+            SaleLine,
+            r#"
+                SELECT id, object_id as "object_id: ItemObjectId", item_name, item_code, price
+                FROM sale_line
+                WHERE sale_id = $1
+                ORDER BY item_name
+            "#,
+            sale_id as _
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn get_daily_sales_summary(&self, day: Option<NaiveDate>, time_zone: &str) -> ResultDb<SalesSummary> {
+        Ok(sqlx::query_as!(
+            SalesSummary,
+            r#"
+                WITH chosen_day AS (
+                    SELECT COALESCE($1, (now() AT TIME ZONE $2)::date) AS day
+                )
+
+                SELECT
+                    chosen_day.day as "day!",
+                    COUNT(DISTINCT sale.id) as "sales_count!",
+                    COUNT(sale_line.id) as "objects_sold!",
+                    COALESCE(SUM(sale_line.price), 0)::bigint as "total!"
+                FROM
+                    chosen_day
+                LEFT JOIN
+                    sale ON sale.created_at >= chosen_day.day::timestamp AT TIME ZONE $2
+                        AND sale.created_at < (chosen_day.day + 1)::timestamp AT TIME ZONE $2
+                LEFT JOIN
+                    sale_line ON sale_line.sale_id = sale.id
+                GROUP BY
+                    chosen_day.day
+            "#,
+            day,
+            time_zone
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+}
@@ -0,0 +1,124 @@
+use crate::data::saved_searches::{SavedSearch, SavedSearchId, UserId};
+
+use super::{ResultDb, Repository, DbError};
+
+#[async_trait::async_trait]
+pub trait SavedSearchDB {
+    async fn get_saved_searches(&self, user_id: UserId) -> ResultDb<Vec<SavedSearch>>;
+    async fn get_default_saved_search(&self, user_id: UserId) -> ResultDb<Option<SavedSearch>>;
+    /// Saves a search, a search of the user with the same name is replaced.
+    /// Only links to the home page are saved.
+    async fn add_saved_search(&self, user_id: UserId, name: &str, href: &str) -> ResultDb<SavedSearch>;
+    async fn remove_saved_search(&self, user_id: UserId, saved_search_id: SavedSearchId) -> ResultDb<()>;
+    /// Makes the search the default one of the user, or unsets the default, if it's `None`.
+    async fn set_default_saved_search(&self, user_id: UserId, saved_search_id: Option<SavedSearchId>) -> ResultDb<()>;
+}
+
+#[async_trait::async_trait]
+impl SavedSearchDB for Repository {
+    async fn get_saved_searches(&self, user_id: UserId) -> ResultDb<Vec<SavedSearch>> {
+        let saved_searches = sqlx::query_as!(
+            SavedSearch,
+            r#"
+                SELECT id, name, href, is_default
+                FROM saved_search
+                WHERE user_id = $1 AND NOT removal_pending('saved_search', id)
+                ORDER BY name
+            "#,
+            user_id as _
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Links saved before they were checked aren't shown
+        Ok(saved_searches.into_iter().filter(|saved_search| SavedSearch::is_home_link(&saved_search.href)).collect())
+    }
+
+    async fn get_default_saved_search(&self, user_id: UserId) -> ResultDb<Option<SavedSearch>> {
+        Ok(sqlx::query_as!(
+            SavedSearch,
+            r#"
+                SELECT id, name, href, is_default
+                FROM saved_search
+                WHERE user_id = $1 AND is_default AND NOT removal_pending('saved_search', id)
+            "#,
+            user_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .filter(|saved_search| SavedSearch::is_home_link(&saved_search.href)))
+    }
+
+    async fn add_saved_search(&self, user_id: UserId, name: &str, href: &str) -> ResultDb<SavedSearch> {
+        if !SavedSearch::is_home_link(href) {
+            return Err(DbError::InvalidLink);
+        }
+
+        Ok(sqlx::query_as!(
+            SavedSearch,
+            r#"
+                INSERT INTO saved_search (user_id, name, href)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, name) DO UPDATE SET href = excluded.href
+                RETURNING id, name, href, is_default
+            "#,
+            user_id as _,
+            name,
+            href
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn remove_saved_search(&self, user_id: UserId, saved_search_id: SavedSearchId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM saved_search
+                WHERE id = $1 AND user_id = $2
+            ",
+            saved_search_id as _,
+            user_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_default_saved_search(&self, user_id: UserId, saved_search_id: Option<SavedSearchId>) -> ResultDb<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!(
+            "
+                UPDATE saved_search
+                SET is_default = false
+                WHERE user_id = $1 AND is_default
+            ",
+            user_id as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        if let Some(saved_search_id) = saved_search_id {
+            let updated = sqlx::query!(
+                "
+                    UPDATE saved_search
+                    SET is_default = true
+                    WHERE id = $1 AND user_id = $2 AND NOT removal_pending('saved_search', id)
+                ",
+                saved_search_id as _,
+                user_id as _
+            )
+            .execute(&mut *transaction)
+            .await?;
+
+            if updated.rows_affected() == 0 {
+                return Err(DbError::ItemNotFound);
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use crate::data::{stats::{Stats, CategoryItemsCount, TagUsage, ItemSummary, ObjectSummary}, categories::Category, item::{ItemId, ItemObjectId, TagId}};
+
+use super::{ResultDb, Repository};
+
+/// How many entries the top and recent lists of [`Stats`] have.
+const STATS_LIST_LIMIT: i64 = 10;
+
+#[async_trait::async_trait]
+pub trait StatsDB {
+    async fn get_stats(&self) -> ResultDb<Stats>;
+}
+
+#[async_trait::async_trait]
+impl StatsDB for Repository {
+    async fn get_stats(&self) -> ResultDb<Stats> {
+        // All the queries see the same snapshot, so the numbers add up
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *transaction)
+            .await?;
+
+        let counts = sqlx::query!(
+            r#"
+                SELECT
+                    (SELECT COUNT(*) FROM category) as "categories_count!",
+                    (SELECT COUNT(*) FROM item) as "items_count!",
+                    (SELECT COUNT(*) FROM item_objects WHERE sold_at IS NULL) as "objects_count!",
+                    (SELECT COUNT(*) FROM tag) as "tags_count!"
+            "#
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+
+        let items_per_category = sqlx::query_as!(
+            CategoryItemsCount,
+            r#"
+                SELECT
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    COUNT(item.id) as "items_count!"
+                FROM
+                    category
+                LEFT JOIN
+                    item ON item.category_id = category.id
+                GROUP BY
+                    category.id
+                ORDER BY
+                    category.name
+            "#
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let top_tags = sqlx::query_as!(
+            TagUsage,
+            r#"
+                SELECT
+                    tag.id as "tag_id: TagId",
+                    tag.name as tag_name,
+                    tag.color,
+                    COUNT(item_tag.item_id) as "items_count!"
+                FROM
+                    tag
+                INNER JOIN
+                    item_tag ON item_tag.tag_id = tag.id
+                GROUP BY
+                    tag.id
+                ORDER BY
+                    "items_count!" DESC, tag.name
+                LIMIT $1
+            "#,
+            STATS_LIST_LIMIT
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let recent_items = sqlx::query_as!(
+            ItemSummary,
+            r#"
+                SELECT
+                    item.id as "item_id: ItemId",
+                    item.name as item_name,
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    item.created_at
+                FROM
+                    item
+                INNER JOIN
+                    category ON category.id = item.category_id
+                ORDER BY
+                    item.created_at DESC
+                LIMIT $1
+            "#,
+            STATS_LIST_LIMIT
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let recent_objects = sqlx::query_as!(
+            ObjectSummary,
+            r#"
+                SELECT
+                    item_objects.id as "object_id: ItemObjectId",
+                    item_objects.item_code,
+                    item.name as item_name,
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    item_objects.created_at
+                FROM
+                    item_objects
+                INNER JOIN
+                    item ON item.id = item_objects.item_id
+                INNER JOIN
+                    category ON category.id = item.category_id
+                WHERE
+                    item_objects.sold_at IS NULL
+                ORDER BY
+                    item_objects.created_at DESC
+                LIMIT $1
+            "#,
+            STATS_LIST_LIMIT
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let items_without_objects = sqlx::query_as!(
+            ItemSummary,
+            r#"
+                SELECT
+                    item.id as "item_id: ItemId",
+                    item.name as item_name,
+                    (category.id, category.name, category.parent_id, category.slug) as "category!: Category",
+                    item.created_at
+                FROM
+                    item
+                INNER JOIN
+                    category ON category.id = item.category_id
+                WHERE
+                    NOT EXISTS (
+                        SELECT 1
+                        FROM item_objects
+                        WHERE item_objects.item_id = item.id AND item_objects.sold_at IS NULL
+                    )
+                ORDER BY
+                    category.name, item.name
+            "#
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(Stats {
+            categories_count: counts.categories_count,
+            items_count: counts.items_count,
+            objects_count: counts.objects_count,
+            tags_count: counts.tags_count,
+            items_per_category,
+            top_tags,
+            recent_items,
+            recent_objects,
+            items_without_objects,
+        })
+    }
+}
@@ -0,0 +1,364 @@
+use crate::data::{stock_takes::{StockTake, StockTakeId, StockTakeReport, StockTakeCorrection, MissingObject, UnexpectedScan, DuplicateScan}, categories::CategoryId, locations::LocationId, item::{ItemId, ItemObjectId}};
+
+use sqlx::PgConnection;
+
+use super::{ResultDb, Repository, DbError, item::{insert_item_object, delete_item_object}, webhooks::enqueue_stock_changed};
+
+/// Compares the scanned codes with the objects in stock-take's scope.
+///
+/// Takes a connection, so that the report can be checked in the transaction, which applies its corrections.
+async fn select_report(conn: &mut PgConnection, stock_take_id: StockTakeId) -> ResultDb<StockTakeReport> {
+    let missing = sqlx::query_as!(
+        MissingObject,
+        r#"
+            WITH RECURSIVE chosen_take AS (
+                SELECT category_id, location_id
+                FROM stock_take
+                WHERE id = $1
+            ),
+
+            chosen_categories AS (
+                SELECT category.id
+                FROM category
+                INNER JOIN chosen_take ON category.id = chosen_take.category_id
+
+                UNION
+
+                SELECT category.id
+                FROM category
+                INNER JOIN chosen_categories ON category.parent_id = chosen_categories.id
+            )
+
+            SELECT
+                item_objects.id as "object_id: ItemObjectId",
+                item_objects.item_code,
+                item.id as "item_id: ItemId",
+                item.name as item_name
+            FROM
+                item_objects
+            INNER JOIN
+                item ON item.id = item_objects.item_id
+            CROSS JOIN
+                chosen_take
+            WHERE
+                item_objects.sold_at IS NULL
+            AND
+                NOT removal_pending('item_object', item_objects.id)
+            AND
+                (chosen_take.category_id IS NULL OR item.category_id IN (SELECT id FROM chosen_categories))
+            AND
+                (chosen_take.location_id IS NULL OR item_objects.location_id = chosen_take.location_id)
+            AND
+                NOT EXISTS (
+                    SELECT 1
+                    FROM stock_take_scan
+                    WHERE stock_take_scan.stock_take_id = $1 AND stock_take_scan.item_code = item_objects.item_code
+                )
+            ORDER BY
+                item.name, item_objects.item_code
+        "#,
+        stock_take_id as _
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let unexpected = sqlx::query_as!(
+        UnexpectedScan,
+        r#"
+            WITH RECURSIVE chosen_take AS (
+                SELECT category_id, location_id
+                FROM stock_take
+                WHERE id = $1
+            ),
+
+            chosen_categories AS (
+                SELECT category.id
+                FROM category
+                INNER JOIN chosen_take ON category.id = chosen_take.category_id
+
+                UNION
+
+                SELECT category.id
+                FROM category
+                INNER JOIN chosen_categories ON category.parent_id = chosen_categories.id
+            ),
+
+            scope_codes AS (
+                SELECT item_objects.item_code
+                FROM item_objects
+                INNER JOIN item ON item.id = item_objects.item_id
+                CROSS JOIN chosen_take
+                WHERE
+                    item_objects.sold_at IS NULL
+                AND
+                    (chosen_take.category_id IS NULL OR item.category_id IN (SELECT id FROM chosen_categories))
+                AND
+                    (chosen_take.location_id IS NULL OR item_objects.location_id = chosen_take.location_id)
+            )
+
+            SELECT DISTINCT
+                stock_take_scan.item_code,
+                (
+                    SELECT item.name
+                    FROM item_objects
+                    INNER JOIN item ON item.id = item_objects.item_id
+                    WHERE item_objects.item_code = stock_take_scan.item_code AND item_objects.sold_at IS NULL
+                    LIMIT 1
+                ) as item_name
+            FROM
+                stock_take_scan
+            WHERE
+                stock_take_scan.stock_take_id = $1
+            AND
+                stock_take_scan.item_code NOT IN (SELECT item_code FROM scope_codes WHERE item_code IS NOT NULL)
+            ORDER BY
+                stock_take_scan.item_code
+        "#,
+        stock_take_id as _
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let duplicates = sqlx::query_as!(
+        DuplicateScan,
+        r#"
+            SELECT item_code, COUNT(*) as "count!"
+            FROM stock_take_scan
+            WHERE stock_take_id = $1
+            GROUP BY item_code
+            HAVING COUNT(*) > 1
+            ORDER BY item_code
+        "#,
+        stock_take_id as _
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(StockTakeReport {
+        missing,
+        unexpected,
+        duplicates,
+    })
+}
+
+#[async_trait::async_trait]
+pub trait StockTakeDB {
+    /// Stock-takes, newest first.
+    async fn get_stock_takes(&self) -> ResultDb<Vec<StockTake>>;
+    async fn get_stock_take(&self, stock_take_id: StockTakeId) -> ResultDb<StockTake>;
+    async fn add_stock_take(&self, category_id: Option<CategoryId>, location_id: Option<LocationId>) -> ResultDb<StockTake>;
+    async fn remove_stock_take(&self, stock_take_id: StockTakeId) -> ResultDb<()>;
+    /// Records a scanned code, approved stock-takes can't be scanned into.
+    async fn add_stock_take_scan(&self, stock_take_id: StockTakeId, item_code: &str) -> ResultDb<StockTake>;
+    /// Compares the scanned codes with the objects in stock-take's scope.
+    async fn get_stock_take_report(&self, stock_take_id: StockTakeId) -> ResultDb<StockTakeReport>;
+    /// Applies the corrections and closes the stock-take, all at once. Each correction has to fix
+    /// a discrepancy of the report, as it's at the moment of the approval.
+    ///
+    /// New objects are put at stock-take's location.
+    async fn approve_stock_take(&self, stock_take_id: StockTakeId, corrections: &[StockTakeCorrection]) -> ResultDb<StockTake>;
+}
+
+#[async_trait::async_trait]
+impl StockTakeDB for Repository {
+    async fn get_stock_takes(&self) -> ResultDb<Vec<StockTake>> {
+        Ok(sqlx::query_as!(
+            StockTake,
+            r#"
+                SELECT
+                    stock_take.id,
+                    stock_take.category_id as "category_id: CategoryId",
+                    category.name as "category_name?",
+                    stock_take.location_id as "location_id: LocationId",
+                    location.name as "location_name?",
+                    stock_take.created_at,
+                    stock_take.approved_at,
+                    (SELECT COUNT(*) FROM stock_take_scan WHERE stock_take_scan.stock_take_id = stock_take.id) as "scans_count!"
+                FROM
+                    stock_take
+                LEFT JOIN
+                    category ON category.id = stock_take.category_id
+                LEFT JOIN
+                    location ON location.id = stock_take.location_id
+                WHERE
+                    NOT removal_pending('stock_take', stock_take.id)
+                ORDER BY
+                    stock_take.created_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn get_stock_take(&self, stock_take_id: StockTakeId) -> ResultDb<StockTake> {
+        sqlx::query_as!(
+            StockTake,
+            r#"
+                SELECT
+                    stock_take.id,
+                    stock_take.category_id as "category_id: CategoryId",
+                    category.name as "category_name?",
+                    stock_take.location_id as "location_id: LocationId",
+                    location.name as "location_name?",
+                    stock_take.created_at,
+                    stock_take.approved_at,
+                    (SELECT COUNT(*) FROM stock_take_scan WHERE stock_take_scan.stock_take_id = stock_take.id) as "scans_count!"
+                FROM
+                    stock_take
+                LEFT JOIN
+                    category ON category.id = stock_take.category_id
+                LEFT JOIN
+                    location ON location.id = stock_take.location_id
+                WHERE
+                    stock_take.id = $1 AND NOT removal_pending('stock_take', stock_take.id)
+            "#,
+            stock_take_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn add_stock_take(&self, category_id: Option<CategoryId>, location_id: Option<LocationId>) -> ResultDb<StockTake> {
+        let stock_take_id = sqlx::query_scalar!(
+            r#"
+                INSERT INTO stock_take (category_id, location_id)
+                VALUES ($1, $2)
+                RETURNING id as "id: StockTakeId"
+            "#,
+            category_id as _,
+            location_id as _
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.get_stock_take(stock_take_id).await
+    }
+
+    async fn remove_stock_take(&self, stock_take_id: StockTakeId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM stock_take
+                WHERE id = $1
+            ",
+            stock_take_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn add_stock_take_scan(&self, stock_take_id: StockTakeId, item_code: &str) -> ResultDb<StockTake> {
+        if self.get_stock_take(stock_take_id).await?.approved_at.is_some() {
+            return Err(DbError::StockTakeApproved);
+        }
+
+        sqlx::query!(
+            "
+                INSERT INTO stock_take_scan (stock_take_id, item_code)
+                VALUES ($1, $2)
+            ",
+            stock_take_id as _,
+            item_code
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.get_stock_take(stock_take_id).await
+    }
+
+    async fn get_stock_take_report(&self, stock_take_id: StockTakeId) -> ResultDb<StockTakeReport> {
+        select_report(&mut *self.pool.acquire().await?, stock_take_id).await
+    }
+
+    async fn approve_stock_take(&self, stock_take_id: StockTakeId, corrections: &[StockTakeCorrection]) -> ResultDb<StockTake> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Locked, so that the stock-take isn't approved twice at once
+        let stock_take = sqlx::query!(
+            r#"
+                SELECT location_id as "location_id: LocationId", approved_at
+                FROM stock_take
+                WHERE id = $1 AND NOT removal_pending('stock_take', id)
+                FOR UPDATE
+            "#,
+            stock_take_id as _
+        )
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        if stock_take.approved_at.is_some() {
+            return Err(DbError::StockTakeApproved);
+        }
+
+        // The page sends the corrections for the report it has shown, which might be outdated by now
+        let report = select_report(&mut transaction, stock_take_id).await?;
+        for (idx, correction) in corrections.iter().enumerate() {
+            let in_report = match correction {
+                StockTakeCorrection::RemoveObject(object_id) => report.missing.iter().any(|missing| missing.object_id == *object_id),
+                // Codes of known objects are counted elsewhere, and can't be added twice
+                StockTakeCorrection::AddObject { item_code, .. } => report.unexpected.iter()
+                    .any(|scan| scan.item_code == *item_code && scan.item_name.is_none()),
+            };
+            if !in_report || corrections[..idx].contains(correction) {
+                return Err(DbError::CorrectionNotInReport);
+            }
+        }
+
+        let mut changed_item_ids = vec![];
+        for correction in corrections {
+            match correction {
+                StockTakeCorrection::RemoveObject(object_id) => {
+                    changed_item_ids.push(delete_item_object(&mut *transaction, *object_id).await?);
+                },
+                StockTakeCorrection::AddObject { item_id, item_code } => {
+                    insert_item_object(&mut *transaction, *item_id, item_code, stock_take.location_id, None, None).await?;
+                    changed_item_ids.push(*item_id);
+                },
+            }
+        }
+        enqueue_stock_changed(&mut transaction, &changed_item_ids).await?;
+
+        sqlx::query!(
+            "
+                UPDATE stock_take
+                SET approved_at = now()
+                WHERE id = $1
+            ",
+            stock_take_id as _
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        self.get_stock_take(stock_take_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::db::{fixtures::with_category, item::ItemsDB};
+
+    #[sqlx::test]
+    async fn approval_applies_only_the_corrections_of_the_report(pool: PgPool) {
+        let (db, category) = with_category(pool).await;
+        let item = db.add_item("Светр", category.id).await.unwrap();
+        let object = db.add_item_object(item.id, "A1", None, None).await.unwrap();
+        let stock_take = db.add_stock_take(Some(category.id), None).await.unwrap();
+
+        let unknown = StockTakeCorrection::RemoveObject(ItemObjectId(Uuid::new_v4()));
+        assert!(matches!(db.approve_stock_take(stock_take.id, &[unknown]).await, Err(DbError::CorrectionNotInReport)));
+
+        let removal = StockTakeCorrection::RemoveObject(object.id);
+        db.approve_stock_take(stock_take.id, &[removal.clone()]).await.unwrap();
+        assert!(db.get_item_objects(item.id).await.unwrap().is_empty());
+        assert!(matches!(db.approve_stock_take(stock_take.id, &[removal]).await, Err(DbError::StockTakeApproved)));
+    }
+}
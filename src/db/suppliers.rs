@@ -0,0 +1,292 @@
+use futures::{StreamExt, TryStreamExt};
+
+use crate::data::{suppliers::{Supplier, SupplierId, PurchaseOrder, PurchaseOrderId, PurchaseOrderIncomplete, PurchaseOrderLine, PurchaseOrderLineId}, item::{ItemId, ItemObject}, locations::LocationId};
+
+use super::{ResultDb, Repository, DbError, item::insert_item_object, webhooks::enqueue_stock_changed};
+
+impl PurchaseOrderIncomplete {
+    pub async fn fetch_related(self, repo: &impl SupplierDB) -> ResultDb<PurchaseOrder> {
+        let lines = repo.get_purchase_order_lines(self.id).await?;
+
+        Ok(PurchaseOrder {
+            id: self.id,
+            supplier: self.supplier,
+            created_at: self.created_at,
+            lines,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+pub trait SupplierDB {
+    async fn get_suppliers(&self) -> ResultDb<Vec<Supplier>>;
+    async fn add_supplier(&self, name: &str, contact: Option<&str>) -> ResultDb<Supplier>;
+    async fn remove_supplier(&self, supplier_id: SupplierId) -> ResultDb<()>;
+    /// Purchase orders, newest first.
+    async fn get_purchase_orders(&self) -> ResultDb<Vec<PurchaseOrder>>;
+    async fn get_purchase_order_lines(&self, order_id: PurchaseOrderId) -> ResultDb<Vec<PurchaseOrderLine>>;
+    async fn add_purchase_order(&self, supplier_id: SupplierId) -> ResultDb<PurchaseOrder>;
+    async fn remove_purchase_order(&self, order_id: PurchaseOrderId) -> ResultDb<()>;
+    async fn add_purchase_order_line(&self, order_id: PurchaseOrderId, item_id: ItemId, quantity: i32) -> ResultDb<PurchaseOrderLine>;
+    async fn remove_purchase_order_line(&self, line_id: PurchaseOrderLineId) -> ResultDb<()>;
+    /// Creates an object at the location for every received code and counts them as received on the line.
+    ///
+    /// Everything happens in one transaction, receiving more than is left on the line fails.
+    async fn receive_purchase_order_line(
+        &self,
+        line_id: PurchaseOrderLineId,
+        item_codes: &[String],
+        location_id: Option<LocationId>,
+    ) -> ResultDb<(PurchaseOrderLine, Vec<ItemObject>)>;
+}
+
+#[async_trait::async_trait]
+impl SupplierDB for Repository {
+    async fn get_suppliers(&self) -> ResultDb<Vec<Supplier>> {
+        Ok(sqlx::query_as!(
+            Supplier,
+            "
+                SELECT id, name, contact
+                FROM supplier
+                WHERE NOT removal_pending('supplier', id)
+                ORDER BY name
+            "
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_supplier(&self, name: &str, contact: Option<&str>) -> ResultDb<Supplier> {
+        Ok(sqlx::query_as!(
+            Supplier,
+            "
+                INSERT INTO supplier (name, contact)
+                VALUES ($1, $2)
+                RETURNING id, name, contact
+            ",
+            name,
+            contact
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn remove_supplier(&self, supplier_id: SupplierId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM supplier
+                WHERE id = $1
+            ",
+            supplier_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_purchase_orders(&self) -> ResultDb<Vec<PurchaseOrder>> {
+        Ok(futures::stream::iter(
+            sqlx::query!(
+                r#"
+                    SELECT
+                        purchase_order.id as "id: PurchaseOrderId",
+                        purchase_order.created_at,
+                        supplier.id as "supplier_id: SupplierId",
+                        supplier.name as supplier_name,
+                        supplier.contact as supplier_contact
+                    FROM
+                        purchase_order
+                    INNER JOIN
+                        supplier ON supplier.id = purchase_order.supplier_id
+                    WHERE
+                        NOT removal_pending('purchase_order', purchase_order.id)
+                    ORDER BY
+                        purchase_order.created_at DESC
+                "#
+            )
+            .fetch_all(&self.pool)
+            .await?
+        )
+        .map(|order| PurchaseOrderIncomplete {
+            id: order.id,
+            supplier: Supplier {
+                id: order.supplier_id,
+                name: order.supplier_name,
+                contact: order.supplier_contact,
+            },
+            created_at: order.created_at,
+        }.fetch_related(self))
+        .buffered(10)
+        .try_collect()
+        .await?)
+    }
+
+    async fn get_purchase_order_lines(&self, order_id: PurchaseOrderId) -> ResultDb<Vec<PurchaseOrderLine>> {
+        Ok(sqlx::query_as!(
+            PurchaseOrderLine,
+            r#"
+                SELECT
+                    purchase_order_line.id,
+                    purchase_order_line.order_id,
+                    purchase_order_line.item_id,
+                    item.name as item_name,
+                    purchase_order_line.quantity,
+                    purchase_order_line.received_quantity
+                FROM
+                    purchase_order_line
+                INNER JOIN
+                    item ON item.id = purchase_order_line.item_id
+                WHERE
+                    purchase_order_line.order_id = $1 AND NOT removal_pending('purchase_order_line', purchase_order_line.id)
+                ORDER BY
+                    item.name
+            "#,
+            order_id as _
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_purchase_order(&self, supplier_id: SupplierId) -> ResultDb<PurchaseOrder> {
+        let supplier = sqlx::query_as!(
+            Supplier,
+            "
+                SELECT id, name, contact
+                FROM supplier
+                WHERE id = $1 AND NOT removal_pending('supplier', id)
+            ",
+            supplier_id as _
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        let order = sqlx::query!(
+            r#"
+                INSERT INTO purchase_order (supplier_id)
+                VALUES ($1)
+                RETURNING id as "id: PurchaseOrderId", created_at
+            "#,
+            supplier_id as _
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(PurchaseOrder {
+            id: order.id,
+            supplier,
+            created_at: order.created_at,
+            lines: vec![],
+        })
+    }
+
+    async fn remove_purchase_order(&self, order_id: PurchaseOrderId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM purchase_order
+                WHERE id = $1
+            ",
+            order_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn add_purchase_order_line(&self, order_id: PurchaseOrderId, item_id: ItemId, quantity: i32) -> ResultDb<PurchaseOrderLine> {
+        sqlx::query_as!(
+            PurchaseOrderLine,
+            r#"
+                WITH inserted_lines AS (
+                    INSERT INTO purchase_order_line (order_id, item_id, quantity)
+                    SELECT $1, $2, $3
+                    WHERE NOT removal_pending('purchase_order', $1) AND NOT removal_pending('item', $2)
+                    RETURNING id, order_id, item_id, quantity, received_quantity
+                )
+
+                SELECT
+                    inserted_lines.id,
+                    inserted_lines.order_id,
+                    inserted_lines.item_id,
+                    item.name as item_name,
+                    inserted_lines.quantity,
+                    inserted_lines.received_quantity
+                FROM
+                    inserted_lines
+                INNER JOIN
+                    item ON item.id = inserted_lines.item_id
+            "#,
+            order_id as _,
+            item_id as _,
+            quantity
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn remove_purchase_order_line(&self, line_id: PurchaseOrderLineId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM purchase_order_line
+                WHERE id = $1
+            ",
+            line_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn receive_purchase_order_line(
+        &self,
+        line_id: PurchaseOrderLineId,
+        item_codes: &[String],
+        location_id: Option<LocationId>,
+    ) -> ResultDb<(PurchaseOrderLine, Vec<ItemObject>)> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Locks the line, so that concurrent receiving can't go over the ordered quantity
+        let line = sqlx::query_as!(
+            PurchaseOrderLine,
+            r#"
+                UPDATE purchase_order_line
+                SET received_quantity = purchase_order_line.received_quantity + $2
+                FROM item
+                WHERE
+                    purchase_order_line.id = $1 AND item.id = purchase_order_line.item_id
+                    AND NOT removal_pending('purchase_order_line', purchase_order_line.id)
+                    AND NOT removal_pending('item', item.id)
+                RETURNING
+                    purchase_order_line.id,
+                    purchase_order_line.order_id,
+                    purchase_order_line.item_id,
+                    item.name as item_name,
+                    purchase_order_line.quantity,
+                    purchase_order_line.received_quantity
+            "#,
+            line_id as _,
+            item_codes.len() as i32
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        .map_err(|err| match &err {
+            sqlx::Error::Database(db_err) if db_err.is_check_violation() => DbError::ReceivedTooMany,
+            _ => err.into(),
+        })?
+        .ok_or(DbError::ItemNotFound)?;
+
+        let mut objects = Vec::with_capacity(item_codes.len());
+        for item_code in item_codes {
+            objects.push(insert_item_object(&mut *transaction, line.item_id, item_code, location_id, None, Some(line.id)).await?);
+        }
+        enqueue_stock_changed(&mut transaction, &[line.item_id]).await?;
+
+        transaction.commit().await?;
+
+        Ok((line, objects))
+    }
+}
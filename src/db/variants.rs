@@ -0,0 +1,100 @@
+use sqlx::types::Json;
+
+use crate::data::{attributes::{CategoryAttribute, AttributeKind}, item::{Item, ItemId, ItemVariantId}, variants::VariantAxis, webhooks::WebhookEvent};
+
+use super::{ResultDb, Repository, DbError, item::{select_item, lock_item}, webhooks::enqueue_event};
+
+#[async_trait::async_trait]
+pub trait VariantDB {
+    /// Adds a variant for every combination of the options of the `axes`, which the item doesn't have yet.
+    ///
+    /// The axes must be choice attributes of the item's category, and the options must be theirs.
+    async fn add_item_variants(&self, item_id: ItemId, axes: &[VariantAxis]) -> ResultDb<Item>;
+    /// Removes the variant, unless it has objects. Returns the item it was of.
+    async fn remove_item_variant(&self, variant_id: ItemVariantId) -> ResultDb<ItemId>;
+}
+
+#[async_trait::async_trait]
+impl VariantDB for Repository {
+    async fn add_item_variants(&self, item_id: ItemId, axes: &[VariantAxis]) -> ResultDb<Item> {
+        let mut transaction = self.pool.begin().await?;
+
+        lock_item(&mut transaction, self, item_id, None).await?;
+        let attributes = sqlx::query_as!(
+            CategoryAttribute,
+            r#"
+                SELECT
+                    category_attribute.id,
+                    category_attribute.category_id,
+                    category_attribute.name,
+                    category_attribute.kind as "kind: AttributeKind",
+                    category_attribute.unit,
+                    category_attribute.options
+                FROM category_attribute
+                INNER JOIN item ON item.category_id = category_attribute.category_id
+                WHERE item.id = $1
+            "#,
+            item_id as _
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        for axis in axes {
+            let attribute = attributes.iter()
+                .find(|attribute| attribute.id == axis.attribute_id)
+                .ok_or(DbError::ItemNotFound)?;
+            let is_valid = attribute.kind == AttributeKind::Enum
+                && axis.options.iter().all(|option| attribute.options.contains(option));
+            if !is_valid {
+                return Err(DbError::InvalidAttribute(attribute.name.clone()));
+            }
+        }
+
+        // Variants made before keep their objects, only the missing combinations are added
+        for combination in VariantAxis::combinations(axes) {
+            sqlx::query!(
+                "
+                    INSERT INTO item_variant (item_id, name, attributes)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (item_id, name) DO NOTHING
+                ",
+                item_id as _,
+                combination.name,
+                Json(&combination.attributes) as _
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        let item = select_item(&mut transaction, item_id).await?;
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemUpdated(item.clone())).await?;
+
+        transaction.commit().await?;
+
+        Ok(item)
+    }
+
+    async fn remove_item_variant(&self, variant_id: ItemVariantId) -> ResultDb<ItemId> {
+        let mut transaction = self.pool.begin().await?;
+
+        let item_id = sqlx::query_scalar!(
+            r#"
+                DELETE FROM item_variant
+                WHERE id = $1
+                RETURNING item_id as "item_id: ItemId"
+            "#,
+            variant_id as _
+        )
+        .fetch_optional(&mut *transaction)
+        .await?
+        .ok_or(DbError::ItemNotFound)?;
+
+        let item = select_item(&mut transaction, item_id).await?;
+        enqueue_event(&mut *transaction, &WebhookEvent::ItemUpdated(item)).await?;
+
+        // The deferred check of the objects fails here, if the variant has them
+        transaction.commit().await?;
+
+        Ok(item_id)
+    }
+}
@@ -0,0 +1,265 @@
+use chrono::{DateTime, Utc};
+use sqlx::{types::Json, PgConnection, PgExecutor};
+
+use crate::data::{webhooks::{Webhook, WebhookId, WebhookEvent, WebhookEventType, WebhookDelivery, WebhookDeliveryId, WebhookDeliveryStatus}, item::ItemId};
+
+use super::{ResultDb, Repository, DbError};
+
+/// A delivery taken for an attempt, with everything needed to send it.
+pub struct DueDelivery {
+    pub id: WebhookDeliveryId,
+    pub url: String,
+    pub secret: String,
+    pub event_type: WebhookEventType,
+    pub payload: serde_json::Value,
+    /// Attempts made before this one.
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Queues an event for every webhook subscribed to its type.
+///
+/// Takes an executor, so that the event is queued in the same transaction as the change.
+pub(super) async fn enqueue_event<'e>(executor: impl PgExecutor<'e>, event: &WebhookEvent) -> ResultDb<()> {
+    sqlx::query!(
+        "
+            INSERT INTO webhook_delivery (webhook_id, event_type, payload)
+            SELECT id, $1, $2
+            FROM webhook
+            WHERE $1 = ANY(event_types)
+        ",
+        event.event_type() as _,
+        Json(event) as _
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Queues the current stock of the items, after their objects were added, removed or sold.
+pub(super) async fn enqueue_stock_changed(conn: &mut PgConnection, item_ids: &[ItemId]) -> ResultDb<()> {
+    let stock = sqlx::query!(
+        r#"
+            SELECT item.id as "item_id: ItemId", COUNT(item_objects.id) as "in_stock!"
+            FROM item
+            LEFT JOIN item_objects ON item_objects.item_id = item.id AND item_objects.sold_at IS NULL
+            WHERE item.id = ANY($1)
+            GROUP BY item.id
+        "#,
+        item_ids as _
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for item in stock {
+        enqueue_event(&mut *conn, &WebhookEvent::StockChanged { item_id: item.item_id, in_stock: item.in_stock }).await?;
+    }
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+pub trait WebhookDB {
+    async fn get_webhooks(&self) -> ResultDb<Vec<Webhook>>;
+    async fn add_webhook(&self, url: &str, event_types: &[WebhookEventType], secret: &str) -> ResultDb<Webhook>;
+    async fn remove_webhook(&self, webhook_id: WebhookId) -> ResultDb<()>;
+    /// The latest deliveries of a webhook, newest first.
+    async fn get_webhook_deliveries(&self, webhook_id: WebhookId, limit: i64) -> ResultDb<Vec<WebhookDelivery>>;
+    /// Queues a ping to the webhook and takes it for an immediate attempt.
+    async fn add_ping_delivery(&self, webhook_id: WebhookId, lease_secs: f64) -> ResultDb<DueDelivery>;
+    /// Takes the deliveries due for an attempt, they aren't taken again until the lease expires.
+    async fn take_due_deliveries(&self, limit: i64, lease_secs: f64) -> ResultDb<Vec<DueDelivery>>;
+    /// Records the outcome of an attempt, the delivery is retried at `next_attempt_at`, if it's still pending.
+    async fn record_delivery_attempt(
+        &self,
+        delivery_id: WebhookDeliveryId,
+        status: WebhookDeliveryStatus,
+        status_code: Option<i32>,
+        error: Option<&str>,
+        next_attempt_at: DateTime<Utc>,
+    ) -> ResultDb<WebhookDelivery>;
+}
+
+#[async_trait::async_trait]
+impl WebhookDB for Repository {
+    async fn get_webhooks(&self) -> ResultDb<Vec<Webhook>> {
+        Ok(sqlx::query_as!(
+            Webhook,
+            r#"
+                SELECT id, url, event_types as "event_types: Vec<WebhookEventType>", created_at
+                FROM webhook
+                WHERE NOT removal_pending('webhook', id)
+                ORDER BY created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_webhook(&self, url: &str, event_types: &[WebhookEventType], secret: &str) -> ResultDb<Webhook> {
+        Ok(sqlx::query_as!(
+            Webhook,
+            r#"
+                INSERT INTO webhook (url, event_types, secret)
+                VALUES ($1, $2, $3)
+                RETURNING id, url, event_types as "event_types: Vec<WebhookEventType>", created_at
+            "#,
+            url,
+            event_types as _,
+            secret
+        )
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn remove_webhook(&self, webhook_id: WebhookId) -> ResultDb<()> {
+        sqlx::query!(
+            "
+                DELETE FROM webhook
+                WHERE id = $1
+            ",
+            webhook_id as _
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_webhook_deliveries(&self, webhook_id: WebhookId, limit: i64) -> ResultDb<Vec<WebhookDelivery>> {
+        Ok(sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+                SELECT
+                    id,
+                    webhook_id,
+                    event_type as "event_type: WebhookEventType",
+                    status as "status: WebhookDeliveryStatus",
+                    attempts,
+                    next_attempt_at,
+                    last_status_code,
+                    last_error,
+                    created_at,
+                    delivered_at
+                FROM webhook_delivery
+                WHERE webhook_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+            "#,
+            webhook_id as _,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn add_ping_delivery(&self, webhook_id: WebhookId, lease_secs: f64) -> ResultDb<DueDelivery> {
+        sqlx::query_as!(
+            DueDelivery,
+            r#"
+                WITH inserted AS (
+                    INSERT INTO webhook_delivery (webhook_id, event_type, payload, next_attempt_at)
+                    SELECT id, 'ping', $2, now() + make_interval(secs => $3)
+                    FROM webhook
+                    WHERE id = $1 AND NOT removal_pending('webhook', id)
+                    RETURNING *
+                )
+
+                SELECT
+                    inserted.id,
+                    webhook.url,
+                    webhook.secret,
+                    inserted.event_type as "event_type: WebhookEventType",
+                    inserted.payload,
+                    inserted.attempts,
+                    inserted.created_at
+                FROM inserted
+                INNER JOIN webhook ON webhook.id = inserted.webhook_id
+            "#,
+            webhook_id as _,
+            Json(WebhookEvent::Ping) as _,
+            lease_secs
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+
+    async fn take_due_deliveries(&self, limit: i64, lease_secs: f64) -> ResultDb<Vec<DueDelivery>> {
+        // Skipping the locked rows lets several servers share the queue
+        Ok(sqlx::query_as!(
+            DueDelivery,
+            r#"
+                WITH due AS (
+                    SELECT id
+                    FROM webhook_delivery
+                    WHERE status = 'pending' AND next_attempt_at <= now()
+                    ORDER BY next_attempt_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+
+                UPDATE webhook_delivery
+                SET next_attempt_at = now() + make_interval(secs => $2)
+                FROM due, webhook
+                WHERE webhook_delivery.id = due.id AND webhook.id = webhook_delivery.webhook_id
+                RETURNING
+                    webhook_delivery.id,
+                    webhook.url,
+                    webhook.secret,
+                    webhook_delivery.event_type as "event_type: WebhookEventType",
+                    webhook_delivery.payload,
+                    webhook_delivery.attempts,
+                    webhook_delivery.created_at
+            "#,
+            limit,
+            lease_secs
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn record_delivery_attempt(
+        &self,
+        delivery_id: WebhookDeliveryId,
+        status: WebhookDeliveryStatus,
+        status_code: Option<i32>,
+        error: Option<&str>,
+        next_attempt_at: DateTime<Utc>,
+    ) -> ResultDb<WebhookDelivery> {
+        sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+                UPDATE webhook_delivery
+                SET
+                    attempts = attempts + 1,
+                    status = $2,
+                    last_status_code = $3,
+                    last_error = $4,
+                    next_attempt_at = $5,
+                    delivered_at = CASE WHEN $2 = 'delivered'::webhook_delivery_status THEN now() END
+                WHERE id = $1
+                RETURNING
+                    id,
+                    webhook_id,
+                    event_type as "event_type: WebhookEventType",
+                    status as "status: WebhookDeliveryStatus",
+                    attempts,
+                    next_attempt_at,
+                    last_status_code,
+                    last_error,
+                    created_at,
+                    delivered_at
+            "#,
+            delivery_id as _,
+            status as _,
+            status_code,
+            error,
+            next_attempt_at
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(DbError::ItemNotFound)
+    }
+}
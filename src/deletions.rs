@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use crate::{
+    data::{deletions::{Deletion, PendingDeletion, PendingDeletionId, UNDO_WINDOW}, live::LiveEvent},
+    db::{
+        Repository, DbError, AnyPrecondition,
+        deletions::DeletionDB,
+        item::ItemsDB,
+        attachments::AttachmentDB,
+        attributes::AttributeDB,
+        categories::CategoryDB,
+        locations::LocationDB,
+        suppliers::SupplierDB,
+        stock_takes::StockTakeDB,
+        saved_searches::SavedSearchDB,
+        webhooks::WebhookDB,
+        variants::VariantDB,
+    },
+    attachments::AttachmentStorage,
+    live::LiveUpdates,
+};
+
+/// How often the queue is checked for the removals, which can't be undone anymore.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const BATCH_SIZE: i64 = 50;
+
+/// Queues the removal, nothing is removed until the undo window passes.
+pub async fn schedule(db: &Repository, deletion: Deletion, precondition: Option<&dyn AnyPrecondition>) -> Result<PendingDeletion, DbError> {
+    db.add_pending_deletion(&deletion, UNDO_WINDOW.as_secs_f64(), precondition).await
+}
+
+/// Carries out the removal without waiting for the worker, unless the worker has carried it out already.
+pub async fn finish(
+    db: &Repository,
+    storage: &AttachmentStorage,
+    live: &LiveUpdates,
+    deletion_id: PendingDeletionId,
+) -> Result<(), DbError> {
+    let deletion = match db.take_pending_deletion(deletion_id).await {
+        Err(DbError::ItemNotFound) => return Ok(()),
+        deletion => deletion?,
+    };
+    // E.g. a supplier with orders is kept, the page shows why
+    delete(db, storage, live, deletion).await.map_err(DbError::referenced_as_in_use)
+}
+
+/// Removes the entity, the open pages learn about it only now.
+async fn delete(db: &Repository, storage: &AttachmentStorage, live: &LiveUpdates, deletion: Deletion) -> Result<(), DbError> {
+    match deletion {
+        Deletion::Item { item_id } => {
+            // Attachment records are removed with the item, but the files have to be removed by hand
+            let attachments = db.get_item_attachments(item_id).await?;
+            db.remove_item(item_id, None).await?;
+            for attachment in &attachments {
+                storage.remove_files(attachment);
+            }
+            live.publish(LiveEvent::ItemRemoved(item_id));
+        },
+        Deletion::Items { item_ids } => {
+            let mut attachments = vec![];
+            for &item_id in &item_ids {
+                attachments.push(db.get_item_attachments(item_id).await?);
+            }
+
+            let outcome = db.remove_items(&item_ids).await?;
+            for (item_id, attachments) in item_ids.iter().zip(&attachments) {
+                if outcome.succeeded.contains(item_id) {
+                    attachments.iter().for_each(|attachment| storage.remove_files(attachment));
+                }
+            }
+            for &item_id in &outcome.succeeded {
+                live.publish(LiveEvent::ItemRemoved(item_id));
+            }
+
+            if let Some(reasons) = outcome.failure_reasons() {
+                return Err(DbError::PartlyFailed(reasons));
+            }
+        },
+        Deletion::ItemObject { item_object_id } => {
+            db.remove_item_object(item_object_id, None).await?;
+            live.publish(LiveEvent::ObjectsRemoved(vec![item_object_id]));
+        },
+        Deletion::ItemTag { item_id, tag_id } => {
+            db.remove_item_tag(item_id, tag_id).await?;
+            live.publish(LiveEvent::ItemUpdated(db.get_item(item_id).await?));
+        },
+        Deletion::ItemAttachment { attachment_id } => {
+            let attachment = db.remove_item_attachment(attachment_id).await?;
+            storage.remove_files(&attachment);
+        },
+        Deletion::ItemVariant { variant_id } => {
+            let item_id = db.remove_item_variant(variant_id).await?;
+            live.publish(LiveEvent::ItemUpdated(db.get_item(item_id).await?));
+        },
+        Deletion::Tag { tag_id } => {
+            db.remove_tag(tag_id, None).await?;
+            live.publish(LiveEvent::TagRemoved(tag_id));
+        },
+        Deletion::TagGroup { group_id } => {
+            db.remove_tag_group(group_id).await?;
+            live.publish(LiveEvent::TagGroupRemoved(group_id));
+        },
+        Deletion::Category { category_id } => {
+            db.remove_category(category_id, None).await?;
+            live.publish(LiveEvent::CategoryRemoved(category_id));
+        },
+        Deletion::CategoryAttribute { attribute_id } => db.remove_category_attribute(attribute_id).await?,
+        Deletion::Location { location_id } => db.remove_location(location_id).await?,
+        Deletion::Supplier { supplier_id } => db.remove_supplier(supplier_id).await?,
+        Deletion::PurchaseOrder { order_id } => db.remove_purchase_order(order_id).await?,
+        Deletion::PurchaseOrderLine { line_id } => db.remove_purchase_order_line(line_id).await?,
+        Deletion::StockTake { stock_take_id } => db.remove_stock_take(stock_take_id).await?,
+        Deletion::SavedSearch { user_id, saved_search_id } => db.remove_saved_search(user_id, saved_search_id).await?,
+        Deletion::Webhook { webhook_id } => db.remove_webhook(webhook_id).await?,
+    }
+
+    Ok(())
+}
+
+/// Carries out the removals, once they can't be undone, for as long as the server runs.
+pub async fn run_deletion_worker(db: Repository, storage: AttachmentStorage, live: LiveUpdates) {
+    let mut interval = actix_web::rt::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let due = match db.take_due_deletions(BATCH_SIZE).await {
+            Ok(due) => due,
+            Err(error) => {
+                log::error!("Could not take the pending deletions: {error:?}");
+                continue;
+            },
+        };
+
+        for deletion in due {
+            if let Err(error) = delete(&db, &storage, &live, deletion.clone()).await {
+                log::error!("Could not carry out {deletion:?}: {error:?}");
+            }
+        }
+    }
+}
@@ -2,6 +2,20 @@ pub mod app;
 pub mod data;
 #[cfg(feature = "ssr")]
 pub mod db;
+#[cfg(feature = "ssr")]
+pub mod attachments;
+#[cfg(feature = "ssr")]
+pub mod users;
+#[cfg(feature = "ssr")]
+pub mod api_tokens;
+#[cfg(feature = "ssr")]
+pub mod webhooks;
+#[cfg(feature = "ssr")]
+pub mod deletions;
+#[cfg(feature = "ssr")]
+pub mod live;
+#[cfg(feature = "ssr")]
+pub mod rest;
 pub mod server_funcs;
 pub mod ui;
 use cfg_if::cfg_if;
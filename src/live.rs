@@ -0,0 +1,75 @@
+use std::{convert::Infallible, future::{Ready, ready}, time::Duration};
+
+use actix_web::{FromRequest, web, HttpResponse, http::header::CACHE_CONTROL};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{data::{live::LiveEvent, api_tokens::ApiTokenScope}, api_tokens::ApiAccess};
+
+/// Events, which a slow page hasn't received yet, are kept up to this number.
+const CHANNEL_CAPACITY: usize = 256;
+/// A comment is sent this often, so that the idle connections aren't closed by the proxies.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Sends the changes to the pages open on this server.
+#[derive(Clone)]
+pub struct LiveUpdates {
+    sender: broadcast::Sender<LiveEvent>,
+}
+
+impl LiveUpdates {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        LiveUpdates {
+            sender
+        }
+    }
+
+    /// Sends the event to every open page, it's dropped if there are none.
+    pub fn publish(&self, event: LiveEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Default for LiveUpdates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromRequest for LiveUpdates {
+    type Error = Infallible;
+    type Future = Ready<Result<LiveUpdates, Infallible>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(Ok(req.app_data::<Self>().expect("LiveUpdates was not found").clone()))
+    }
+}
+
+fn server_sent_event(event: &LiveEvent) -> web::Bytes {
+    // PANIC: the events are plain data, which always serializes
+    format!("data: {}\n\n", serde_json::to_string(event).unwrap()).into()
+}
+
+/// Streams the changes as server-sent events, for as long as the page is open.
+#[actix_web::get("/events")]
+pub async fn live_events(live: LiveUpdates, access: ApiAccess) -> actix_web::Result<HttpResponse> {
+    access.require(ApiTokenScope::Read)?;
+
+    let receiver = live.sender.subscribe();
+    let events = futures::stream::unfold(receiver, |mut receiver| async move {
+        let message = match actix_web::rt::time::timeout(KEEP_ALIVE, receiver.recv()).await {
+            Ok(Ok(event)) => server_sent_event(&event),
+            Ok(Err(RecvError::Lagged(_))) => server_sent_event(&LiveEvent::Missed),
+            Ok(Err(RecvError::Closed)) => return None,
+            Err(_) => web::Bytes::from_static(b": keep-alive\n\n"),
+        };
+
+        Some((Ok::<_, Infallible>(message), receiver))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .streaming(events))
+}
@@ -5,7 +5,7 @@ async fn main() -> std::io::Result<()> {
     use actix_web::*;
     use leptos::*;
     use leptos_actix::{generate_route_list, LeptosRoutes};
-    use web_db::{app::*, db::Repository};
+    use web_db::{app::*, db::Repository, api_tokens, rest, webhooks, deletions, live::{LiveUpdates, live_events}, attachments::{self, AttachmentStorage, upload_attachment}, data::attachments::ATTACHMENTS_PATH};
 
     dotenvy::dotenv().expect("Dotenvy failed");
 
@@ -15,6 +15,12 @@ async fn main() -> std::io::Result<()> {
     let routes = generate_route_list(App);
 
     let db = Repository::new().await;
+    api_tokens::ensure_admin_token(&db).await.expect("Could not create the first admin token");
+    let attachment_storage = AttachmentStorage::from_env();
+    let live_updates = LiveUpdates::new();
+    actix_web::rt::spawn(webhooks::run_delivery_worker(db.clone()));
+    actix_web::rt::spawn(attachments::run_cleanup_worker(db.clone(), attachment_storage.clone()));
+    actix_web::rt::spawn(deletions::run_deletion_worker(db.clone(), attachment_storage.clone(), live_updates.clone()));
     println!("listening on http://{}", &addr);
 
     HttpServer::new(move || {
@@ -23,15 +29,24 @@ async fn main() -> std::io::Result<()> {
 
         App::new()
             .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
+            // versioned JSON API for the integrations
+            .service(rest::scope())
             // serve JS/WASM/CSS from `pkg`
             .service(Files::new("/pkg", format!("{site_root}/pkg")))
             // serve other assets from the `assets` directory
             .service(Files::new("/assets", site_root))
             // serve the favicon from /favicon.ico
             .service(favicon)
+            // serve uploaded item attachments
+            .service(upload_attachment)
+            .service(Files::new(ATTACHMENTS_PATH, attachment_storage.dir()))
+            // stream the changes to the open pages
+            .service(live_events)
             .leptos_routes(leptos_options.to_owned(), routes.to_owned(), App)
             .app_data(web::Data::new(leptos_options.to_owned()))
             .app_data(db.clone())
+            .app_data(attachment_storage.clone())
+            .app_data(live_updates.clone())
         //.wrap(middleware::Compress::default())
     })
     .bind(&addr)?
@@ -0,0 +1,632 @@
+//! Versioned JSON API for the integrations, which can't speak the server function encodings.
+//!
+//! Every request must have a token of a machine client in the `Authorization: Bearer` header,
+//! with a scope allowing it.
+//!
+//! Every representation carries an `ETag`: a `GET` is answered with `304 Not Modified` if it
+//! matches `If-None-Match`, and changes are refused with `412 Precondition Failed` if it doesn't
+//! match `If-Match`.
+//!
+//! Removals are queued as the pages' removals are, they're answered with `202 Accepted` and carried
+//! out once the undo window passes.
+
+use actix_web::{
+    web, get, post, put, delete, HttpRequest, HttpResponse, ResponseError,
+    http::{StatusCode, header::{self, EntityTag, Header, IfMatch, IfNoneMatch}},
+    error::{JsonPayloadError, QueryPayloadError},
+};
+use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::{OpenApi, ToSchema, IntoParams, Modify, openapi::security::{SecurityScheme, HttpAuthScheme, HttpBuilder}};
+
+use crate::{
+    data::{
+        categories::{Category, CategoryId},
+        item::{Tag, TagId, TagGroupId, Item, ItemId, ItemObject, ItemObjectId, ItemVariant, ItemVariantId, GroupedTags},
+        attributes::{AttributeValue, CategoryAttributeId, ItemAttributes},
+        locations::LocationId,
+        api_tokens::ApiTokenScope,
+        live::LiveEvent,
+        deletions::{Deletion, PendingDeletion, PendingDeletionId},
+    },
+    db::{Repository, DbError, Precondition, categories::CategoryDB, item::ItemsDB},
+    api_tokens::ApiAccess,
+    live::LiveUpdates,
+    deletions,
+};
+
+/// PostgreSQL codes of the constraint violations, which are the client's fault.
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+const NOT_NULL_VIOLATION: &str = "23502";
+const CHECK_VIOLATION: &str = "23514";
+
+#[derive(Debug, Display, From)]
+pub enum RestError {
+    Db(DbError),
+    #[display(fmt = "Некоректний запит: {}", _0)]
+    #[from(ignore)]
+    Invalid(String),
+}
+
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+impl RestError {
+    /// Status of a database error, constraint violations are reported as the client's errors.
+    fn db_status(error: &DbError) -> StatusCode {
+        let DbError::DbError(sqlx::Error::Database(error)) = error else {
+            return error.status_code();
+        };
+
+        match error.code().as_deref() {
+            Some(UNIQUE_VIOLATION | FOREIGN_KEY_VIOLATION) => StatusCode::CONFLICT,
+            Some(NOT_NULL_VIOLATION | CHECK_VIOLATION) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl ResponseError for RestError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RestError::Db(error) => Self::db_status(error),
+            RestError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error = match (self, self.status_code()) {
+            (RestError::Db(DbError::DbError(_)), StatusCode::CONFLICT) => "Об'єкт конфліктує з наявними".to_string(),
+            (RestError::Db(DbError::DbError(_)), StatusCode::UNPROCESSABLE_ENTITY) => "Некоректні дані".to_string(),
+            _ => self.to_string(),
+        };
+
+        let mut response = HttpResponse::build(self.status_code());
+        if self.status_code() == StatusCode::UNAUTHORIZED {
+            response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+        response.json(ErrorBody { error })
+    }
+}
+
+type RestResult = Result<HttpResponse, RestError>;
+
+/// Strong tag of a representation, a hash of its JSON, which stays the same across restarts and builds.
+fn etag_of(body: &[u8]) -> EntityTag {
+    EntityTag::new_strong(hex::encode(Sha256::digest(body)))
+}
+
+fn to_json(value: &impl Serialize) -> Vec<u8> {
+    // PANIC: the data types always serialize
+    serde_json::to_vec(value).unwrap()
+}
+
+/// Responds with the representation and its tag, or with `304 Not Modified`, if the client has it already.
+fn respond(req: &HttpRequest, status: StatusCode, value: &impl Serialize) -> HttpResponse {
+    let body = to_json(value);
+    let etag = etag_of(&body);
+
+    let not_modified = match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        Err(_) => false,
+    };
+    if not_modified && status == StatusCode::OK {
+        return HttpResponse::NotModified().insert_header(header::ETag(etag)).finish();
+    }
+
+    HttpResponse::build(status)
+        .insert_header(header::ETag(etag))
+        .content_type("application/json")
+        .body(body)
+}
+
+/// Responds with `201 Created`, pointing to the created resource.
+fn created(req: &HttpRequest, location: String, value: &impl Serialize) -> HttpResponse {
+    let mut response = respond(req, StatusCode::CREATED, value);
+    // PANIC: the location consists of the path and a uuid
+    response.headers_mut().insert(header::LOCATION, location.parse().unwrap());
+    response
+}
+
+/// The client's `If-Match`, a change is refused, unless it matches the current representation.
+///
+/// The repository checks it with the object locked, so a concurrent change can't slip in after the check.
+fn if_match(req: &HttpRequest) -> Option<IfMatch> {
+    // A missing header is parsed as an empty list, which would never match
+    if !req.headers().contains_key(header::IF_MATCH) {
+        return None;
+    }
+
+    // A malformed header matches nothing
+    Some(IfMatch::parse(req).unwrap_or(IfMatch::Items(vec![])))
+}
+
+impl<T: Serialize> Precondition<T> for IfMatch {
+    fn holds(&self, current: &T) -> bool {
+        match self {
+            IfMatch::Any => true,
+            IfMatch::Items(tags) => {
+                let etag = etag_of(&to_json(current));
+                tags.iter().any(|tag| tag.strong_eq(&etag))
+            },
+        }
+    }
+}
+
+fn precondition<T: Serialize>(if_match: &Option<IfMatch>) -> Option<&dyn Precondition<T>> {
+    if_match.as_ref().map(|if_match| if_match as _)
+}
+
+/// Queues the removal as the pages do, it's carried out once the undo window passes, `If-Match` is checked right away.
+///
+/// Responds with `202 Accepted`, the open pages learn about the removal when it's carried out.
+async fn schedule(req: &HttpRequest, db: &Repository, deletion: Deletion) -> RestResult {
+    let if_match = if_match(req);
+    let pending = deletions::schedule(db, deletion, if_match.as_ref().map(|if_match| if_match as _)).await?;
+    Ok(respond(req, StatusCode::ACCEPTED, &pending))
+}
+
+/// Unlike the server functions, the API isn't open to anonymous browsers even for reading, a token is always required.
+///
+/// Takes the result of the extraction, so that an unknown token is reported the API's way.
+fn authorize(access: Result<ApiAccess, DbError>, scope: ApiTokenScope) -> Result<(), RestError> {
+    match access? {
+        ApiAccess::Anonymous => Err(DbError::Unauthorized.into()),
+        access => Ok(access.require(scope)?),
+    }
+}
+
+fn check_name(name: &str) -> Result<&str, RestError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(RestError::Invalid("назва не може бути порожньою".to_string()));
+    }
+    Ok(name)
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct NewCategory {
+    pub name: String,
+    pub parent_id: Option<CategoryId>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CategoryUpdate {
+    pub name: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct NewTag {
+    pub name: String,
+    pub group_id: Option<TagGroupId>,
+    /// Display colour in `#rrggbb` form.
+    pub color: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TagUpdate {
+    pub name: String,
+    pub group_id: Option<TagGroupId>,
+    /// Display colour in `#rrggbb` form.
+    pub color: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct NewItem {
+    pub name: String,
+    pub category_id: CategoryId,
+}
+
+/// New name and category of an item, the values of the old category's attributes are kept.
+#[derive(Deserialize, ToSchema)]
+pub struct ItemUpdate {
+    pub name: String,
+    pub category_id: CategoryId,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct NewItemObject {
+    pub item_code: String,
+    pub location_id: Option<LocationId>,
+    /// One of the item's variants, if it has them.
+    #[serde(default)]
+    pub variant_id: Option<ItemVariantId>,
+}
+
+/// Filters of the item list, unset ones don't restrict it.
+#[derive(Deserialize, IntoParams)]
+pub struct ItemsQuery {
+    /// Part of the item's name.
+    pub q: Option<String>,
+    /// Category of the items, including its sub-categories.
+    #[param(value_type = Option<uuid::Uuid>)]
+    pub category_id: Option<CategoryId>,
+    #[param(value_type = Option<uuid::Uuid>)]
+    pub tag_id: Option<TagId>,
+    /// Location, where the items have objects in stock.
+    #[param(value_type = Option<uuid::Uuid>)]
+    pub location_id: Option<LocationId>,
+}
+
+#[utoipa::path(
+    get, path = "/rest/v1/categories",
+    responses((status = 200, body = [Category]), (status = 304))
+)]
+#[get("/categories")]
+async fn list_categories(req: HttpRequest, db: Repository, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    Ok(respond(&req, StatusCode::OK, &db.get_categories().await?))
+}
+
+#[utoipa::path(
+    post, path = "/rest/v1/categories",
+    request_body = NewCategory,
+    responses((status = 201, body = Category), (status = 409, body = ErrorBody), (status = 422, body = ErrorBody))
+)]
+#[post("/categories")]
+async fn create_category(req: HttpRequest, db: Repository, live: LiveUpdates, new_category: web::Json<NewCategory>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let name = check_name(&new_category.name)?;
+    let category = db.add_category(name, new_category.parent_id).await?;
+    live.publish(LiveEvent::CategoryAdded(category.clone()));
+    Ok(created(&req, format!("/rest/v1/categories/{}", category.id.0), &category))
+}
+
+#[utoipa::path(
+    get, path = "/rest/v1/categories/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 200, body = Category), (status = 304), (status = 404, body = ErrorBody))
+)]
+#[get("/categories/{id}")]
+async fn get_category(req: HttpRequest, db: Repository, id: web::Path<CategoryId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    Ok(respond(&req, StatusCode::OK, &db.get_category(*id).await?))
+}
+
+/// Renames a category, its former slug keeps redirecting to it.
+#[utoipa::path(
+    put, path = "/rest/v1/categories/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    request_body = CategoryUpdate,
+    responses(
+        (status = 200, body = Category),
+        (status = 404, body = ErrorBody),
+        (status = 412, body = ErrorBody),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[put("/categories/{id}")]
+async fn update_category(req: HttpRequest, db: Repository, live: LiveUpdates, id: web::Path<CategoryId>, update: web::Json<CategoryUpdate>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let name = check_name(&update.name)?;
+    let category = db.rename_category(*id, name, precondition(&if_match(&req))).await?;
+    live.publish(LiveEvent::CategoryRenamed(category.clone()));
+    Ok(respond(&req, StatusCode::OK, &category))
+}
+
+/// Queues the removal of a category, fails with `409 Conflict`, if it has sub-categories.
+/// A category, which still has items then, is kept.
+#[utoipa::path(
+    delete, path = "/rest/v1/categories/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 202, body = PendingDeletion), (status = 404, body = ErrorBody), (status = 409, body = ErrorBody), (status = 412, body = ErrorBody))
+)]
+#[delete("/categories/{id}")]
+async fn delete_category(req: HttpRequest, db: Repository, id: web::Path<CategoryId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    schedule(&req, &db, Deletion::Category { category_id: *id }).await
+}
+
+#[utoipa::path(
+    get, path = "/rest/v1/tags",
+    responses((status = 200, body = [Tag]), (status = 304))
+)]
+#[get("/tags")]
+async fn list_tags(req: HttpRequest, db: Repository, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    let grouped = db.get_tags().await?;
+    let tags: Vec<&Tag> = GroupedTags::all_tags(&grouped).collect();
+    Ok(respond(&req, StatusCode::OK, &tags))
+}
+
+#[utoipa::path(
+    post, path = "/rest/v1/tags",
+    request_body = NewTag,
+    responses((status = 201, body = Tag), (status = 409, body = ErrorBody), (status = 422, body = ErrorBody))
+)]
+#[post("/tags")]
+async fn create_tag(req: HttpRequest, db: Repository, live: LiveUpdates, new_tag: web::Json<NewTag>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let name = check_name(&new_tag.name)?;
+    let tag = db.add_tag(name, new_tag.group_id, &new_tag.color).await?;
+    live.publish(LiveEvent::TagAdded(tag.clone()));
+    Ok(created(&req, format!("/rest/v1/tags/{}", tag.id), &tag))
+}
+
+#[utoipa::path(
+    get, path = "/rest/v1/tags/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 200, body = Tag), (status = 304), (status = 404, body = ErrorBody))
+)]
+#[get("/tags/{id}")]
+async fn get_tag(req: HttpRequest, db: Repository, id: web::Path<TagId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    Ok(respond(&req, StatusCode::OK, &db.get_tag(*id).await?))
+}
+
+#[utoipa::path(
+    put, path = "/rest/v1/tags/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    request_body = TagUpdate,
+    responses(
+        (status = 200, body = Tag),
+        (status = 404, body = ErrorBody),
+        (status = 409, body = ErrorBody),
+        (status = 412, body = ErrorBody),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[put("/tags/{id}")]
+async fn update_tag(req: HttpRequest, db: Repository, live: LiveUpdates, id: web::Path<TagId>, update: web::Json<TagUpdate>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let name = check_name(&update.name)?;
+    let tag = db.update_tag(*id, name, update.group_id, &update.color, precondition(&if_match(&req))).await?;
+    live.publish(LiveEvent::TagUpdated(tag.clone()));
+    Ok(respond(&req, StatusCode::OK, &tag))
+}
+
+#[utoipa::path(
+    delete, path = "/rest/v1/tags/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 202, body = PendingDeletion), (status = 404, body = ErrorBody), (status = 412, body = ErrorBody))
+)]
+#[delete("/tags/{id}")]
+async fn delete_tag(req: HttpRequest, db: Repository, id: web::Path<TagId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    schedule(&req, &db, Deletion::Tag { tag_id: *id }).await
+}
+
+#[utoipa::path(
+    get, path = "/rest/v1/items",
+    params(ItemsQuery),
+    responses((status = 200, body = [Item]), (status = 304))
+)]
+#[get("/items")]
+async fn list_items(req: HttpRequest, db: Repository, query: web::Query<ItemsQuery>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    // Integrations expect exact matches only, so the query isn't corrected, unlike in the UI's search
+    let q = query.q.as_deref().map(str::trim);
+    let items = db.list_items(q, query.category_id, query.location_id, query.tag_id).await?;
+    Ok(respond(&req, StatusCode::OK, &items))
+}
+
+#[utoipa::path(
+    post, path = "/rest/v1/items",
+    request_body = NewItem,
+    responses((status = 201, body = Item), (status = 409, body = ErrorBody), (status = 422, body = ErrorBody))
+)]
+#[post("/items")]
+async fn create_item(req: HttpRequest, db: Repository, live: LiveUpdates, new_item: web::Json<NewItem>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let name = check_name(&new_item.name)?;
+    let item = db.add_item(name, new_item.category_id).await?;
+    live.publish(LiveEvent::ItemAdded(item.clone()));
+    Ok(created(&req, format!("/rest/v1/items/{}", item.id.0), &item))
+}
+
+#[utoipa::path(
+    get, path = "/rest/v1/items/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 200, body = Item), (status = 304), (status = 404, body = ErrorBody))
+)]
+#[get("/items/{id}")]
+async fn get_item(req: HttpRequest, db: Repository, id: web::Path<ItemId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    Ok(respond(&req, StatusCode::OK, &db.get_item(*id).await?))
+}
+
+#[utoipa::path(
+    put, path = "/rest/v1/items/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    request_body = ItemUpdate,
+    responses(
+        (status = 200, body = Item),
+        (status = 404, body = ErrorBody),
+        (status = 409, body = ErrorBody),
+        (status = 412, body = ErrorBody),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[put("/items/{id}")]
+async fn update_item(req: HttpRequest, db: Repository, live: LiveUpdates, id: web::Path<ItemId>, update: web::Json<ItemUpdate>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let name = check_name(&update.name)?;
+    let item = db.update_item(*id, name, update.category_id, precondition(&if_match(&req))).await?;
+    live.publish(LiveEvent::ItemUpdated(item.clone()));
+    Ok(respond(&req, StatusCode::OK, &item))
+}
+
+/// Queues the removal of an item with all of its objects.
+#[utoipa::path(
+    delete, path = "/rest/v1/items/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 202, body = PendingDeletion), (status = 404, body = ErrorBody), (status = 412, body = ErrorBody))
+)]
+#[delete("/items/{id}")]
+async fn delete_item(req: HttpRequest, db: Repository, id: web::Path<ItemId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    schedule(&req, &db, Deletion::Item { item_id: *id }).await
+}
+
+#[utoipa::path(
+    put, path = "/rest/v1/items/{id}/tags/{tag_id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the item"), ("tag_id" = uuid::Uuid, Path, description = "Id of the tag")),
+    responses((status = 204), (status = 404, body = ErrorBody))
+)]
+#[put("/items/{id}/tags/{tag_id}")]
+async fn add_item_tag(db: Repository, live: LiveUpdates, path: web::Path<(ItemId, TagId)>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let (item_id, tag_id) = path.into_inner();
+    let item = db.get_item(item_id).await?;
+    let tag = db.get_tag(tag_id).await?;
+
+    // Putting a tag is idempotent
+    if !item.tags.iter().any(|item_tag| item_tag.id == tag.id) {
+        db.add_item_tag(item_id, tag_id).await?;
+        live.publish(LiveEvent::ItemUpdated(db.get_item(item_id).await?));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    delete, path = "/rest/v1/items/{id}/tags/{tag_id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the item"), ("tag_id" = uuid::Uuid, Path, description = "Id of the tag")),
+    responses((status = 202, body = PendingDeletion), (status = 404, body = ErrorBody))
+)]
+#[delete("/items/{id}/tags/{tag_id}")]
+async fn delete_item_tag(req: HttpRequest, db: Repository, path: web::Path<(ItemId, TagId)>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Admin)?;
+    let (item_id, tag_id) = path.into_inner();
+    let item = db.get_item(item_id).await?;
+
+    if !item.tags.iter().any(|item_tag| item_tag.id == tag_id) {
+        return Err(DbError::ItemNotFound.into());
+    }
+    schedule(&req, &db, Deletion::ItemTag { item_id, tag_id }).await
+}
+
+/// Objects of an item, which are in stock.
+#[utoipa::path(
+    get, path = "/rest/v1/items/{id}/objects",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 200, body = [ItemObject]), (status = 304), (status = 404, body = ErrorBody))
+)]
+#[get("/items/{id}/objects")]
+async fn list_item_objects(req: HttpRequest, db: Repository, id: web::Path<ItemId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    let item = db.get_item(*id).await?;
+    Ok(respond(&req, StatusCode::OK, &item.objects))
+}
+
+#[utoipa::path(
+    post, path = "/rest/v1/items/{id}/objects",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    request_body = NewItemObject,
+    responses(
+        (status = 201, body = ItemObject),
+        (status = 404, body = ErrorBody),
+        (status = 409, body = ErrorBody),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[post("/items/{id}/objects")]
+async fn create_item_object(req: HttpRequest, db: Repository, live: LiveUpdates, id: web::Path<ItemId>, new_object: web::Json<NewItemObject>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::ObjectsWrite)?;
+    db.get_item(*id).await?;
+    let object = db.add_item_object(*id, new_object.item_code.trim(), new_object.location_id, new_object.variant_id).await?;
+    live.publish(LiveEvent::ItemUpdated(db.get_item(*id).await?));
+    Ok(created(&req, format!("/rest/v1/objects/{}", object.id.0), &object))
+}
+
+#[utoipa::path(
+    get, path = "/rest/v1/objects/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 200, body = ItemObject), (status = 304), (status = 404, body = ErrorBody))
+)]
+#[get("/objects/{id}")]
+async fn get_item_object(req: HttpRequest, db: Repository, id: web::Path<ItemObjectId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::Read)?;
+    Ok(respond(&req, StatusCode::OK, &db.get_item_object(*id).await?))
+}
+
+#[utoipa::path(
+    delete, path = "/rest/v1/objects/{id}",
+    params(("id" = uuid::Uuid, Path, description = "Id of the resource")),
+    responses((status = 202, body = PendingDeletion), (status = 404, body = ErrorBody), (status = 412, body = ErrorBody))
+)]
+#[delete("/objects/{id}")]
+async fn delete_item_object(req: HttpRequest, db: Repository, id: web::Path<ItemObjectId>, access: Result<ApiAccess, DbError>) -> RestResult {
+    authorize(access, ApiTokenScope::ObjectsWrite)?;
+    schedule(&req, &db, Deletion::ItemObject { item_object_id: *id }).await
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "web_db", version = "1", description = "Категорії, теги, товари та їхні об'єкти"),
+    paths(
+        list_categories, create_category, get_category, update_category, delete_category,
+        list_tags, create_tag, get_tag, update_tag, delete_tag,
+        list_items, create_item, get_item, update_item, delete_item, add_item_tag, delete_item_tag,
+        list_item_objects, create_item_object, get_item_object, delete_item_object,
+    ),
+    modifiers(&BearerToken),
+    security(("bearer" = [])),
+    components(schemas(
+        Category, CategoryId, Tag, TagId, TagGroupId, Item, ItemId, ItemObject, ItemObjectId, ItemVariant, ItemVariantId,
+        ItemAttributes, AttributeValue, CategoryAttributeId, LocationId,
+        NewCategory, CategoryUpdate, NewTag, TagUpdate, NewItem, ItemUpdate, NewItemObject, PendingDeletion, PendingDeletionId,
+        ErrorBody,
+    ))
+)]
+struct ApiDoc;
+
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        // PANIC: the components are set by the derive
+        let components = openapi.components.as_mut().unwrap();
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+#[get("/openapi.json")]
+async fn openapi_document() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+fn invalid_json(error: JsonPayloadError, _: &HttpRequest) -> actix_web::Error {
+    RestError::Invalid(error.to_string()).into()
+}
+
+fn invalid_query(error: QueryPayloadError, _: &HttpRequest) -> actix_web::Error {
+    RestError::Invalid(error.to_string()).into()
+}
+
+/// The `/rest/v1` scope with all of the API's endpoints.
+pub fn scope() -> actix_web::Scope {
+    web::scope("/rest/v1")
+        .app_data(web::JsonConfig::default().error_handler(invalid_json))
+        .app_data(web::QueryConfig::default().error_handler(invalid_query))
+        .service(openapi_document)
+        .service(list_categories)
+        .service(create_category)
+        .service(get_category)
+        .service(update_category)
+        .service(delete_category)
+        .service(list_tags)
+        .service(create_tag)
+        .service(get_tag)
+        .service(update_tag)
+        .service(delete_tag)
+        .service(list_items)
+        .service(create_item)
+        .service(get_item)
+        .service(update_item)
+        .service(delete_item)
+        .service(add_item_tag)
+        .service(delete_item_tag)
+        .service(list_item_objects)
+        .service(create_item_object)
+        .service(get_item_object)
+        .service(delete_item_object)
+}
@@ -0,0 +1,74 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::api_tokens::{ApiToken, ApiTokenId, ApiTokenScope, CreatedApiToken};
+
+#[server(GetApiTokens, "/api", "GetJson")]
+pub async fn get_api_tokens() -> Result<Vec<ApiToken>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, api_tokens::ApiTokenDB}, api_tokens::ApiAccess};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.get_api_tokens().await
+    }).await??)
+}
+
+#[server(AddApiToken, "/api")]
+pub async fn add_api_token(name: String, scope: ApiTokenScope) -> Result<CreatedApiToken, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, api_tokens::ApiTokenDB}, api_tokens::{ApiAccess, generate_secret, hash_secret}};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let secret = generate_secret();
+        let token = db.add_api_token(&name, scope, &hash_secret(&secret)).await?;
+        Ok::<_, DbError>(CreatedApiToken { token, secret })
+    }).await??)
+}
+
+#[server(RevokeApiToken, "/api")]
+pub async fn revoke_api_token(api_token_id: ApiTokenId) -> Result<(), ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, api_tokens::ApiTokenDB}, api_tokens::ApiAccess};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.revoke_api_token(api_token_id).await
+    }).await??)
+}
+
+/// Token the browser is signed in with, `None` for an anonymous one.
+#[server(GetSession, "/api", "GetJson")]
+pub async fn get_session() -> Result<Option<ApiToken>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::api_tokens::ApiAccess;
+
+    Ok(extract(|access: ApiAccess| async move {
+        match access {
+            ApiAccess::Anonymous => None,
+            ApiAccess::Token(token) => Some(token),
+        }
+    }).await?)
+}
+
+#[server(SignIn, "/api")]
+pub async fn sign_in(secret: String) -> Result<ApiToken, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, api_tokens::ApiTokenDB}, api_tokens::{hash_secret, set_session}};
+
+    let secret = secret.trim().to_string();
+    let token = extract(move |db: Repository| async move {
+        db.use_api_token(&hash_secret(&secret)).await?
+            .map(|token| (token, secret))
+            .ok_or(DbError::Unauthorized)
+    }).await??;
+
+    set_session(Some(&token.1));
+    Ok(token.0)
+}
+
+#[server(SignOut, "/api")]
+pub async fn sign_out() -> Result<(), ServerFnError> {
+    crate::api_tokens::set_session(None);
+    Ok(())
+}
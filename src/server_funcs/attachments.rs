@@ -0,0 +1,25 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{attachments::{ItemAttachment, ItemAttachmentId}, item::ItemId, deletions::PendingDeletion};
+
+#[server(GetItemAttachments, "/api", "GetJson")]
+pub async fn get_item_attachments(item_id: ItemId) -> Result<Vec<ItemAttachment>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, attachments::AttachmentDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_item_attachments(item_id).await
+    }).await??)
+}
+
+#[server(RemoveItemAttachment, "/api")]
+pub async fn remove_item_attachment(attachment_id: ItemAttachmentId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::ItemAttachment { attachment_id }, None).await
+    }).await??)
+}
@@ -0,0 +1,55 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{attributes::{CategoryAttribute, CategoryAttributeId, AttributeKind, AttributeInput, ItemAttributes}, categories::CategoryId, item::ItemId, deletions::PendingDeletion};
+
+#[server(GetAttributes, "/api", "GetJson")]
+pub async fn get_attributes() -> Result<Vec<CategoryAttribute>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, attributes::AttributeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(|db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_attributes().await
+    }).await??)
+}
+
+#[server(AddCategoryAttribute, "/api")]
+pub async fn add_category_attribute(
+    category_id: CategoryId,
+    name: String,
+    kind: AttributeKind,
+    unit: Option<String>,
+    #[server(default)] options: Vec<String>,
+) -> Result<CategoryAttribute, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, attributes::AttributeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_category_attribute(category_id, &name, kind, unit.as_deref(), &options).await
+    }).await??)
+}
+
+#[server(RemoveCategoryAttribute, "/api")]
+pub async fn remove_category_attribute(attribute_id: CategoryAttributeId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::CategoryAttribute { attribute_id }, None).await
+    }).await??)
+}
+
+#[server(SetItemAttributes, "/api")]
+pub async fn set_item_attributes(item_id: ItemId, #[server(default)] values: Vec<AttributeInput>) -> Result<ItemAttributes, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, attributes::AttributeDB, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let attributes = db.set_item_attributes(item_id, &values).await?;
+        live.publish(LiveEvent::ItemUpdated(db.get_item(item_id).await?));
+        Ok::<_, DbError>(attributes)
+    }).await??)
+}
@@ -1,33 +1,84 @@
 use leptos::{server, ServerFnError};
 
-use crate::data::categories::{Category, CategoryId};
+use crate::data::{categories::{Category, CategoryId, CategoryTree, ResolvedSlug}, deletions::PendingDeletion};
 
 #[server(AddCategory, "/api")]
-pub async fn add_category(category_name: String) -> Result<Category, ServerFnError> {
+pub async fn add_category(category_name: String, parent_id: Option<CategoryId>) -> Result<Category, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, categories::CategoryDB};
+    use crate::{db::{Repository, DbError, categories::CategoryDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
 
-    Ok(extract(|db: Repository| async move {
-        db.add_category(&category_name).await
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let category = db.add_category(&category_name, parent_id).await?;
+        live.publish(LiveEvent::CategoryAdded(category.clone()));
+        Ok::<_, DbError>(category)
     }).await??)
 }
 
 #[server(GetCategories, "/api", "GetJson")]
 pub async fn get_categories() -> Result<Vec<Category>, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, categories::CategoryDB};
+    use crate::{db::{Repository, categories::CategoryDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
 
-    Ok(extract(|db: Repository| async move {
+    Ok(extract(|db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
         db.get_categories().await
     }).await??)
 }
 
+#[server(GetCategoryTree, "/api", "GetJson")]
+pub async fn get_category_tree() -> Result<Vec<CategoryTree>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, categories::CategoryDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(|db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_category_tree().await
+    }).await??)
+}
+
+#[server(GetCategoryPath, "/api", "GetJson")]
+pub async fn get_category_path(category_id: CategoryId) -> Result<Vec<Category>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, categories::CategoryDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_category_path(category_id).await
+    }).await??)
+}
+
+#[server(ResolveCategorySlug, "/api", "GetJson")]
+pub async fn resolve_category_slug(slug: String) -> Result<ResolvedSlug, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, categories::CategoryDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.resolve_category_slug(&slug).await
+    }).await??)
+}
+
+#[server(RenameCategory, "/api")]
+pub async fn rename_category(category_id: CategoryId, category_name: String) -> Result<Category, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, categories::CategoryDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let category = db.rename_category(category_id, &category_name, None).await?;
+        live.publish(LiveEvent::CategoryRenamed(category.clone()));
+        Ok::<_, DbError>(category)
+    }).await??)
+}
+
 #[server(RemoveCategory, "/api")]
-pub async fn remove_category(category_id: CategoryId) -> Result<(), ServerFnError> {
+pub async fn remove_category(category_id: CategoryId) -> Result<PendingDeletion, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, categories::CategoryDB};
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
 
-    Ok(extract(move |db: Repository| async move {
-        db.remove_category(category_id).await
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::Category { category_id }, None).await
     }).await??)
-}
\ No newline at end of file
+}
@@ -0,0 +1,33 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::deletions::PendingDeletionId;
+
+/// Takes a removal off the queue, so the entity stays.
+#[server(UndoDeletion, "/api")]
+pub async fn undo_deletion(deletion_id: PendingDeletionId) -> Result<(), ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, deletions::DeletionDB}, api_tokens::ApiAccess};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(db.get_pending_deletion(deletion_id).await?.scope())?;
+        db.take_pending_deletion(deletion_id).await?;
+        Ok::<_, DbError>(())
+    }).await??)
+}
+
+/// Carries out a removal without waiting for the undo window to pass.
+#[server(FinishDeletion, "/api")]
+pub async fn finish_deletion(deletion_id: PendingDeletionId) -> Result<(), ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, deletions::DeletionDB}, attachments::AttachmentStorage, api_tokens::ApiAccess, live::LiveUpdates, deletions};
+
+    Ok(extract(move |db: Repository, storage: AttachmentStorage, live: LiveUpdates, access: ApiAccess| async move {
+        let deletion = match db.get_pending_deletion(deletion_id).await {
+            // The worker was first, when the undo window passed
+            Err(DbError::ItemNotFound) => return Ok(()),
+            deletion => deletion?,
+        };
+        access.require(deletion.scope())?;
+        deletions::finish(&db, &storage, &live, deletion_id).await
+    }).await??)
+}
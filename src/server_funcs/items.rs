@@ -1,103 +1,272 @@
 use leptos::{server, ServerFnError};
 
-use crate::data::item::{TagId, Item, Tag, ItemId, ItemObject, ItemObjectId};
+use crate::data::attributes::AttributeFilter;
+use crate::data::categories::CategoryId;
+use crate::data::search::SearchResults;
+use crate::data::locations::LocationId;
+use crate::data::item::{TagId, Item, Tag, ItemId, ItemObject, ItemObjectId, ItemVariantId, ItemObjectLookup, TagGroup, TagGroupId, GroupedTags, Suggestion};
+use crate::data::deletions::PendingDeletion;
+use crate::data::bulk::BulkOutcome;
+
+/// At most this many suggestions are given, whatever the caller asks for.
+pub const MAX_SUGGESTIONS: i64 = 20;
 
 #[server(SearchItems, "/api", "GetJson")]
-pub async fn search_items(query: Option<String>, #[server(default)] tags_filtered: Vec<String>, category: String) -> Result<Vec<Item>, ServerFnError> {
+pub async fn search_items(
+    query: Option<String>,
+    #[server(default)] tags_filtered: Vec<TagId>,
+    #[server(default)] category: Option<CategoryId>,
+    #[server(default)] include_subcategories: bool,
+    #[server(default)] attribute_filters: Vec<AttributeFilter>,
+    #[server(default)] location: Option<LocationId>,
+    #[server(default)] tag: Option<TagId>,
+) -> Result<SearchResults, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::{Repository, item::ItemsDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
 
-    Ok(extract(move |db: Repository| async move {
-        db.search_items(query.as_deref(), &tags_filtered, &category).await
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.search_items(query.as_deref(), &tags_filtered, category, include_subcategories, &attribute_filters, location, tag).await
+    }).await??)
+}
+
+#[server(Suggest, "/api", "GetJson")]
+pub async fn suggest(prefix: String, limit: i64) -> Result<Vec<Suggestion>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, item::ItemsDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.suggest(&prefix, limit.clamp(1, MAX_SUGGESTIONS)).await
     }).await??)
 }
 
 #[server(AddTag, "/api")]
-pub async fn add_tag(tag_name: String) -> Result<Tag, ServerFnError> {
+pub async fn add_tag(tag_name: String, group_id: Option<TagGroupId>, color: String) -> Result<Tag, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let tag = db.add_tag(&tag_name, group_id, &color).await?;
+        live.publish(LiveEvent::TagAdded(tag.clone()));
+        Ok::<_, DbError>(tag)
+    }).await??)
+}
+
+#[server(UpdateTag, "/api")]
+pub async fn update_tag(tag_id: TagId, tag_name: String, group_id: Option<TagGroupId>, color: String) -> Result<Tag, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let tag = db.update_tag(tag_id, &tag_name, group_id, &color, None).await?;
+        live.publish(LiveEvent::TagUpdated(tag.clone()));
+        Ok::<_, DbError>(tag)
+    }).await??)
+}
+
+#[server(AddTagGroup, "/api")]
+pub async fn add_tag_group(group_name: String) -> Result<TagGroup, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
 
-    Ok(extract(move |db: Repository| async move {
-        db.add_tag(&tag_name).await
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let group = db.add_tag_group(&group_name).await?;
+        live.publish(LiveEvent::TagGroupAdded(group.clone()));
+        Ok::<_, DbError>(group)
+    }).await??)
+}
+
+#[server(RemoveTagGroup, "/api")]
+pub async fn remove_tag_group(group_id: TagGroupId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::TagGroup { group_id }, None).await
     }).await??)
 }
 
 #[server(AddItem, "/api")]
-pub async fn add_item(item_name: String, item_category: String) -> Result<Item, ServerFnError> {
+pub async fn add_item(item_name: String, category_id: CategoryId) -> Result<Item, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
 
-    Ok(extract(move |db: Repository| async move {
-        db.add_item(&item_name, &item_category).await
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let item = db.add_item(&item_name, category_id).await?;
+        live.publish(LiveEvent::ItemAdded(item.clone()));
+        Ok::<_, DbError>(item)
     }).await??)
 }
 
 #[server(AddItemObject, "/api")]
-pub async fn add_item_object(item_id: ItemId, item_code: String) -> Result<ItemObject, ServerFnError> {
+pub async fn add_item_object(
+    item_id: ItemId,
+    item_code: String,
+    location_id: Option<LocationId>,
+    #[server(default)] variant_id: Option<ItemVariantId>,
+) -> Result<ItemObject, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::ObjectsWrite)?;
+        let object = db.add_item_object(item_id, &item_code, location_id, variant_id).await?;
+        live.publish(LiveEvent::ItemUpdated(db.get_item(item_id).await?));
+        Ok::<_, DbError>(object)
+    }).await??)
+}
+
+#[server(FindByItemCode, "/api", "GetJson")]
+pub async fn find_by_item_code(item_code: String) -> Result<ItemObjectLookup, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::{Repository, item::ItemsDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
 
-    Ok(extract(move |db: Repository| async move {
-        db.add_item_object(item_id, &item_code).await
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.find_by_item_code(&item_code).await
     }).await??)
 }
 
 #[server(GetTags, "/api", "GetJson")]
-pub async fn get_tags() -> Result<Vec<Tag>, ServerFnError> {
+pub async fn get_tags() -> Result<Vec<GroupedTags>, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::{Repository, item::ItemsDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
 
-    Ok(extract(move |db: Repository| async move {
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
         db.get_tags().await
     }).await??)
 }
 
 #[server(RemoveTag, "/api")]
-pub async fn remove_tag(tag_id: TagId) -> Result<(), ServerFnError> {
+pub async fn remove_tag(tag_id: TagId) -> Result<PendingDeletion, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
 
-    Ok(extract(move |db: Repository| async move {
-        db.remove_tag(tag_id).await
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::Tag { tag_id }, None).await
     }).await??)
 }
 
 #[server(RemoveItem, "/api")]
-pub async fn remove_item(item_id: ItemId) -> Result<(), ServerFnError> {
+pub async fn remove_item(item_id: ItemId) -> Result<PendingDeletion, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
 
-    Ok(extract(move |db: Repository| async move {
-        db.remove_item(item_id).await
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::Item { item_id }, None).await
     }).await??)
 }
 
 #[server(RemoveItemObject, "/api")]
-pub async fn remove_item_object(item_object_id: ItemObjectId) -> Result<(), ServerFnError> {
+pub async fn remove_item_object(item_object_id: ItemObjectId) -> Result<PendingDeletion, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
 
-    Ok(extract(move |db: Repository| async move {
-        db.remove_item_object(item_object_id).await
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::ObjectsWrite)?;
+        deletions::schedule(&db, Deletion::ItemObject { item_object_id }, None).await
     }).await??)
 }
 
 #[server(AddItemTag, "/api")]
 pub async fn add_item_tag(item_id: ItemId, tag_id: TagId) -> Result<(), ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
 
-    Ok(extract(move |db: Repository| async move {
-        db.add_item_tag(item_id, tag_id).await
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_item_tag(item_id, tag_id).await?;
+        live.publish(LiveEvent::ItemUpdated(db.get_item(item_id).await?));
+        Ok::<_, DbError>(())
     }).await??)
 }
 
 #[server(RemoveItemTag, "/api")]
-pub async fn remove_item_tag(item_id: ItemId, tag_id: TagId) -> Result<(), ServerFnError> {
+pub async fn remove_item_tag(item_id: ItemId, tag_id: TagId) -> Result<PendingDeletion, ServerFnError> {
     use leptos_actix::extract;
-    use crate::db::{Repository, item::ItemsDB};
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
 
-    Ok(extract(move |db: Repository| async move {
-        db.remove_item_tag(item_id, tag_id).await
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::ItemTag { item_id, tag_id }, None).await
     }).await??)
-}
\ No newline at end of file
+}
+
+#[server(TagItems, "/api")]
+pub async fn tag_items(item_ids: Vec<ItemId>, tag_id: TagId) -> Result<BulkOutcome<Item>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let outcome = db.tag_items(&item_ids, tag_id).await?;
+        for item in &outcome.succeeded {
+            live.publish(LiveEvent::ItemUpdated(item.clone()));
+        }
+        Ok::<_, DbError>(outcome)
+    }).await??)
+}
+
+#[server(UntagItems, "/api")]
+pub async fn untag_items(item_ids: Vec<ItemId>, tag_id: TagId) -> Result<BulkOutcome<Item>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let outcome = db.untag_items(&item_ids, tag_id).await?;
+        for item in &outcome.succeeded {
+            live.publish(LiveEvent::ItemUpdated(item.clone()));
+        }
+        Ok::<_, DbError>(outcome)
+    }).await??)
+}
+
+#[server(MoveItems, "/api")]
+pub async fn move_items(item_ids: Vec<ItemId>, category_id: CategoryId) -> Result<BulkOutcome<Item>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let outcome = db.move_items(&item_ids, category_id).await?;
+        for item in &outcome.succeeded {
+            live.publish(LiveEvent::ItemUpdated(item.clone()));
+        }
+        Ok::<_, DbError>(outcome)
+    }).await??)
+}
+
+/// Queues the removal of the items, the ones, which fail to be removed, are reported when it's carried out.
+#[server(RemoveItems, "/api")]
+pub async fn remove_items(item_ids: Vec<ItemId>) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::Items { item_ids }, None).await
+    }).await??)
+}
+
+/// The items as CSV, the ones, which are gone, are left out.
+#[server(ExportItems, "/api")]
+pub async fn export_items(item_ids: Vec<ItemId>) -> Result<String, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, bulk::items_csv}};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        let outcome = db.get_items(&item_ids).await?;
+        Ok::<_, DbError>(items_csv(&outcome.succeeded))
+    }).await??)
+}
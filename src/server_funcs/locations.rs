@@ -0,0 +1,60 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{locations::{Location, LocationId, ObjectTransfer, TransferOutcome}, item::ItemObjectId, deletions::PendingDeletion};
+
+#[server(GetLocations, "/api", "GetJson")]
+pub async fn get_locations() -> Result<Vec<Location>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, locations::LocationDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_locations().await
+    }).await??)
+}
+
+#[server(AddLocation, "/api")]
+pub async fn add_location(location_name: String) -> Result<Location, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, locations::LocationDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_location(&location_name).await
+    }).await??)
+}
+
+#[server(RemoveLocation, "/api")]
+pub async fn remove_location(location_id: LocationId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::Location { location_id }, None).await
+    }).await??)
+}
+
+#[server(TransferObjects, "/api")]
+pub async fn transfer_objects(object_ids: Vec<ItemObjectId>, to_location_id: LocationId) -> Result<TransferOutcome, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, locations::LocationDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::ObjectsWrite)?;
+        let outcome = db.transfer_objects(&object_ids, to_location_id).await?;
+        live.publish(LiveEvent::ObjectsMoved(outcome.moved.clone()));
+        Ok::<_, DbError>(outcome)
+    }).await??)
+}
+
+#[server(GetObjectTransfers, "/api", "GetJson")]
+pub async fn get_object_transfers(object_id: ItemObjectId) -> Result<Vec<ObjectTransfer>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, locations::LocationDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_object_transfers(object_id).await
+    }).await??)
+}
@@ -0,0 +1,61 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::item::{Item, ItemId, TagId};
+use crate::data::merges::{DuplicateTags, DuplicateItems};
+
+/// How many likely duplicates are suggested at once.
+pub const DUPLICATES_SHOWN: i64 = 50;
+
+#[server(GetDuplicateTags, "/api", "GetJson")]
+pub async fn get_duplicate_tags() -> Result<Vec<DuplicateTags>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, merges::MergeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.get_duplicate_tags(DUPLICATES_SHOWN).await
+    }).await??)
+}
+
+#[server(GetDuplicateItems, "/api", "GetJson")]
+pub async fn get_duplicate_items() -> Result<Vec<DuplicateItems>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, merges::MergeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.get_duplicate_items(DUPLICATES_SHOWN).await
+    }).await??)
+}
+
+/// Merges the `source` tag into the `target` one, which is kept.
+#[server(MergeTags, "/api")]
+pub async fn merge_tags(source: TagId, target: TagId) -> Result<(), ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, merges::MergeDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let items = db.merge_tags(source, target).await?;
+        live.publish(LiveEvent::TagRemoved(source));
+        for item in items {
+            live.publish(LiveEvent::ItemUpdated(item));
+        }
+        Ok::<_, DbError>(())
+    }).await??)
+}
+
+/// Merges the `source` item into the `target` one, which is kept.
+#[server(MergeItems, "/api")]
+pub async fn merge_items(source: ItemId, target: ItemId) -> Result<Item, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, merges::MergeDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let item = db.merge_items(source, target).await?;
+        live.publish(LiveEvent::ItemRemoved(source));
+        live.publish(LiveEvent::ItemUpdated(item.clone()));
+        Ok::<_, DbError>(item)
+    }).await??)
+}
@@ -1,2 +1,15 @@
 pub mod categories;
-pub mod items;
\ No newline at end of file
+pub mod items;
+pub mod attributes;
+pub mod attachments;
+pub mod suppliers;
+pub mod locations;
+pub mod sales;
+pub mod stock_takes;
+pub mod stats;
+pub mod saved_searches;
+pub mod api_tokens;
+pub mod webhooks;
+pub mod deletions;
+pub mod merges;
+pub mod variants;
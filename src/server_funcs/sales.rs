@@ -0,0 +1,62 @@
+use chrono::NaiveDate;
+use leptos::{server, ServerFnError};
+
+use crate::data::{sales::{Sale, SaleId, SaleObject, CartLine, SalesSummary}, item::ItemId};
+
+#[server(FindSaleObject, "/api", "GetJson")]
+pub async fn find_sale_object(item_code: String) -> Result<SaleObject, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, sales::SaleDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.find_sale_object(&item_code).await
+    }).await??)
+}
+
+#[server(GetSaleObjects, "/api", "GetJson")]
+pub async fn get_sale_objects(item_id: ItemId) -> Result<Vec<SaleObject>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, sales::SaleDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_sale_objects(item_id).await
+    }).await??)
+}
+
+#[server(AddSale, "/api")]
+pub async fn add_sale(cart: Vec<CartLine>) -> Result<Sale, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, sales::SaleDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::ObjectsWrite)?;
+        let sale = db.add_sale(&cart).await?;
+        live.publish(LiveEvent::ObjectsRemoved(cart.iter().map(|line| line.object_id).collect()));
+        Ok::<_, DbError>(sale)
+    }).await??)
+}
+
+#[server(GetSale, "/api", "GetJson")]
+pub async fn get_sale(sale_id: SaleId) -> Result<Sale, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, sales::SaleDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_sale(sale_id).await
+    }).await??)
+}
+
+/// Totals of the sales made on a day of the shop, today if it's not set.
+#[server(GetDailySalesSummary, "/api", "GetJson")]
+pub async fn get_daily_sales_summary(#[server(default)] day: Option<NaiveDate>) -> Result<SalesSummary, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, sales::SaleDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, sales::SHOP_TIME_ZONE}};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_daily_sales_summary(day, SHOP_TIME_ZONE).await
+    }).await??)
+}
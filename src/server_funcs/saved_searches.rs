@@ -0,0 +1,75 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{saved_searches::{SavedSearch, SavedSearchId}, deletions::PendingDeletion};
+
+#[server(GetSavedSearches, "/api", "GetJson")]
+pub async fn get_saved_searches() -> Result<Vec<SavedSearch>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, saved_searches::SavedSearchDB}, users::CurrentUser, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, user: CurrentUser, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        match user.0 {
+            Some(user_id) => db.get_saved_searches(user_id).await,
+            None => Ok(vec![]),
+        }
+    }).await??)
+}
+
+#[server(GetDefaultSavedSearch, "/api", "GetJson")]
+pub async fn get_default_saved_search() -> Result<Option<SavedSearch>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, saved_searches::SavedSearchDB}, users::CurrentUser, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, user: CurrentUser, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        match user.0 {
+            Some(user_id) => db.get_default_saved_search(user_id).await,
+            None => Ok(None),
+        }
+    }).await??)
+}
+
+#[server(AddSavedSearch, "/api")]
+pub async fn add_saved_search(name: String, href: String) -> Result<SavedSearch, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, saved_searches::SavedSearchDB}, users::CurrentUser, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    let user_id = extract(|user: CurrentUser| async move { user }).await?.get_or_issue();
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.add_saved_search(user_id, &name, &href).await
+    }).await??)
+}
+
+#[server(RemoveSavedSearch, "/api")]
+pub async fn remove_saved_search(saved_search_id: SavedSearchId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError}, users::CurrentUser, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, user: CurrentUser, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        // A user without the cookie has no saved searches
+        let user_id = user.0.ok_or(DbError::ItemNotFound)?;
+        deletions::schedule(&db, Deletion::SavedSearch { user_id, saved_search_id }, None).await
+    }).await??)
+}
+
+#[server(SetDefaultSavedSearch, "/api")]
+pub async fn set_default_saved_search(saved_search_id: Option<SavedSearchId>) -> Result<(), ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, saved_searches::SavedSearchDB}, users::CurrentUser, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    use crate::db::DbError;
+
+    Ok(extract(move |db: Repository, user: CurrentUser, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        match user.0 {
+            Some(user_id) => db.set_default_saved_search(user_id, saved_search_id).await,
+            // A user without a cookie has no searches to choose from
+            None if saved_search_id.is_none() => Ok(()),
+            None => Err(DbError::ItemNotFound),
+        }
+    }).await??)
+}
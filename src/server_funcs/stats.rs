@@ -0,0 +1,14 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::stats::Stats;
+
+#[server(GetStats, "/api", "GetJson")]
+pub async fn get_stats() -> Result<Stats, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, stats::StatsDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_stats().await
+    }).await??)
+}
@@ -0,0 +1,96 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{stock_takes::{StockTake, StockTakeId, StockTakeReport, StockTakeCorrection}, categories::CategoryId, locations::LocationId, deletions::PendingDeletion};
+
+#[server(GetStockTakes, "/api", "GetJson")]
+pub async fn get_stock_takes() -> Result<Vec<StockTake>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, stock_takes::StockTakeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_stock_takes().await
+    }).await??)
+}
+
+#[server(GetStockTake, "/api", "GetJson")]
+pub async fn get_stock_take(stock_take_id: StockTakeId) -> Result<StockTake, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, stock_takes::StockTakeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_stock_take(stock_take_id).await
+    }).await??)
+}
+
+#[server(AddStockTake, "/api")]
+pub async fn add_stock_take(category_id: Option<CategoryId>, location_id: Option<LocationId>) -> Result<StockTake, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, stock_takes::StockTakeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_stock_take(category_id, location_id).await
+    }).await??)
+}
+
+#[server(RemoveStockTake, "/api")]
+pub async fn remove_stock_take(stock_take_id: StockTakeId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::StockTake { stock_take_id }, None).await
+    }).await??)
+}
+
+#[server(AddStockTakeScan, "/api")]
+pub async fn add_stock_take_scan(stock_take_id: StockTakeId, item_code: String) -> Result<StockTake, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, stock_takes::StockTakeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::ObjectsWrite)?;
+        db.add_stock_take_scan(stock_take_id, &item_code).await
+    }).await??)
+}
+
+#[server(GetStockTakeReport, "/api", "GetJson")]
+pub async fn get_stock_take_report(stock_take_id: StockTakeId) -> Result<StockTakeReport, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, stock_takes::StockTakeDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_stock_take_report(stock_take_id).await
+    }).await??)
+}
+
+#[server(ApproveStockTake, "/api")]
+pub async fn approve_stock_take(stock_take_id: StockTakeId, #[server(default)] corrections: Vec<StockTakeCorrection>) -> Result<StockTake, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, stock_takes::StockTakeDB, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let stock_take = db.approve_stock_take(stock_take_id, &corrections).await?;
+
+        let mut removed_ids = vec![];
+        let mut added_to = vec![];
+        for correction in &corrections {
+            match correction {
+                StockTakeCorrection::RemoveObject(object_id) => removed_ids.push(*object_id),
+                StockTakeCorrection::AddObject { item_id, .. } if !added_to.contains(item_id) => added_to.push(*item_id),
+                StockTakeCorrection::AddObject { .. } => {},
+            }
+        }
+        live.publish(LiveEvent::ObjectsRemoved(removed_ids));
+        for item_id in added_to {
+            live.publish(LiveEvent::ItemUpdated(db.get_item(item_id).await?));
+        }
+
+        Ok::<_, DbError>(stock_take)
+    }).await??)
+}
@@ -0,0 +1,109 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{suppliers::{Supplier, SupplierId, PurchaseOrder, PurchaseOrderId, PurchaseOrderLine, PurchaseOrderLineId}, item::{ItemId, ItemObject}, locations::LocationId, deletions::PendingDeletion};
+
+#[server(GetSuppliers, "/api", "GetJson")]
+pub async fn get_suppliers() -> Result<Vec<Supplier>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, suppliers::SupplierDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_suppliers().await
+    }).await??)
+}
+
+#[server(AddSupplier, "/api")]
+pub async fn add_supplier(supplier_name: String, contact: Option<String>) -> Result<Supplier, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, suppliers::SupplierDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_supplier(&supplier_name, contact.as_deref()).await
+    }).await??)
+}
+
+#[server(RemoveSupplier, "/api")]
+pub async fn remove_supplier(supplier_id: SupplierId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::Supplier { supplier_id }, None).await
+    }).await??)
+}
+
+#[server(GetPurchaseOrders, "/api", "GetJson")]
+pub async fn get_purchase_orders() -> Result<Vec<PurchaseOrder>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, suppliers::SupplierDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Read)?;
+        db.get_purchase_orders().await
+    }).await??)
+}
+
+#[server(AddPurchaseOrder, "/api")]
+pub async fn add_purchase_order(supplier_id: SupplierId) -> Result<PurchaseOrder, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, suppliers::SupplierDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_purchase_order(supplier_id).await
+    }).await??)
+}
+
+#[server(RemovePurchaseOrder, "/api")]
+pub async fn remove_purchase_order(order_id: PurchaseOrderId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::PurchaseOrder { order_id }, None).await
+    }).await??)
+}
+
+#[server(AddPurchaseOrderLine, "/api")]
+pub async fn add_purchase_order_line(order_id: PurchaseOrderId, item_id: ItemId, quantity: i32) -> Result<PurchaseOrderLine, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, suppliers::SupplierDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_purchase_order_line(order_id, item_id, quantity).await
+    }).await??)
+}
+
+#[server(RemovePurchaseOrderLine, "/api")]
+pub async fn remove_purchase_order_line(line_id: PurchaseOrderLineId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::PurchaseOrderLine { line_id }, None).await
+    }).await??)
+}
+
+/// Receives the line, creating an object at the location for every code.
+#[server(ReceivePurchaseOrderLine, "/api")]
+pub async fn receive_purchase_order_line(
+    line_id: PurchaseOrderLineId,
+    item_codes: Vec<String>,
+    location_id: Option<LocationId>,
+) -> Result<(PurchaseOrderLine, Vec<ItemObject>), ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, suppliers::SupplierDB, item::ItemsDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::ObjectsWrite)?;
+        let (line, objects) = db.receive_purchase_order_line(line_id, &item_codes, location_id).await?;
+        live.publish(LiveEvent::ItemUpdated(db.get_item(line.item_id).await?));
+        Ok::<_, DbError>((line, objects))
+    }).await??)
+}
@@ -0,0 +1,28 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{item::{Item, ItemId, ItemVariantId}, variants::VariantAxis, deletions::PendingDeletion};
+
+/// Makes the variants of the item from every combination of the chosen options.
+#[server(AddItemVariants, "/api")]
+pub async fn add_item_variants(item_id: ItemId, #[server(default)] axes: Vec<VariantAxis>) -> Result<Item, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, DbError, variants::VariantDB}, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, live::LiveEvent}, live::LiveUpdates};
+
+    Ok(extract(move |db: Repository, live: LiveUpdates, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        let item = db.add_item_variants(item_id, &axes).await?;
+        live.publish(LiveEvent::ItemUpdated(item.clone()));
+        Ok::<_, DbError>(item)
+    }).await??)
+}
+
+#[server(RemoveItemVariant, "/api")]
+pub async fn remove_item_variant(variant_id: ItemVariantId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::ItemVariant { variant_id }, None).await
+    }).await??)
+}
@@ -0,0 +1,62 @@
+use leptos::{server, ServerFnError};
+
+use crate::data::{webhooks::{Webhook, WebhookId, WebhookEventType, WebhookDelivery}, deletions::PendingDeletion};
+
+/// Number of the latest deliveries shown in the log of a webhook.
+pub const DELIVERY_LOG_LIMIT: i64 = 20;
+
+#[server(GetWebhooks, "/api", "GetJson")]
+pub async fn get_webhooks() -> Result<Vec<Webhook>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, webhooks::WebhookDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.get_webhooks().await
+    }).await??)
+}
+
+#[server(AddWebhook, "/api")]
+pub async fn add_webhook(url: String, event_types: Vec<WebhookEventType>, secret: String) -> Result<Webhook, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, webhooks::WebhookDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.add_webhook(url.trim(), &event_types, &secret).await
+    }).await??)
+}
+
+#[server(RemoveWebhook, "/api")]
+pub async fn remove_webhook(webhook_id: WebhookId) -> Result<PendingDeletion, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::{api_tokens::ApiTokenScope, deletions::Deletion}, deletions};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        deletions::schedule(&db, Deletion::Webhook { webhook_id }, None).await
+    }).await??)
+}
+
+#[server(GetWebhookDeliveries, "/api", "GetJson")]
+pub async fn get_webhook_deliveries(webhook_id: WebhookId) -> Result<Vec<WebhookDelivery>, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::{Repository, webhooks::WebhookDB}, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        db.get_webhook_deliveries(webhook_id, DELIVERY_LOG_LIMIT).await
+    }).await??)
+}
+
+/// Sends a ping to the webhook right away, the failed ping is retried like any other delivery.
+#[server(TestWebhook, "/api")]
+pub async fn test_webhook(webhook_id: WebhookId) -> Result<WebhookDelivery, ServerFnError> {
+    use leptos_actix::extract;
+    use crate::{db::Repository, api_tokens::ApiAccess, data::api_tokens::ApiTokenScope, webhooks::send_test};
+
+    Ok(extract(move |db: Repository, access: ApiAccess| async move {
+        access.require(ApiTokenScope::Admin)?;
+        send_test(&db, webhook_id).await
+    }).await??)
+}
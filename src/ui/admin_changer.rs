@@ -1,12 +1,16 @@
 use leptos::*;
 
-use super::state::AdminState;
+use super::{state::AdminState, keybindings::Keybindings};
 
 #[component]
 pub fn AdminChanger(admin_state_setter: WriteSignal<AdminState>) -> impl IntoView {
     let admin_state = use_context::<ReadSignal<AdminState>>()
         .expect("`AdminState` to be added to the context");
 
+    Keybindings::use_keybindings().register("a", "Перемкнути режим адміністратора", move || {
+        admin_state_setter(AdminState { set: !admin_state.get_untracked().set })
+    });
+
     let admin_view = move || {
         if admin_state().set {
             view! {
@@ -0,0 +1,249 @@
+use leptos::*;
+
+use crate::{server_funcs::api_tokens::{get_api_tokens, add_api_token, revoke_api_token, get_session, sign_in, sign_out}, data::api_tokens::{ApiToken, ApiTokenId, ApiTokenScope, CreatedApiToken}, ui::{state::AdminState, keybindings::on_enter}};
+
+#[component]
+fn ApiTokenRow<F>(token: ApiToken, revoke_api_token_cb: F) -> impl IntoView
+where
+    F: Fn(&ApiTokenId) + Copy + 'static
+{
+    let revoke_api_token_action = create_action(move |_| {
+        async move {
+            revoke_api_token(token.id).await?;
+            revoke_api_token_cb(&token.id);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let last_used = token.last_used_at
+        .map(|last_used_at| format!("Використано {}", last_used_at.format("%d.%m.%Y %H:%M")))
+        .unwrap_or("Не використовувався".to_string());
+
+    view! {
+        <div class="flex flex-row items-center gap-2 p-2 bg-slate-200 rounded-xl">
+            <div class="font-bold">{token.name}</div>
+            <div>{token.scope.label()}</div>
+            <div class="text-sm">{format!("Створено {}", token.created_at.format("%d.%m.%Y %H:%M"))}</div>
+            <div class="text-sm">{last_used}</div>
+            <button
+                on:click=move |_| {
+                    revoke_api_token_action.dispatch(())
+                }
+                class="ml-auto bg-red-700 disabled:text-slate-400 rounded-xl px-2"
+                disabled=revoke_api_token_action.pending()
+            >
+                "Відкликати"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn AddApiToken(add_api_token_action: Action<(String, ApiTokenScope), Option<CreatedApiToken>>) -> impl IntoView {
+    let (name, name_set) = create_signal(String::new());
+    let (scope, scope_set) = create_signal(ApiTokenScope::Read);
+
+    view! {
+        <div class="flex flex-row items-center gap-1">
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                type="text"
+                placeholder="Назва, напр. сканер на складі"
+                required
+                on:input=move |ev| {
+                    name_set(event_target_value(&ev))
+                }
+                on:keydown=on_enter(move || {
+                    if !name().trim().is_empty() {
+                        add_api_token_action.dispatch((name(), scope()))
+                    }
+                })
+
+                prop:value=name
+            />
+            <select
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                on:change=move |ev| {
+                    if let Some(scope) = event_target_value(&ev).parse().ok().and_then(|idx: usize| ApiTokenScope::ALL.get(idx)) {
+                        scope_set(*scope)
+                    }
+                }
+            >
+                {
+                    ApiTokenScope::ALL.into_iter().enumerate().map(|(idx, option)| view! {
+                        <option value=idx.to_string() selected=move || scope() == option>{option.label()}</option>
+                    }).collect_view()
+                }
+            </select>
+            <button
+                class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                on:click=move |_| {
+                    add_api_token_action.dispatch((name(), scope()))
+                }
+                disabled=move || name().trim().is_empty()
+            >
+                "Створити"
+            </button>
+        </div>
+    }
+}
+
+/// Signing the browser in with a token, without one it can only read.
+#[component]
+fn SessionBlock(api_tokens: Resource<(), Result<Vec<ApiToken>, ServerFnError>>) -> impl IntoView {
+    let session = create_resource(|| (), |_| get_session());
+    let (secret, secret_set) = create_signal(String::new());
+
+    let sign_in_action = create_action(move |secret: &String| {
+        let secret = secret.clone();
+        async move {
+            let signed_in = sign_in(secret).await;
+            if signed_in.is_ok() {
+                secret_set(String::new());
+                session.refetch();
+                api_tokens.refetch();
+            }
+            signed_in
+        }
+    });
+
+    let sign_out_action = create_action(move |_| {
+        async move {
+            sign_out().await?;
+            session.refetch();
+            api_tokens.refetch();
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let sign_in_failed = move || {
+        matches!(sign_in_action.value()(), Some(Err(_))).then(|| view! {
+            <div class="text-red-700">"Невідомий токен"</div>
+        })
+    };
+
+    let signed_in = move || {
+        session().map(|session| match session {
+            Ok(Some(token)) => view! {
+                <div class="flex flex-row items-center gap-2">
+                    <div>{format!("Ви увійшли з токеном «{}» ({})", token.name, token.scope.label())}</div>
+                    <button
+                        class="bg-slate-300 rounded-xl px-2 disabled:text-slate-400"
+                        on:click=move |_| {
+                            sign_out_action.dispatch(())
+                        }
+                        disabled=sign_out_action.pending()
+                    >
+                        "Вийти"
+                    </button>
+                </div>
+            }.into_view(),
+            Ok(None) => view! {
+                <div class="flex flex-row items-center gap-1">
+                    <input
+                        class="rounded-lg p-1 border-solid border-slate-400 border"
+                        type="password"
+                        placeholder="Токен, щоб змінювати дані"
+                        on:input=move |ev| {
+                            secret_set(event_target_value(&ev))
+                        }
+                        on:keydown=move |ev| {
+                            if ev.key() == "Enter" && !secret().trim().is_empty() {
+                                sign_in_action.dispatch(secret())
+                            }
+                        }
+                        prop:value=secret
+                    />
+                    <button
+                        class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                        on:click=move |_| {
+                            sign_in_action.dispatch(secret())
+                        }
+                        disabled=move || secret().trim().is_empty() || sign_in_action.pending()()
+                    >
+                        "Увійти"
+                    </button>
+                    {sign_in_failed}
+                </div>
+            }.into_view(),
+            Err(_) => view! { "Помилка завантаження сесії" }.into_view(),
+        })
+    };
+
+    view! {
+        <Suspense fallback=move || view! { Завантаження сесії... }>
+            {signed_in}
+        </Suspense>
+    }
+}
+
+/// Tokens of the machine clients of the API, the secret of a created token is shown only once.
+#[component]
+pub fn ApiTokensBlock() -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let api_tokens = create_resource(|| (), |_| get_api_tokens());
+
+    let revoke_api_token_cb = move |api_token_id: &ApiTokenId| {
+        api_tokens.update(|api_tokens| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after tokens have loaded.
+            api_tokens.as_mut().unwrap().as_mut().unwrap().retain(|token| &token.id != api_token_id);
+        })
+    };
+
+    let add_api_token_action = create_action(move |input: &(String, ApiTokenScope)| {
+        let (name, scope) = input.clone();
+        async move {
+            let created = add_api_token(name.trim().to_string(), scope).await.ok()?;
+            api_tokens.update(|api_tokens| {
+                // PANIC: unwraps are fine, because this action is passed to a component, that is
+                //        rendered only after tokens have loaded.
+                api_tokens.as_mut().unwrap().as_mut().unwrap().push(created.token.clone())
+            });
+            Some(created)
+        }
+    });
+
+    let created_secret = move || {
+        add_api_token_action.value()().flatten().map(|created| view! {
+            <div class="flex flex-col gap-1 p-2 bg-green-200 rounded-xl">
+                <div>{format!("Токен «{}» створено. Скопіюйте його зараз, більше він показаний не буде:", created.token.name)}</div>
+                <code class="select-all">{created.secret}</code>
+            </div>
+        })
+    };
+
+    let loaded_api_tokens = move || {
+        api_tokens().map(|api_tokens| {
+            match api_tokens {
+                Ok(api_tokens) if api_tokens.is_empty() => view! { "Токенів немає" }.into_view(),
+                Ok(api_tokens) => api_tokens.into_iter().map(|token| view! {
+                    <ApiTokenRow token revoke_api_token_cb />
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження токенів }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2">
+            <h2 class="text-2xl">"Токени API"</h2>
+            <SessionBlock api_tokens />
+            {
+                move || if admin_state().set {
+                    view! {
+                        <AddApiToken add_api_token_action />
+                        {created_secret}
+                        <Suspense fallback=move || view! { Завантаження токенів... }>
+                            {loaded_api_tokens}
+                        </Suspense>
+                    }.into_view()
+                } else {
+                    view! { "Токенами керує лише адміністратор" }.into_view()
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,170 @@
+use leptos::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    data::{attachments::{ItemAttachment, ItemAttachmentId, UploadQuery, UPLOAD_PATH}, item::ItemId, deletions::PendingDeletion},
+    server_funcs::attachments::{get_item_attachments, remove_item_attachment},
+    ui::{state::AdminState, toasts::Toasts},
+};
+
+/// Sends the file to the upload endpoint, returning the error message of the server on failure.
+async fn upload_file(item_id: ItemId, file: web_sys::File) -> Result<ItemAttachment, String> {
+    let query = serde_qs::to_string(&UploadQuery { name: file.name() }).map_err(|err| err.to_string())?;
+    let url = format!("{UPLOAD_PATH}/{}?{query}", item_id.0);
+
+    let mut init = web_sys::RequestInit::new();
+    init.method("POST").body(Some(&file));
+    let request = web_sys::Request::new_with_str_and_init(&url, &init)
+        .map_err(|_| "Не вдалося створити запит".to_string())?;
+
+    let response: web_sys::Response = JsFuture::from(window().fetch_with_request(&request))
+        .await
+        .and_then(|response| response.dyn_into())
+        .map_err(|_| "Не вдалося надіслати файл".to_string())?;
+    let text = match response.text() {
+        Ok(text) => JsFuture::from(text).await.ok().and_then(|text| text.as_string()).unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+
+    if response.ok() {
+        serde_json::from_str(&text).map_err(|err| err.to_string())
+    } else {
+        Err(text)
+    }
+}
+
+#[component]
+pub fn AttachmentView<F>(attachment: ItemAttachment, remove_attachment_cb: F) -> impl IntoView
+where
+    F: Fn(&ItemAttachmentId, PendingDeletion) + Copy + 'static
+{
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let attachment_id = attachment.id;
+    let remove_attachment_action = create_action(move |_| {
+        async move {
+            let pending = remove_item_attachment(attachment_id).await?;
+            remove_attachment_cb(&attachment_id, pending);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let preview = match attachment.thumbnail_url() {
+        Some(thumbnail_url) => view! {
+            <img class="h-24 w-24 object-contain" src=thumbnail_url alt=attachment.file_name.clone() />
+        }.into_view(),
+        None => view! {
+            <div class="h-24 w-24 flex items-center justify-center text-center break-all text-sm">
+                {attachment.file_name.clone()}
+            </div>
+        }.into_view(),
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <a
+                class="border-solid border-slate-200 border-2 rounded-lg"
+                href=attachment.url()
+                target="_blank"
+                title=attachment.file_name
+            >
+                {preview}
+            </a>
+
+            {
+                move || admin_state().set.then(|| view! {
+                    <button
+                        on:click=move |_| {
+                            remove_attachment_action.dispatch(())
+                        }
+                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
+                        disabled=remove_attachment_action.pending()
+                    >
+                        Видалити
+                    </button>
+                })
+            }
+        </div>
+    }
+}
+
+/// Images and files attached to an item, with uploading in admin mode.
+#[component]
+pub fn ItemGallery(item_id: ItemId) -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let attachments = create_resource(|| (), move |_| get_item_attachments(item_id));
+
+    let toasts = Toasts::use_toasts();
+    let remove_attachment_cb = move |attachment_id: &ItemAttachmentId, pending: PendingDeletion| {
+        attachments.update(|attachments| {
+            // PANIC: unwraps are fine, because this callback is passed to a component, that is
+            //        rendered only after attachments have loaded.
+            attachments.as_mut().unwrap().as_mut().unwrap()
+                .retain(|attachment| &attachment.id != attachment_id);
+        });
+        toasts.show_undo("Вкладення видалено".to_string(), "Не вдалося видалити вкладення", pending.id, move || attachments.refetch());
+    };
+
+    let upload_action = create_action(move |file: &web_sys::File| {
+        let file = file.clone();
+        async move {
+            let new_attachment = upload_file(item_id, file).await?;
+            attachments.update(|attachments| {
+                if let Some(Ok(attachments)) = attachments {
+                    attachments.push(new_attachment)
+                }
+            });
+            Ok::<_, String>(())
+        }
+    });
+
+    let upload_error = move || {
+        upload_action.value()().and_then(|result| result.err()).map(|err| view! {
+            <div class="text-red-700">{err}</div>
+        })
+    };
+
+    let loaded_attachments = move || {
+        attachments().map(|attachments| {
+            match attachments {
+                Ok(attachments) => attachments.into_iter().map(|attachment| {
+                    view! {
+                        <AttachmentView attachment remove_attachment_cb />
+                    }
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження файлів }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <Transition fallback=|| ()>
+            <div class="flex flex-row flex-wrap gap-1">
+                {loaded_attachments}
+                {
+                    move || admin_state().set.then(|| view! {
+                        <div class="flex flex-col items-center border-solid border-black border">
+                            <input
+                                type="file"
+                                accept="image/*,application/pdf"
+                                disabled=upload_action.pending()
+                                on:change=move |ev| {
+                                    let input = event_target::<web_sys::HtmlInputElement>(&ev);
+                                    if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                                        upload_action.dispatch(file);
+                                    }
+                                    input.set_value("");
+                                }
+                            />
+                            {upload_error}
+                        </div>
+                    })
+                }
+            </div>
+        </Transition>
+    }
+}
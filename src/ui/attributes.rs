@@ -0,0 +1,343 @@
+use leptos::*;
+
+use crate::{
+    data::{attributes::{CategoryAttribute, CategoryAttributeId, AttributeKind, AttributeFilter, AttributeInput, ItemAttributes}, categories::CategoryId},
+    server_funcs::attributes::{add_category_attribute, remove_category_attribute},
+    ui::{state::AdminState, toasts::Toasts, keybindings::on_enter},
+};
+
+use super::state::SearchQuery;
+
+/// Changes the filter on an attribute, dropping it when it no longer restricts anything.
+fn update_attribute_filter(mut search_query: SearchQuery, attribute_id: CategoryAttributeId, update: impl FnOnce(&mut AttributeFilter)) {
+    let mut filter = search_query.attributes.iter()
+        .position(|filter| filter.id == attribute_id)
+        .map(|idx| search_query.attributes.remove(idx))
+        .unwrap_or(AttributeFilter { id: attribute_id, value: None, min: None, max: None });
+    update(&mut filter);
+
+    if filter.value.is_some() || filter.min.is_some() || filter.max.is_some() {
+        search_query.attributes.push(filter);
+    }
+    search_query.set();
+}
+
+/// Category, name, kind, unit and enum options of an attribute being added.
+type NewAttribute = (CategoryId, String, AttributeKind, Option<String>, Vec<String>);
+
+fn non_empty(value: String) -> Option<String> {
+    (!value.trim().is_empty()).then_some(value)
+}
+
+#[component]
+pub fn AttributeFilterInput(attribute: CategoryAttribute) -> impl IntoView {
+    let search_query = SearchQuery::use_query();
+    let search_query_untracked = SearchQuery::use_query_untracked();
+    let attribute_id = attribute.id;
+    let filter = move || search_query().attributes.into_iter().find(|filter| filter.id == attribute_id);
+
+    let input = match attribute.kind {
+        AttributeKind::Text => view! {
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border w-full"
+                type="text"
+                on:change=move |ev| update_attribute_filter(search_query_untracked(), attribute_id, |filter| filter.value = non_empty(event_target_value(&ev)))
+                prop:value=move || filter().and_then(|filter| filter.value).unwrap_or_default()
+            />
+        }.into_view(),
+        AttributeKind::Number => view! {
+            <div class="flex flex-row gap-1">
+                <input
+                    class="rounded-lg p-1 border-solid border-slate-400 border w-20"
+                    type="number"
+                    placeholder="від"
+                    on:change=move |ev| update_attribute_filter(search_query_untracked(), attribute_id, |filter| filter.min = event_target_value(&ev).parse().ok())
+                    prop:value=move || filter().and_then(|filter| filter.min).map(|min| min.to_string()).unwrap_or_default()
+                />
+                <input
+                    class="rounded-lg p-1 border-solid border-slate-400 border w-20"
+                    type="number"
+                    placeholder="до"
+                    on:change=move |ev| update_attribute_filter(search_query_untracked(), attribute_id, |filter| filter.max = event_target_value(&ev).parse().ok())
+                    prop:value=move || filter().and_then(|filter| filter.max).map(|max| max.to_string()).unwrap_or_default()
+                />
+                {attribute.unit.clone()}
+            </div>
+        }.into_view(),
+        AttributeKind::Enum | AttributeKind::Boolean => {
+            let options = match attribute.kind {
+                AttributeKind::Boolean => vec![("true".to_string(), "так".to_string()), ("false".to_string(), "ні".to_string())],
+                _ => attribute.options.iter().map(|option| (option.clone(), option.clone())).collect(),
+            };
+
+            view! {
+                <select
+                    class="rounded-lg p-1 border-solid border-slate-400 border w-full"
+                    on:change=move |ev| update_attribute_filter(search_query_untracked(), attribute_id, |filter| filter.value = non_empty(event_target_value(&ev)))
+                >
+                    <option value="" selected=move || filter().and_then(|filter| filter.value).is_none()>"Будь-яке"</option>
+                    {
+                        options.into_iter().map(|(value, label)| {
+                            let is_selected = {
+                                let value = value.clone();
+                                move || filter().and_then(|filter| filter.value) == Some(value.clone())
+                            };
+                            view! {
+                                <option value=value selected=is_selected>{label}</option>
+                            }
+                        }).collect_view()
+                    }
+                </select>
+            }.into_view()
+        }
+    };
+
+    view! {
+        <div class="flex flex-col">
+            <div>{attribute.name}</div>
+            {input}
+        </div>
+    }
+}
+
+#[component]
+pub fn AddAttribute(category_id: CategoryId, add_attribute_action: Action<NewAttribute, ()>) -> impl IntoView {
+    let (name, name_set) = create_signal(String::new());
+    let (kind, kind_set) = create_signal(AttributeKind::Text);
+    let (unit, unit_set) = create_signal(String::new());
+    let (options, options_set) = create_signal(String::new());
+
+    let submit = move || {
+        let kind = kind();
+        let unit = (kind == AttributeKind::Number).then(|| non_empty(unit())).flatten();
+        let options = if kind == AttributeKind::Enum {
+            options().split(',').map(|option| option.trim().to_string()).filter(|option| !option.is_empty()).collect()
+        } else {
+            vec![]
+        };
+        add_attribute_action.dispatch((category_id, name(), kind, unit, options))
+    };
+
+    view! {
+        <div class="flex flex-col items-center border-solid border-black border">
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                type="text"
+                placeholder="Назва"
+                on:input=move |ev| name_set(event_target_value(&ev))
+                on:keydown=on_enter(submit)
+                prop:value=name
+            />
+            <select
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                on:change=move |ev| kind_set(match event_target_value(&ev).as_str() {
+                    "number" => AttributeKind::Number,
+                    "enum" => AttributeKind::Enum,
+                    "boolean" => AttributeKind::Boolean,
+                    _ => AttributeKind::Text,
+                })
+            >
+                <option value="text">"Текст"</option>
+                <option value="number">"Число"</option>
+                <option value="enum">"Перелік"</option>
+                <option value="boolean">"Так/ні"</option>
+            </select>
+            {
+                move || match kind() {
+                    AttributeKind::Number => Some(view! {
+                        <input
+                            class="rounded-lg p-1 border-solid border-slate-400 border"
+                            type="text"
+                            placeholder="Одиниця виміру"
+                            on:input=move |ev| unit_set(event_target_value(&ev))
+                            on:keydown=on_enter(submit)
+                            prop:value=unit
+                        />
+                    }),
+                    AttributeKind::Enum => Some(view! {
+                        <input
+                            class="rounded-lg p-1 border-solid border-slate-400 border"
+                            type="text"
+                            placeholder="Варіанти через кому"
+                            on:input=move |ev| options_set(event_target_value(&ev))
+                            on:keydown=on_enter(submit)
+                            prop:value=options
+                        />
+                    }),
+                    _ => None,
+                }
+            }
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                on:click=move |_| submit()
+            >
+                "Додати атрибут"
+            </button>
+        </div>
+    }
+}
+
+/// Filters by the attributes of the chosen category, and in admin mode - editing of its attributes.
+#[component]
+pub fn AttributesBlock(attributes: Resource<(), Result<Vec<CategoryAttribute>, ServerFnError>>) -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let search_query = SearchQuery::use_query();
+    let category_id = move || search_query().category.map(|category| category.id);
+
+    let toasts = Toasts::use_toasts();
+    let remove_attribute_action = create_action(move |attribute_id: &CategoryAttributeId| {
+        let attribute_id = *attribute_id;
+        async move {
+            let pending = remove_category_attribute(attribute_id).await?;
+            attributes.update(|attributes| {
+                // PANIC: unwraps are fine, because this action is used only after attributes have loaded.
+                attributes.as_mut().unwrap().as_mut().unwrap().retain(|attribute| attribute.id != attribute_id)
+            });
+            toasts.show_undo("Атрибут видалено".to_string(), "Не вдалося видалити атрибут", pending.id, move || attributes.refetch());
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let add_attribute_action = create_action(move |input: &NewAttribute| {
+        let (category_id, name, kind, unit, options) = input.clone();
+        async move {
+            if let Ok(new_attribute) = add_category_attribute(category_id, name, kind, unit, options).await {
+                attributes.update(|attributes| {
+                    // PANIC: unwraps are fine, because this action is used only after attributes have loaded.
+                    attributes.as_mut().unwrap().as_mut().unwrap().push(new_attribute)
+                })
+            }
+        }
+    });
+
+    let category_attributes = move || {
+        let category_id = category_id()?;
+        let attributes = attributes().and_then(|attributes| attributes.ok())?;
+        Some(
+            attributes.into_iter()
+                .filter(|attribute| attribute.category_id == category_id)
+                .map(|attribute| {
+                    let attribute_id = attribute.id;
+                    view! {
+                        <AttributeFilterInput attribute />
+                        {
+                            move || admin_state().set.then(|| view! {
+                                <button
+                                    on:click=move |_| remove_attribute_action.dispatch(attribute_id)
+                                    class="bg-red-700 disabled:text-slate-400 rounded-xl"
+                                    disabled=remove_attribute_action.pending()
+                                >
+                                    Видалити
+                                </button>
+                            })
+                        }
+                    }
+                }).collect_view()
+        )
+    };
+
+    view! {
+        <Transition fallback=|| ()>
+            <div class="flex flex-col gap-1 p-2">
+                {category_attributes}
+                {
+                    move || admin_state().set.then(|| category_id().map(|category_id| view! {
+                        <AddAttribute category_id add_attribute_action />
+                    }))
+                }
+            </div>
+        </Transition>
+    }
+}
+
+/// Attributes of an item, editable in admin mode.
+#[component]
+pub fn ItemAttributesView(
+    attributes: Vec<CategoryAttribute>,
+    values: ItemAttributes,
+    set_attributes_action: Action<Vec<AttributeInput>, ()>,
+) -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let inputs = attributes.iter().map(|attribute| {
+        let value = values.0.get(&attribute.id).map(|value| value.to_input()).unwrap_or_default();
+        (attribute.id, create_rw_signal(value))
+    }).collect::<Vec<_>>();
+    let inputs = store_value(inputs);
+
+    let save = move || {
+        set_attributes_action.dispatch(inputs.with_value(|inputs| {
+            inputs.iter().map(|(id, value)| AttributeInput { id: *id, value: value.get_untracked() }).collect()
+        }))
+    };
+
+    let attributes_view = attributes.into_iter().map(|attribute| {
+        let value = values.0.get(&attribute.id).map(|value| attribute.display_value(value));
+        // PANIC: inputs are made for every attribute above.
+        let input = inputs.with_value(|inputs| inputs.iter().find(|(id, _)| id == &attribute.id).unwrap().1);
+        let edit_view = match attribute.kind {
+            AttributeKind::Boolean | AttributeKind::Enum => {
+                let options = match attribute.kind {
+                    AttributeKind::Boolean => vec![("true".to_string(), "так".to_string()), ("false".to_string(), "ні".to_string())],
+                    _ => attribute.options.iter().map(|option| (option.clone(), option.clone())).collect(),
+                };
+                view! {
+                    <select on:change=move |ev| input.set(event_target_value(&ev))>
+                        <option value="" selected=move || input().is_empty()>"—"</option>
+                        {
+                            options.into_iter().map(|(value, label)| {
+                                let is_selected = {
+                                    let value = value.clone();
+                                    move || input() == value
+                                };
+                                view! { <option value=value selected=is_selected>{label}</option> }
+                            }).collect_view()
+                        }
+                    </select>
+                }.into_view()
+            }
+            AttributeKind::Text | AttributeKind::Number => view! {
+                <input
+                    class="rounded-lg p-1 border-solid border-slate-400 border"
+                    type=if attribute.kind == AttributeKind::Number { "number" } else { "text" }
+                    on:input=move |ev| input.set(event_target_value(&ev))
+                    on:keydown=on_enter(save)
+                    prop:value=input
+                />
+                {attribute.unit.clone()}
+            }.into_view(),
+        };
+        let edit_view = store_value(edit_view);
+
+        view! {
+            <div class="flex flex-row gap-2">
+                <div>{attribute.name}":"</div>
+                {
+                    move || if admin_state().set {
+                        edit_view()
+                    } else {
+                        value.clone().unwrap_or("—".to_string()).into_view()
+                    }
+                }
+            </div>
+        }
+    }).collect_view();
+
+    view! {
+        {attributes_view}
+        {
+            move || admin_state().set.then(|| view! {
+                <button
+                    class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                    disabled=set_attributes_action.pending()
+                    on:click=move |_| save()
+                >
+                    "Зберегти атрибути"
+                </button>
+            })
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use leptos::*;
+
+use crate::{data::{item::{ItemId, GroupedTags, TagId}, categories::CategoryId, bulk::BulkOutcome, live::LiveEvent, search::SearchResults}, server_funcs::{items::{tag_items, untag_items, move_items, remove_items, export_items}, categories::get_categories}, ui::{optimistic::Optimistic, toasts::Toasts}};
+
+use super::state::SearchQuery;
+
+/// Items chosen in the grid for a bulk change.
+#[derive(Clone, Copy)]
+pub struct ItemSelection(RwSignal<Vec<ItemId>>);
+
+impl ItemSelection {
+    pub fn provide() -> ItemSelection {
+        let selection = ItemSelection(create_rw_signal(vec![]));
+        provide_context(selection);
+        selection
+    }
+
+    pub fn use_selection() -> ItemSelection {
+        use_context::<ItemSelection>()
+            .expect("`ItemSelection` to be added to the context")
+    }
+
+    pub fn is_selected(&self, item_id: ItemId) -> bool {
+        self.0.with(|selected| selected.contains(&item_id))
+    }
+
+    pub fn toggle(&self, item_id: ItemId) {
+        self.0.update(|selected| match selected.iter().position(|selected_id| *selected_id == item_id) {
+            Some(idx) => { selected.remove(idx); },
+            None => selected.push(item_id),
+        })
+    }
+
+    pub fn deselect(&self, item_ids: &[ItemId]) {
+        self.0.update(|selected| selected.retain(|item_id| !item_ids.contains(item_id)))
+    }
+}
+
+/// Checkbox of an item card, which adds the item to the selection.
+#[component]
+pub fn SelectItem(item_id: ItemId) -> impl IntoView {
+    let selection = ItemSelection::use_selection();
+
+    view! {
+        <input
+            type="checkbox"
+            prop:checked=move || selection.is_selected(item_id)
+            on:change=move |_| selection.toggle(item_id)
+        />
+    }
+}
+
+#[derive(Clone, Copy)]
+enum BulkChange {
+    AddTag(TagId),
+    RemoveTag(TagId),
+    Move(CategoryId),
+}
+
+impl BulkChange {
+    fn failure(&self) -> &'static str {
+        match self {
+            BulkChange::AddTag(_) => "Не вдалося додати тег",
+            BulkChange::RemoveTag(_) => "Не вдалося прибрати тег",
+            BulkChange::Move(_) => "Не вдалося перемістити товари",
+        }
+    }
+}
+
+/// Encodes the CSV into a link, which downloads it without asking the server again.
+fn csv_data_url(csv: &str) -> String {
+    let mut url = String::from("data:text/csv;charset=utf-8,");
+    for byte in csv.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    url
+}
+
+/// Changes of the selected items, the ones, which fail, are reported together and stay selected.
+#[component]
+pub fn BulkActions(
+    items_resource: Resource<SearchQuery, Result<SearchResults, ServerFnError>>,
+    tags: Resource<(), Result<Vec<GroupedTags>, ServerFnError>>,
+) -> impl IntoView {
+    let selection = ItemSelection::use_selection();
+    let toasts = Toasts::use_toasts();
+    let optimistic = Optimistic::new(items_resource);
+    let categories = create_resource(|| (), |_| get_categories());
+
+    let (tag_id, tag_id_set) = create_signal(None::<TagId>);
+    let (category_id, category_id_set) = create_signal(None::<CategoryId>);
+    let (export_url, export_url_set) = create_signal(None::<String>);
+
+    let shown_item_ids = move || {
+        items_resource().and_then(|results| results.ok())
+            .map(|results| results.items.iter().map(|item| item.id).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+
+    // The changed items are shown as the server returned them
+    let change_action = create_action(move |input: &(BulkChange, Vec<ItemId>)| {
+        let (change, item_ids) = input.clone();
+
+        async move {
+            let outcome = match change {
+                BulkChange::AddTag(tag_id) => optimistic.confirmed(tag_items(item_ids, tag_id), change.failure()).await,
+                BulkChange::RemoveTag(tag_id) => optimistic.confirmed(untag_items(item_ids, tag_id), change.failure()).await,
+                BulkChange::Move(category_id) => optimistic.confirmed(move_items(item_ids, category_id), change.failure()).await,
+            };
+
+            if let Some(outcome) = outcome {
+                optimistic.update(|results| {
+                    for item in &outcome.succeeded {
+                        LiveEvent::ItemUpdated(item.clone()).apply_to_items(&mut results.items);
+                    }
+                });
+                show_failures(toasts, &outcome, change.failure());
+            }
+        }
+    });
+
+    let remove_action = create_action(move |item_ids: &Vec<ItemId>| {
+        let item_ids = item_ids.clone();
+
+        async move {
+            optimistic.update(|results| results.items.retain(|item| !item_ids.contains(&item.id)));
+
+            match remove_items(item_ids.clone()).await {
+                Ok(pending) => {
+                    selection.deselect(&item_ids);
+                    toasts.show_undo(
+                        format!("Видалено товарів: {}", item_ids.len()),
+                        "Не вдалося видалити товари",
+                        pending.id,
+                        move || items_resource.refetch(),
+                    );
+                },
+                Err(err) => {
+                    items_resource.refetch();
+                    toasts.show_error(err, "Не вдалося видалити товари");
+                },
+            }
+        }
+    });
+
+    let export_action = create_action(move |item_ids: &Vec<ItemId>| {
+        let item_ids = item_ids.clone();
+
+        async move {
+            if let Some(csv) = optimistic.confirmed(export_items(item_ids), "Не вдалося експортувати товари").await {
+                export_url_set(Some(csv_data_url(&csv)));
+            }
+        }
+    });
+
+    let selected = move || selection.0.get();
+    let nothing_selected = move || selection.0.with(Vec::is_empty);
+
+    let tag_options = move || {
+        tags().and_then(|tags| tags.ok()).map(|tags| {
+            GroupedTags::all_tags(&tags).map(|tag| view! {
+                <option value=tag.id.to_string()>{tag.name.clone()}</option>
+            }).collect_view()
+        })
+    };
+
+    let category_options = move || {
+        categories().and_then(|categories| categories.ok()).map(|categories| {
+            categories.into_iter().map(|category| view! {
+                <option value=category.id.0.to_string()>{category.name}</option>
+            }).collect_view()
+        })
+    };
+
+    let export_link = move || export_url().map(|url| view! {
+        <a class="underline" href=url download="items.csv">"Завантажити CSV"</a>
+    });
+
+    view! {
+        <div class="col-span-2 flex flex-row flex-wrap items-center gap-2 border-solid border-black border p-1">
+            <div>"Вибрано: " {move || selection.0.with(Vec::len)}</div>
+            <button class="underline" on:click=move |_| selection.0.set(shown_item_ids())>"Вибрати всі"</button>
+            <button class="underline" on:click=move |_| selection.0.set(vec![])>"Зняти вибір"</button>
+
+            <Transition fallback=|| ()>
+                <select
+                    class="rounded-lg p-1 border-solid border-slate-400 border"
+                    on:change=move |ev| tag_id_set(event_target_value(&ev).parse().ok())
+                >
+                    <option value="">"Тег"</option>
+                    {tag_options}
+                </select>
+            </Transition>
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                disabled=move || nothing_selected() || tag_id().is_none()
+                on:click=move |_| {
+                    if let Some(tag_id) = tag_id() {
+                        change_action.dispatch((BulkChange::AddTag(tag_id), selected()))
+                    }
+                }
+            >
+                "Додати тег"
+            </button>
+            <button
+                class="bg-red-700 rounded-xl px-2"
+                disabled=move || nothing_selected() || tag_id().is_none()
+                on:click=move |_| {
+                    if let Some(tag_id) = tag_id() {
+                        change_action.dispatch((BulkChange::RemoveTag(tag_id), selected()))
+                    }
+                }
+            >
+                "Прибрати тег"
+            </button>
+
+            <Transition fallback=|| ()>
+                <select
+                    class="rounded-lg p-1 border-solid border-slate-400 border"
+                    on:change=move |ev| category_id_set(event_target_value(&ev).parse().ok())
+                >
+                    <option value="">"Категорія"</option>
+                    {category_options}
+                </select>
+            </Transition>
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                disabled=move || nothing_selected() || category_id().is_none()
+                on:click=move |_| {
+                    if let Some(category_id) = category_id() {
+                        change_action.dispatch((BulkChange::Move(category_id), selected()))
+                    }
+                }
+            >
+                "Перемістити"
+            </button>
+
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                disabled=nothing_selected
+                on:click=move |_| export_action.dispatch(selected())
+            >
+                "Експорт"
+            </button>
+            {export_link}
+
+            <button
+                class="bg-red-700 rounded-xl px-2"
+                disabled=nothing_selected
+                on:click=move |_| remove_action.dispatch(selected())
+            >
+                "Видалити"
+            </button>
+        </div>
+    }
+}
+
+fn show_failures<T>(toasts: Toasts, outcome: &BulkOutcome<T>, failure: &str) {
+    if let Some(reasons) = outcome.failure_reasons() {
+        toasts.show(format!("{failure}: {reasons}"));
+    }
+}
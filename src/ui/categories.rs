@@ -1,40 +1,55 @@
-use leptos::{*, html::P};
+use leptos::*;
 
-use crate::{server_funcs::categories::{add_category, get_categories, remove_category}, data::categories::{Category, CategoryId}, ui::state::AdminState};
+use crate::{server_funcs::categories::{add_category, get_category_tree, get_category_path, remove_category, rename_category}, data::{categories::{Category, CategoryId, CategoryTree}, live::LiveEvent, deletions::PendingDeletion}, ui::{state::AdminState, live::LiveEvents, toasts::Toasts, optimistic::Optimistic, keybindings::on_enter}};
 
 use super::state::SearchQuery;
 
 #[component]
-pub fn CategoryButton<F>(category: Category, remove_category_cb: F) -> impl IntoView
+pub fn CategoryButton<F, R>(category: Category, remove_category_cb: F, rename_category_cb: R) -> impl IntoView
 where
-    F: Fn(&CategoryId) + Copy + 'static
+    F: Fn(&CategoryId, PendingDeletion) + Copy + 'static,
+    R: Fn(Category) + Copy + 'static,
 {
     let admin_state = use_context::<ReadSignal<AdminState>>()
         .expect("`AdminState` to be added to the context");
 
     let search_query = SearchQuery::use_query();
     let update_category = {
-        let category_name = category.name.clone();
+        let category = category.clone();
         move |_| {
             let mut search_query = search_query();
-            search_query.category = Some(category_name.clone());
+            search_query.category = Some(category.clone());
             search_query.set();
         }
     };
-    let is_category_chosen = {
-        let category_name = category.name.clone();
-        move || search_query().category == Some(category_name.clone())
-    };
-    let is_category_not_chosen = {
-        let is_category_chosen = is_category_chosen.clone();
-        move || !is_category_chosen()
-    };
+    let is_category_chosen = move || search_query().category.is_some_and(|chosen| chosen.id == category.id);
+    let is_category_not_chosen = move || !is_category_chosen();
+
+    let toasts = Toasts::use_toasts();
+
+    let (new_name, new_name_set) = create_signal(category.name.clone());
+    let rename_category_action = create_action(move |input: &String| {
+        let input = input.clone();
+        async move {
+            rename_category(category.id, input).await
+        }
+    });
+
+    // The callback navigates, so it's called in an effect, since router context isn't available inside the action future
+    create_effect(move |_| {
+        match rename_category_action.value()() {
+            Some(Ok(renamed)) => rename_category_cb(renamed),
+            Some(Err(err)) => toasts.show_error(err, "Не вдалося перейменувати категорію"),
+            None => {},
+        }
+    });
 
     let remove_category_action = create_action(move |_| {
         async move {
-            remove_category(category.id).await?;
-            remove_category_cb(&category.id);
-            Ok::<_, ServerFnError>(())
+            match remove_category(category.id).await {
+                Ok(pending) => remove_category_cb(&category.id, pending),
+                Err(err) => toasts.show_error(err, "Не вдалося видалити категорію"),
+            }
         }
     });
 
@@ -43,15 +58,33 @@ where
             <button
                 class="text-xl p-2 rounded-xl border-solid border-blue-700 border-4"
                 class=("bg-slate-200", is_category_not_chosen)
-                class=("bg-blue-200", is_category_chosen.clone())
+                class=("bg-blue-200", is_category_chosen)
                 on:click=update_category
-                disabled=is_category_chosen.clone()
+                disabled=is_category_chosen
             >
                 {category.name}
             </button>
 
             {
                 move || admin_state().set.then(|| view! {
+                    <input
+                        class="rounded-lg p-1"
+                        type="text"
+                        on:input=move |ev| {
+                            new_name_set(event_target_value(&ev))
+                        }
+
+                        prop:value=new_name
+                    />
+                    <button
+                        on:click=move |_| {
+                            rename_category_action.dispatch(new_name())
+                        }
+                        class="bg-slate-400 disabled:text-slate-200 rounded-xl"
+                        disabled=rename_category_action.pending()
+                    >
+                        Перейменувати
+                    </button>
                     <button
                         on:click=move |_| {
                             remove_category_action.dispatch(())
@@ -68,8 +101,24 @@ where
 }
 
 #[component]
-pub fn AddCategory(add_item_action: Action<String, ()>) -> impl IntoView {
+pub fn AddCategory(
+    add_item_action: Action<(String, Option<CategoryId>), ()>,
+    categories: Resource<(), Result<Vec<CategoryTree>, ServerFnError>>,
+) -> impl IntoView {
     let (new_category_name, new_category_set) = create_signal(String::new());
+    let (parent_id, parent_id_set) = create_signal(None::<CategoryId>);
+
+    let parent_options = move || {
+        categories().and_then(|categories| categories.ok()).map(|categories| {
+            CategoryTree::flatten(&categories).into_iter().map(|(depth, category)| {
+                view! {
+                    <option value=category.id.0.to_string()>
+                        {"— ".repeat(depth)}{category.name}
+                    </option>
+                }
+            }).collect_view()
+        })
+    };
 
     view! {
         <div class="flex flex-col items-center">
@@ -79,13 +128,23 @@ pub fn AddCategory(add_item_action: Action<String, ()>) -> impl IntoView {
                 on:input=move |ev| {
                     new_category_set(event_target_value(&ev))
                 }
+                on:keydown=on_enter(move || add_item_action.dispatch((new_category_name(), parent_id())))
 
                 prop:value=new_category_name
             />
+            <select
+                class="rounded-lg p-1"
+                on:change=move |ev| {
+                    parent_id_set(event_target_value(&ev).parse().ok())
+                }
+            >
+                <option value="">"Без батьківської категорії"</option>
+                {parent_options}
+            </select>
             <button
                 class="bg-green-700 rounded-xl px-2"
                 on:click=move |_| {
-                    add_item_action.dispatch(new_category_name())
+                    add_item_action.dispatch((new_category_name(), parent_id()))
                 }
             >
                 "Додати"
@@ -94,35 +153,128 @@ pub fn AddCategory(add_item_action: Action<String, ()>) -> impl IntoView {
     }
 }
 
+/// A category button with its sub-categories, which can be expanded.
+#[component]
+pub fn CategoryNode<F, R>(tree: CategoryTree, remove_category_cb: F, rename_category_cb: R) -> impl IntoView
+where
+    F: Fn(&CategoryId, PendingDeletion) + Copy + 'static,
+    R: Fn(Category) + Copy + 'static,
+{
+    let (expanded, expanded_set) = create_signal(false);
+    let children = store_value(tree.children);
+    let has_children = !children.with_value(Vec::is_empty);
+
+    let children_view = move || {
+        expanded().then(|| view! {
+            <div class="flex flex-col gap-1 pl-4 border-l-2 border-solid border-blue-700">
+                {
+                    children().into_iter().map(|tree| {
+                        // Boxed into a `View`, so the opaque return type doesn't recurse into itself
+                        CategoryNode(CategoryNodeProps { tree, remove_category_cb, rename_category_cb }).into_view()
+                    }).collect_view()
+                }
+            </div>
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <div class="flex flex-row items-start gap-1">
+                {
+                    has_children.then(|| view! {
+                        <button
+                            class="text-xl p-2"
+                            on:click=move |_| expanded_set.update(|expanded| *expanded = !*expanded)
+                        >
+                            {move || if expanded() { "▾" } else { "▸" }}
+                        </button>
+                    })
+                }
+                <CategoryButton category=tree.category remove_category_cb rename_category_cb/>
+            </div>
+            {children_view}
+        </div>
+    }
+}
+
+/// Clears the chosen category, so that items of all the categories are shown.
+#[component]
+pub fn AllCategoriesButton() -> impl IntoView {
+    let search_query = SearchQuery::use_query();
+    let is_chosen = move || search_query().category.is_none();
+
+    let choose_all = move |_| {
+        let mut search_query = search_query();
+        search_query.category = None;
+        search_query.subcategories = false;
+        search_query.set();
+    };
+
+    view! {
+        <button
+            class="text-xl p-2 rounded-xl border-solid border-blue-700 border-4"
+            class=("bg-slate-200", move || !is_chosen())
+            class=("bg-blue-200", is_chosen)
+            on:click=choose_all
+            disabled=is_chosen
+        >
+            "Усі"
+        </button>
+    }
+}
+
 #[component]
 pub fn CategoriesBlock() -> impl IntoView {
-    let categories = create_resource(|| (), |_| get_categories());
+    let search_query = SearchQuery::use_query_untracked();
+    let categories = create_resource(|| (), |_| get_category_tree());
     let categories_loading = categories.loading();
 
+    LiveEvents::on_event(move |event| match event {
+        LiveEvent::Missed => categories.refetch(),
+        event => categories.update(|categories| {
+            if let Some(Ok(categories)) = categories {
+                event.apply_to_categories(categories);
+            }
+        }),
+    });
+
     let loading = move || view! {
         Завантаження категорій...
     };
 
-    let remove_category_cb = move |category_id: &CategoryId| {
+    let toasts = Toasts::use_toasts();
+    let remove_category_cb = move |category_id: &CategoryId, pending: PendingDeletion| {
         categories.update(|categories| {
             // PANIC: unwraps are fine, because this action is passed to a component, that is
             //        rendered only after categories have loaded.
-            let categories = categories.as_mut().unwrap().as_mut().unwrap();
-            // PANIC: categories are rendered from the vec, from which we're removing an item.
-            let idx = categories
-                .iter()
-                .position(|category| &category.id == category_id)
-                .unwrap();
-            categories.remove(idx);
-        })
+            CategoryTree::remove(categories.as_mut().unwrap().as_mut().unwrap(), category_id);
+        });
+        toasts.show_undo("Категорію видалено".to_string(), "Не вдалося видалити категорію", pending.id, move || categories.refetch());
+    };
+
+    let rename_category_cb = move |renamed: Category| {
+        categories.update(|categories| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after categories have loaded.
+            if let Some(tree) = CategoryTree::find_mut(categories.as_mut().unwrap().as_mut().unwrap(), &renamed.id) {
+                tree.category = renamed.clone();
+            }
+        });
+
+        // The address of the chosen category follows its new slug
+        let mut search_query = search_query();
+        if search_query.category.as_ref().is_some_and(|chosen| chosen.id == renamed.id) {
+            search_query.category = Some(renamed);
+            search_query.set();
+        }
     };
 
     let loaded_category_buttons = move || {
         categories().map(|categories| {
             match categories {
-                Ok(categories) => categories.into_iter().map(|category| {
+                Ok(categories) => categories.into_iter().map(|tree| {
                     view! {
-                        <CategoryButton category remove_category_cb/>
+                        <CategoryNode tree remove_category_cb rename_category_cb/>
                     }
                 }).collect_view(),
                 Err(_) => view! { Помилка завантаження категорій }.into_view(),
@@ -130,15 +282,12 @@ pub fn CategoriesBlock() -> impl IntoView {
         })
     };
 
-    let add_category_action = create_action(move |input: &String| {
-        let input = input.clone();
+    let optimistic = Optimistic::new(categories);
+    let add_category_action = create_action(move |input: &(String, Option<CategoryId>)| {
+        let (name, parent_id) = input.clone();
         async move {
-            if let Ok(new_category) = add_category(input).await {
-                categories.update(|categories| {
-                    // PANIC: unwraps are fine, because this action is passed to a component, that is
-                    //        rendered only after categories have loaded.
-                    categories.as_mut().unwrap().as_mut().unwrap().push(new_category)
-                })
+            if let Some(new_category) = optimistic.confirmed(add_category(name, parent_id), "Не вдалося додати категорію").await {
+                optimistic.update(|categories| LiveEvent::CategoryAdded(new_category).apply_to_categories(categories))
             }
         }
     });
@@ -150,16 +299,78 @@ pub fn CategoriesBlock() -> impl IntoView {
         <Suspense
             fallback=loading
         >
-            <div class="flex flex-row items-center gap-2 bg-blue-400 shadow-lg shadow-blue-300/50 p-2">
+            <div class="flex flex-row items-start gap-2 bg-blue-400 shadow-lg shadow-blue-300/50 p-2">
+                <AllCategoriesButton />
                 {loaded_category_buttons}
                 {
                     move || (!categories_loading() && admin_state().set).then(||
                         view! {
-                            <AddCategory add_item_action=add_category_action />
+                            <AddCategory add_item_action=add_category_action categories />
                         }
                     )
                 }
             </div>
         </Suspense>
     }
-}
\ No newline at end of file
+}
+
+/// Path from the root category to the chosen one, with a toggle for showing sub-categories' items.
+#[component]
+pub fn CategoryBreadcrumbs() -> impl IntoView {
+    let search_query = SearchQuery::use_query();
+    let path = create_resource(
+        move || search_query().category.map(|category| category.id),
+        |category_id| async move {
+            match category_id {
+                Some(category_id) => get_category_path(category_id).await,
+                None => Ok(vec![]),
+            }
+        }
+    );
+
+    let crumbs = move || {
+        path().and_then(|path| path.ok()).map(|path| {
+            path.into_iter().enumerate().map(|(idx, category)| {
+                let select_category = {
+                    let category = category.clone();
+                    move |_| {
+                        let mut search_query = search_query();
+                        search_query.category = Some(category.clone());
+                        search_query.set();
+                    }
+                };
+
+                view! {
+                    {(idx != 0).then_some("/")}
+                    <button class="underline" on:click=select_category>{category.name}</button>
+                }
+            }).collect_view()
+        })
+    };
+
+    let toggle_subcategories = move |ev| {
+        let mut search_query = search_query();
+        search_query.subcategories = event_target_checked(&ev);
+        search_query.set();
+    };
+
+    view! {
+        <Transition fallback=|| ()>
+            {
+                move || search_query().category.is_some().then(|| view! {
+                    <div class="flex flex-row items-center gap-2 p-2">
+                        {crumbs}
+                        <label class="ml-auto flex flex-row items-center gap-1">
+                            <input
+                                type="checkbox"
+                                on:change=toggle_subcategories
+                                prop:checked=move || search_query().subcategories
+                            />
+                            "Включно з підкатегоріями"
+                        </label>
+                    </div>
+                })
+            }
+        </Transition>
+    }
+}
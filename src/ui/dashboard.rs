@@ -0,0 +1,134 @@
+use leptos::*;
+use leptos_router::A;
+
+use crate::{server_funcs::stats::get_stats, data::stats::{Stats, ItemSummary}};
+
+use super::state::SearchQuery;
+
+#[component]
+fn CountCard(label: &'static str, count: i64) -> impl IntoView {
+    view! {
+        <div class="flex flex-col items-center p-4 bg-slate-200 rounded-xl shadow-lg">
+            <div class="text-4xl">{count}</div>
+            <div>{label}</div>
+        </div>
+    }
+}
+
+#[component]
+fn ItemLink(item: ItemSummary) -> impl IntoView {
+    let href = SearchQuery {
+        q: Some(item.item_name.clone()),
+        category: Some(item.category.clone()),
+        ..Default::default()
+    }.href();
+
+    view! {
+        <A href class="flex flex-row gap-2">
+            <div class="underline">{item.item_name}</div>
+            <div class="text-sm">{item.category.name}</div>
+        </A>
+    }
+}
+
+#[component]
+fn StatsView(stats: Stats) -> impl IntoView {
+    view! {
+        <div class="grid gap-4 grid-cols-4">
+            <CountCard label="Категорій" count=stats.categories_count />
+            <CountCard label="Товарів" count=stats.items_count />
+            <CountCard label="Об'єктів" count=stats.objects_count />
+            <CountCard label="Тегів" count=stats.tags_count />
+        </div>
+        <div class="grid gap-4 grid-cols-2">
+            <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                <div class="font-bold">"Товарів у категоріях"</div>
+                {
+                    stats.items_per_category.into_iter().map(|count| {
+                        let href = SearchQuery {
+                            category: Some(count.category.clone()),
+                            ..Default::default()
+                        }.href();
+                        view! {
+                            <A href class="flex flex-row">
+                                <div class="underline">{count.category.name}</div>
+                                <div class="ml-auto">{count.items_count}</div>
+                            </A>
+                        }
+                    }).collect_view()
+                }
+            </div>
+            <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                <div class="font-bold">"Найуживаніші теги"</div>
+                {
+                    stats.top_tags.into_iter().map(|tag| view! {
+                        <div class="flex flex-row">
+                            <div class="border-solid border-2 rounded-lg px-1" style=format!("border-color: {}", tag.color)>
+                                {tag.tag_name}
+                            </div>
+                            <div class="ml-auto">{tag.items_count}</div>
+                        </div>
+                    }).collect_view()
+                }
+            </div>
+            <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                <div class="font-bold">"Нещодавно додані товари"</div>
+                {
+                    stats.recent_items.into_iter().map(|item| view! {
+                        <ItemLink item />
+                    }).collect_view()
+                }
+            </div>
+            <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                <div class="font-bold">"Нещодавно додані об'єкти"</div>
+                {
+                    stats.recent_objects.into_iter().map(|object| {
+                        let href = SearchQuery {
+                            q: Some(object.item_name.clone()),
+                            category: Some(object.category),
+                            highlight: Some(object.object_id),
+                            ..Default::default()
+                        }.href();
+                        view! {
+                            <A href class="flex flex-row gap-2">
+                                <div class="underline">{object.item_code.unwrap_or("Код відсутній".into())}</div>
+                                <div class="text-sm">{object.item_name}</div>
+                                <div class="text-sm ml-auto">{object.created_at.format("%d.%m.%Y %H:%M").to_string()}</div>
+                            </A>
+                        }
+                    }).collect_view()
+                }
+            </div>
+            <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                <div class="font-bold">{format!("Товари без об'єктів: {}", stats.items_without_objects.len())}</div>
+                {
+                    stats.items_without_objects.into_iter().map(|item| view! {
+                        <ItemLink item />
+                    }).collect_view()
+                }
+            </div>
+        </div>
+    }
+}
+
+#[component]
+pub fn Dashboard() -> impl IntoView {
+    let stats = create_resource(|| (), |_| get_stats());
+
+    let stats_view = move || {
+        stats().map(|stats| {
+            match stats {
+                Ok(stats) => view! { <StatsView stats /> }.into_view(),
+                Err(_) => view! { Помилка завантаження статистики }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-4 p-2">
+            <Suspense fallback=move || view! { Завантаження статистики... }>
+                {stats_view}
+            </Suspense>
+        </div>
+    }
+}
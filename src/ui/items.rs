@@ -1,41 +1,98 @@
 use leptos::*;
 
-use crate::{data::item::{Item, ItemId, ItemObjectId, ItemObject, Tag, TagId}, server_funcs::items::{search_items, add_item, add_item_object, remove_item, remove_item_object, add_item_tag, remove_item_tag}, ui::state::AdminState};
+use crate::{data::{item::{Item, ItemId, ItemObjectId, ItemObject, ItemVariant, ItemVariantId, Tag, TagId, GroupedTags}, attributes::{CategoryAttribute, AttributeInput, ItemAttributes}, variants::VariantAxis, locations::{Location, LocationId}, live::LiveEvent, search::SearchResults}, server_funcs::{items::{search_items, add_item, add_item_object, remove_item, remove_item_object, add_item_tag, remove_item_tag}, attributes::set_item_attributes, locations::transfer_objects, variants::{add_item_variants, remove_item_variant}}, ui::{state::AdminState, live::LiveEvents, toasts::Toasts, optimistic::{Optimistic, pending_id, find_removed, restore}, attributes::ItemAttributesView, attachments::ItemGallery, locations::{LocationSelect, StockCounts, ObjectTransfers}, bulk::{ItemSelection, SelectItem, BulkActions}, variants::{VariantSelect, VariantStock, VariantMatrix}, keybindings::{Keybindings, on_enter}}};
 
 use super::state::SearchQuery;
 
+/// Changes a found item, if it's still among the results.
+fn change_item(results: &mut SearchResults, item_id: ItemId, change: impl FnOnce(&mut Item)) {
+    if let Some(item) = results.items.iter_mut().find(|item| item.id == item_id) {
+        change(item);
+    }
+}
+
+/// The item, which the keyboard shortcuts act on.
+#[derive(Clone, Copy)]
+struct ItemFocus {
+    item_id: RwSignal<Option<ItemId>>,
+    /// Set to the focused item, to start typing the code of its new object.
+    adding_object: RwSignal<Option<ItemId>>,
+}
+
+impl ItemFocus {
+    fn use_focus() -> ItemFocus {
+        use_context::<ItemFocus>()
+            .expect("`ItemFocus` to be added to the context")
+    }
+
+    /// Focuses the item `step` items after the focused one in the order they are shown, or the first one.
+    fn step(&self, shown: &[ItemId], step: isize) {
+        if shown.is_empty() {
+            return;
+        }
+        let idx = match self.item_id.get_untracked().and_then(|item_id| shown.iter().position(|shown_id| *shown_id == item_id)) {
+            Some(idx) => (idx as isize + step).rem_euclid(shown.len() as isize) as usize,
+            None => 0,
+        };
+        self.item_id.set(Some(shown[idx]));
+    }
+}
+
 #[component]
-pub fn ItemObject<RemObjF>(object: ItemObject, remove_object_cb: RemObjF) -> impl IntoView
-where
-    RemObjF: Fn(&ItemObjectId) + Copy + 'static,
-{
+pub fn ItemObject(
+    object: ItemObject,
+    locations: Vec<Location>,
+    remove_object_action: Action<ItemObjectId, ()>,
+    transfer_object_action: Action<(ItemObjectId, LocationId), ()>,
+) -> impl IntoView {
     let admin_state = use_context::<ReadSignal<AdminState>>()
         .expect("`AdminState` to be added to the context");
 
-    let remove_object_action = create_action(move |_| {
-        async move {
-            remove_item_object(object.id).await?;
-            remove_object_cb(&object.id);
-            Ok::<_, ServerFnError>(())
-        }
-    });
+    let search_query = SearchQuery::use_query();
+    let is_highlighted = move || search_query().highlight == Some(object.id);
+
+    let (history_shown, history_shown_set) = create_signal(false);
+
+    let location_name = locations.iter()
+        .find(|location| Some(location.id) == object.location_id)
+        .map(|location| location.name.clone())
+        .unwrap_or("Без місця".to_string());
+    let locations = store_value(locations);
 
     view! {
         <div class="flex flex-col gap-1">
             <div
                 class="border-solid border-slate-200 border-2 rounded-lg"
+                class=("bg-yellow-200", is_highlighted)
             >
                 {object.item_code.unwrap_or("Код відсутній".into())}
+                <div class="text-sm">{location_name}</div>
             </div>
+            <button
+                class="text-sm underline"
+                on:click=move |_| history_shown_set.update(|shown| *shown = !*shown)
+            >
+                "Історія"
+            </button>
+            {move || history_shown().then(|| view! { <ObjectTransfers object_id=object.id /> })}
 
             {
                 move || admin_state().set.then(|| view! {
+                    <LocationSelect
+                        locations=locations()
+                        selected=object.location_id
+                        on_select=move |location_id| {
+                            // Objects can only be moved to a location, not out of every one
+                            if let Some(location_id) = location_id {
+                                transfer_object_action.dispatch((object.id, location_id))
+                            }
+                        }
+                    />
                     <button
                         on:click=move |_| {
-                            remove_object_action.dispatch(())
+                            remove_object_action.dispatch(object.id)
                         }
-                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
-                        disabled=remove_object_action.pending()
+                        class="bg-red-700 rounded-xl"
                     >
                         Видалити
                     </button>
@@ -46,25 +103,51 @@ where
 }
 
 #[component]
-pub fn AddObject(add_object_action: Action<String, ()>) -> impl IntoView {
+pub fn AddObject(
+    item_id: ItemId,
+    locations: Vec<Location>,
+    variants: Vec<ItemVariant>,
+    add_object_action: Action<(String, Option<LocationId>, Option<ItemVariantId>), ()>,
+) -> impl IntoView {
     let (new_object_name, new_object_set) = create_signal(String::new());
+    let (new_object_location, new_object_location_set) = create_signal(None::<LocationId>);
+    let (new_object_variant, new_object_variant_set) = create_signal(None::<ItemVariantId>);
+
+    // Cleared, so that the next code can be typed or scanned right away
+    let submit = move || {
+        add_object_action.dispatch((new_object_name(), new_object_location(), new_object_variant()));
+        new_object_set(String::new());
+    };
+
+    let focus = ItemFocus::use_focus();
+    let input_ref = create_node_ref::<html::Input>();
+    create_effect(move |_| {
+        if focus.adding_object.get() == Some(item_id) {
+            if let Some(input) = input_ref.get() {
+                let _ = input.focus();
+                focus.adding_object.set(None);
+            }
+        }
+    });
 
     view! {
         <div class="flex flex-col items-center border-solid border-black border">
             <input
                 class="rounded-lg p-1 border-solid border-slate-400 border"
                 type="text"
+                node_ref=input_ref
                 on:input=move |ev| {
                     new_object_set(event_target_value(&ev))
                 }
+                on:keydown=on_enter(submit)
 
                 prop:value=new_object_name
             />
+            <LocationSelect locations selected=None on_select=new_object_location_set />
+            <VariantSelect variants on_select=new_object_variant_set />
             <button
                 class="bg-green-700 rounded-xl px-2"
-                on:click=move |_| {
-                    add_object_action.dispatch(new_object_name())
-                }
+                on:click=move |_| submit()
             >
                 "Додати"
             </button>
@@ -73,25 +156,15 @@ pub fn AddObject(add_object_action: Action<String, ()>) -> impl IntoView {
 }
 
 #[component]
-pub fn ItemTag<RemTagF>(item_id: ItemId, tag: Tag, remove_tag_cb: RemTagF) -> impl IntoView
-where
-    RemTagF: Fn(&TagId) + Copy + 'static
-{
+pub fn ItemTag(tag: Tag, remove_tag_action: Action<TagId, ()>) -> impl IntoView {
     let admin_state = use_context::<ReadSignal<AdminState>>()
         .expect("`AdminState` to be added to the context");
 
-    let remove_tag_action = create_action(move |_| {
-        async move {
-            remove_item_tag(item_id, tag.id).await?;
-            remove_tag_cb(&tag.id);
-            Ok::<_, ServerFnError>(())
-        }
-    });
-
     view! {
         <div class="flex flex-col gap-1">
             <div
-                class="border-solid border-slate-400 border-2 rounded-lg"
+                class="border-solid border-2 rounded-lg"
+                style=format!("border-color: {}", tag.color)
             >
                 {tag.name}
             </div>
@@ -100,10 +173,9 @@ where
                 move || admin_state().set.then(|| view! {
                     <button
                         on:click=move |_| {
-                            remove_tag_action.dispatch(())
+                            remove_tag_action.dispatch(tag.id)
                         }
-                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
-                        disabled=remove_tag_action.pending()
+                        class="bg-red-700 rounded-xl"
                     >
                         Видалити
                     </button>
@@ -114,20 +186,34 @@ where
 }
 
 #[component]
-pub fn AddItemTag(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>, item_tags: Vec<Tag>, item_id: ItemId, add_tag_action: Action<(ItemId, Tag), ()>) -> impl IntoView {
+pub fn AddItemTag(tags: Resource<(), Result<Vec<GroupedTags>, ServerFnError>>, item_tags: Vec<Tag>, item_id: ItemId, add_tag_action: Action<(ItemId, Tag), ()>) -> impl IntoView {
     let item_tags = store_value(item_tags);
     let tags_filtered = move || {
         tags().map(move |tags| tags.map(move |tags|
-            tags.into_iter()
+            GroupedTags::all_tags(&tags)
                 .filter(move |tag| !item_tags().iter().any(|item_tag| item_tag.id == tag.id))
+                .cloned()
+                .collect::<Vec<_>>()
         ))
     };
-    let (new_tag_id, new_tag_set) = create_signal(tags_filtered().unwrap().unwrap().next().map(|tag| tag.id.to_string()).unwrap_or("".to_string()));
+    let (new_tag_id, new_tag_set) = create_signal(String::new());
+
+    // None, while the tags are loading, or if the chosen one is gone
+    let new_tag = move || {
+        let tags = tags_filtered()?.ok()?;
+        // The select shows the first tag, until another one is chosen
+        if new_tag_id().is_empty() {
+            return tags.first().cloned();
+        }
+        let tag_id = new_tag_id().parse::<TagId>().ok()?;
+        tags.into_iter().find(|tag| tag.id == tag_id)
+    };
 
     let tag_options = move || {
         tags_filtered().map(|tags| {
             match tags {
                 Ok(tags) => tags
+                    .into_iter()
                     .map(|tag| {
                         view! {
                             <option value=tag.id.to_string()>{tag.name}</option>
@@ -142,6 +228,12 @@ pub fn AddItemTag(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>, item_tags
         Завантаження тегів...
     };
 
+    let submit = move || {
+        if let Some(tag) = new_tag() {
+            add_tag_action.dispatch((item_id, tag))
+        }
+    };
+
     view! {
         <Suspense
             fallback=loading
@@ -151,17 +243,14 @@ pub fn AddItemTag(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>, item_tags
                 on:change=move |ev| {
                     new_tag_set(event_target_value(&ev));
                 }
+                on:keydown=on_enter(submit)
             >
                 {tag_options}
             </select>
             <button
-                class="bg-green-700 rounded-xl px-2"
-                on:click=move |_| {
-                    add_tag_action.dispatch((
-                        item_id,
-                        tags().unwrap().unwrap().into_iter().find(|tag| tag.id == new_tag_id().parse().unwrap()).unwrap()
-                    ))
-                }
+                class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                on:click=move |_| submit()
+                disabled=move || new_tag().is_none()
             >
                 "Додати"
             </button>
@@ -171,50 +260,122 @@ pub fn AddItemTag(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>, item_tags
 }
 
 #[component]
-pub fn ItemCard<RemItemF, RemObjF, RemTagF>(
+pub fn ItemCard(
     item: Item,
-    tags: Resource<(), Result<Vec<Tag>, ServerFnError>>,
-    remove_item_cb: RemItemF,
-    add_object_action: Action<String, ()>,
-    remove_object_cb: RemObjF,
+    tags: Resource<(), Result<Vec<GroupedTags>, ServerFnError>>,
+    attributes: Resource<(), Result<Vec<CategoryAttribute>, ServerFnError>>,
+    locations: Resource<(), Result<Vec<Location>, ServerFnError>>,
+    set_attributes_action: Action<Vec<AttributeInput>, ()>,
+    remove_item_action: Action<(), ()>,
+    add_object_action: Action<(String, Option<LocationId>, Option<ItemVariantId>), ()>,
+    remove_object_action: Action<ItemObjectId, ()>,
+    transfer_object_action: Action<(ItemObjectId, LocationId), ()>,
     add_tag_action: Action<(ItemId, Tag), ()>,
-    remove_tag_cb: RemTagF,
-) -> impl IntoView
-where
-    RemItemF: Fn(&ItemId) + Copy + 'static,
-    RemObjF: Fn(&ItemObjectId) + Copy + 'static,
-    RemTagF: Fn(&TagId) + Copy + 'static,
-{
+    remove_tag_action: Action<TagId, ()>,
+    add_variants_action: Action<Vec<VariantAxis>, ()>,
+    remove_variant_action: Action<ItemVariantId, ()>,
+) -> impl IntoView {
     let admin_state = use_context::<ReadSignal<AdminState>>()
         .expect("`AdminState` to be added to the context");
 
     let tags_view = item.tags.clone().into_iter().map(|tag| {
         view! {
-            <ItemTag item_id=item.id tag remove_tag_cb />
+            <ItemTag tag remove_tag_action />
         }
     }).collect_view();
 
-    let objects_view = item.objects.clone().into_iter().map(|object| {
-        view! {
-            <ItemObject object remove_object_cb />
-        }
-    }).collect_view();
+    let loaded_locations = move || locations().and_then(|locations| locations.ok()).unwrap_or_default();
 
-    let remove_item_action = create_action(move |_| {
-        async move {
-            remove_item(item.id).await?;
-            remove_item_cb(&item.id);
-            Ok::<_, ServerFnError>(())
+    let focus = ItemFocus::use_focus();
+    let is_focused = {
+        let item_id = item.id;
+        move || focus.item_id.get() == Some(item_id)
+    };
+    let card_ref = create_node_ref::<html::Div>();
+    create_effect(move |_| {
+        if is_focused() {
+            if let Some(card) = card_ref.get() {
+                card.scroll_into_view();
+            }
         }
     });
 
+    let objects_view = {
+        let objects = item.objects.clone();
+        move || {
+            objects.clone().into_iter().map(|object| {
+                view! {
+                    <ItemObject object locations=loaded_locations() remove_object_action transfer_object_action />
+                }
+            }).collect_view()
+        }
+    };
+
+    let stock_view = {
+        let objects = item.objects.clone();
+        move || view! { <StockCounts locations=loaded_locations() objects=objects.clone() /> }
+    };
+
+    let attributes_view = {
+        let category_id = item.category.id;
+        let values = item.attributes.clone();
+        move || {
+            attributes().and_then(|attributes| attributes.ok()).map(|attributes| {
+                let attributes = attributes.into_iter()
+                    .filter(|attribute| attribute.category_id == category_id)
+                    .collect::<Vec<_>>();
+                view! {
+                    <ItemAttributesView attributes values=values.clone() set_attributes_action />
+                }
+            })
+        }
+    };
+
+    let variant_matrix = {
+        let category_id = item.category.id;
+        move || {
+            attributes().and_then(|attributes| attributes.ok()).map(|attributes| {
+                let attributes = attributes.into_iter()
+                    .filter(|attribute| attribute.category_id == category_id)
+                    .collect::<Vec<_>>();
+                view! {
+                    <VariantMatrix attributes add_variants_action />
+                }
+            })
+        }
+    };
+
+    // Items without variants show the matrix only to the admin, to make them
+    let variants_view = {
+        let item = item.clone();
+        move || (!item.variants.is_empty() || admin_state().set).then(|| view! {
+            <div class="flex flex-row gap-2">
+                <div>"Варіанти:"</div>
+                <VariantStock item=item.clone() remove_variant_action />
+                {move || admin_state().set.then(variant_matrix)}
+            </div>
+        })
+    };
+
     view! {
-        <div class="flex flex-col gap-1">
-            <div class="border-2 border-solid border-blue-700 rounded-xl">
+        <div class="flex flex-col gap-1" node_ref=card_ref>
+            <div
+                class="border-2 border-solid border-blue-700 rounded-xl"
+                class=("ring-2 ring-yellow-400", is_focused)
+                on:click={
+                    let item_id = item.id;
+                    move |_| focus.item_id.set(Some(item_id))
+                }
+            >
                 <div class="flex flex-row gap-2">
+                    {
+                        let item_id = item.id;
+                        move || admin_state().set.then(|| view! { <SelectItem item_id /> })
+                    }
                     <div>"Назва:"</div>
                     <div>{item.name}</div>
                 </div>
+                {attributes_view}
                 <div class="flex flex-row gap-2">
                     <div>Теги:</div>
                     <div class="flex flex-row gap-1">{tags_view}</div>
@@ -226,13 +387,22 @@ where
                         )
                     }
                 </div>
+                <div class="flex flex-row gap-2">
+                    <div>Файли:</div>
+                    <ItemGallery item_id=item.id />
+                </div>
+                <div class="flex flex-row gap-2">
+                    <div>Залишки:</div>
+                    {stock_view}
+                </div>
+                {variants_view}
                 <div class="flex flex-row gap-2">
                     <div>Наявні предмети:</div>
                     <div class="flex flex-row gap-1">{objects_view}</div>
                     {
                         move || admin_state().set.then(||
                             view! {
-                                <AddObject add_object_action />
+                                <AddObject item_id=item.id locations=loaded_locations() variants=item.variants.clone() add_object_action />
                             }
                         )
                     }
@@ -245,8 +415,7 @@ where
                         on:click=move |_| {
                             remove_item_action.dispatch(())
                         }
-                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
-                        disabled=remove_item_action.pending()
+                        class="bg-red-700 rounded-xl"
                     >
                         Видалити
                     </button>
@@ -268,6 +437,7 @@ pub fn AddItem(add_item_action: Action<String, ()>) -> impl IntoView {
                 on:input=move |ev| {
                     new_item_set(event_target_value(&ev))
                 }
+                on:keydown=on_enter(move || add_item_action.dispatch(new_item_name()))
 
                 prop:value=new_item_name
             />
@@ -284,112 +454,332 @@ pub fn AddItem(add_item_action: Action<String, ()>) -> impl IntoView {
 }
 
 #[component]
-pub fn Items(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>) -> impl IntoView {
+pub fn Items(
+    tags: Resource<(), Result<Vec<GroupedTags>, ServerFnError>>,
+    attributes: Resource<(), Result<Vec<CategoryAttribute>, ServerFnError>>,
+    locations: Resource<(), Result<Vec<Location>, ServerFnError>>,
+) -> impl IntoView {
     let search_query = SearchQuery::use_query();
     let items_resource = create_resource(
         search_query,
         |search_query| {
             async move {
-                // If category isn't set - we don't load anything yet
-                let Some(category) = search_query.category else { return Ok(vec![]) };
                 search_items(
                     search_query.q,
                     search_query.filter_tags,
-                    category
+                    search_query.category.map(|category| category.id),
+                    search_query.subcategories,
+                    search_query.attributes,
+                    search_query.location,
+                    search_query.tag
                 ).await
             }
         }
     );
     let items_loading = items_resource.loading();
+    ItemSelection::provide();
+
+    LiveEvents::on_event(move |event| match event {
+        LiveEvent::Missed => items_resource.refetch(),
+        LiveEvent::ItemAdded(new_item) => {
+            // A new item has no tags, attributes or objects, so only a search by the category can find it
+            let search_query = search_query();
+            let is_found = search_query.category.map_or(true, |category| category.id == new_item.category.id)
+                && search_query.q.as_deref().map_or(true, str::is_empty)
+                && search_query.filter_tags.is_empty()
+                && search_query.attributes.is_empty()
+                && search_query.location.is_none()
+                && search_query.tag.is_none();
+
+            if is_found {
+                items_resource.update(|items| {
+                    if let Some(Ok(results)) = items {
+                        if !results.items.iter().any(|item| item.id == new_item.id) {
+                            results.items.push(new_item);
+                        }
+                    }
+                })
+            }
+        },
+        event => items_resource.update(|items| {
+            if let Some(Ok(results)) = items {
+                event.apply_to_items(&mut results.items);
+            }
+        }),
+    });
 
     let loading = move || view! {
         Завантаження товарів...
     };
 
-    let remove_item_cb = move |item_id: &ItemId| {
-        items_resource.update(|items| {
-            // PANIC: unwraps are fine, because this action is passed to a component, that is
-            //        rendered only after items have loaded.
-            let items = items.as_mut().unwrap().as_mut().unwrap();
-            // PANIC: items are rendered from the vec, from which we're removing an item.
-            let idx = items
-                .iter()
-                .position(|item| &item.id == item_id)
-                .unwrap();
-            items.remove(idx);
-        })
+    let optimistic = Optimistic::new(items_resource);
+    let toasts = Toasts::use_toasts();
+
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let focus = ItemFocus {
+        item_id: create_rw_signal(None),
+        adding_object: create_rw_signal(None),
+    };
+    provide_context(focus);
+
+    let search_query_untracked = SearchQuery::use_query_untracked();
+
+    // Ids of the items in the order they are shown, grouped by category when none is chosen
+    let shown_ids = move || {
+        let items = untrack(items_resource).and_then(|results| results.ok()).map(|results| results.items).unwrap_or_default();
+        match search_query_untracked().category {
+            Some(_) => items.into_iter().map(|item| item.id).collect::<Vec<_>>(),
+            None => Item::group_by_category(items).into_iter()
+                .flat_map(|(_, items)| items.into_iter().map(|item| item.id))
+                .collect(),
+        }
     };
 
-    let loaded_items = move || {
-        items_resource().map(|items| {
-            match items {
-                Ok(items) => items.into_iter().map(|item| {
-                    let add_object_action = create_action(move |input: &String| {
-                        let input = input.clone();
-
-                        async move {
-                            if let Ok(new_object) = add_item_object(item.id, input).await {
-                                items_resource.update(|items| {
-                                    // PANIC: unwraps are fine, because this action is passed to a component, that is
-                                    //        rendered only after items have loaded.
-                                    items.as_mut().unwrap().as_mut().unwrap()
-                                        .iter_mut().skip_while(|search_item| search_item.id != item.id)
-                                        .next().unwrap().objects.push(new_object);
-                                })
-                            }
+    let keybindings = Keybindings::use_keybindings();
+    keybindings.register("j", "Наступний товар", move || focus.step(&shown_ids(), 1));
+    keybindings.register("k", "Попередній товар", move || focus.step(&shown_ids(), -1));
+    keybindings.register("o", "Додати предмет до вибраного товару", move || {
+        if admin_state.get_untracked().set {
+            focus.adding_object.set(focus.item_id.get_untracked());
+        }
+    });
+
+    // Changes might have already come from the live updates, so they are applied only if they are still missing
+    let item_card = move |item: Item| {
+        let item_id = item.id;
+        let shown_item = store_value(item.clone());
+
+        let remove_item_action = create_action(move |_| {
+            let removed_item = untrack(|| items_resource.with(|items| {
+                items.as_ref()
+                    .and_then(|items| items.as_ref().ok())
+                    .and_then(|results| find_removed(&results.items, |item| item.id == item_id))
+            }));
+
+            async move {
+                optimistic.remove(
+                    |results| results.items.retain(|item| item.id != item_id),
+                    move |results| restore(&mut results.items, removed_item.clone(), |item, removed| item.id == removed.id),
+                    remove_item(item_id),
+                    "Товар видалено",
+                    "Не вдалося видалити товар",
+                ).await;
+            }
+        });
+
+        let add_object_action = create_action(move |input: &(String, Option<LocationId>, Option<ItemVariantId>)| {
+            let (item_code, location_id, variant_id) = input.clone();
+            // Shown until the server gives the object its id
+            let pending_object = ItemObject { id: ItemObjectId(pending_id()), item_code: Some(item_code.clone()), location_id, variant_id };
+            let pending_object_id = pending_object.id;
+
+            async move {
+                let new_object = optimistic.run(
+                    |results| change_item(results, item_id, |item| item.objects.push(pending_object)),
+                    |results| change_item(results, item_id, |item| item.objects.retain(|object| object.id != pending_object_id)),
+                    add_item_object(item_id, item_code, location_id, variant_id),
+                    "Не вдалося додати предмет",
+                ).await;
+
+                if let Some(new_object) = new_object {
+                    optimistic.update(|results| change_item(results, item_id, |item| {
+                        item.objects.retain(|object| object.id != pending_object_id);
+                        if !item.objects.iter().any(|object| object.id == new_object.id) {
+                            item.objects.push(new_object);
                         }
-                    });
-
-                    let remove_object_cb = move |item_object_id: &ItemObjectId| {
-                        items_resource.update(|items| {
-                            // PANIC: unwraps are fine, because this action is passed to a component, that is
-                            //        rendered only after items have loaded.
-                            let item = items.as_mut().unwrap().as_mut().unwrap()
-                                .iter_mut().skip_while(|search_item| search_item.id != item.id)
-                                .next().unwrap();
-                            // PANIC: items are rendered from the vec, from which we're removing an item.
-                            let idx = item.objects
-                                .iter()
-                                .position(|item_object| &item_object.id == item_object_id)
-                                .unwrap();
-                            item.objects.remove(idx);
-                        })
-                    };
+                    }))
+                }
+            }
+        });
 
-                    let add_tag_action = create_action(move |input: &(ItemId, Tag)| {
-                        let (item_id, tag) = input.clone();
-
-                        async move {
-                            if let Ok(_) = add_item_tag(item_id, tag.id).await {
-                                items_resource.update(|items| {
-                                    // PANIC: unwraps are fine, because this action is passed to a component, that is
-                                    //        rendered only after items have loaded.
-                                    items.as_mut().unwrap().as_mut().unwrap()
-                                        .iter_mut().skip_while(|search_item| search_item.id != item.id)
-                                        .next().unwrap().tags.push(tag);
-                                })
-                            }
+        let remove_object_action = create_action(move |object_id: &ItemObjectId| {
+            let object_id = *object_id;
+            let removed_object = shown_item.with_value(|item| find_removed(&item.objects, |object| object.id == object_id));
+
+            async move {
+                optimistic.remove(
+                    |results| change_item(results, item_id, |item| item.objects.retain(|object| object.id != object_id)),
+                    move |results| change_item(results, item_id, |item| restore(&mut item.objects, removed_object.clone(), |object, removed| object.id == removed.id)),
+                    remove_item_object(object_id),
+                    "Предмет видалено",
+                    "Не вдалося видалити предмет",
+                ).await;
+            }
+        });
+
+        let transfer_object_action = create_action(move |input: &(ItemObjectId, LocationId)| {
+            let (object_id, location_id) = *input;
+            let previous_location_id = shown_item.with_value(|item| {
+                item.objects.iter().find(|object| object.id == object_id).and_then(|object| object.location_id)
+            });
+            let set_location = move |location_id: Option<LocationId>| move |results: &mut SearchResults| change_item(results, item_id, |item| {
+                if let Some(object) = item.objects.iter_mut().find(|object| object.id == object_id) {
+                    object.location_id = location_id;
+                }
+            });
+
+            async move {
+                let outcome = optimistic.run(
+                    set_location(Some(location_id)),
+                    set_location(previous_location_id),
+                    transfer_objects(vec![object_id], location_id),
+                    "Не вдалося перемістити предмет",
+                ).await;
+                // E.g. sold meanwhile at the POS, or being removed
+                if outcome.is_some_and(|outcome| outcome.skipped.contains(&object_id)) {
+                    optimistic.update(set_location(previous_location_id));
+                    toasts.show("Предмет не переміщено, його вже продано або видалено".to_string());
+                }
+            }
+        });
+
+        let add_tag_action = create_action(move |input: &(ItemId, Tag)| {
+            let (item_id, tag) = input.clone();
+            let tag_id = tag.id;
+
+            async move {
+                optimistic.run(
+                    |results| change_item(results, item_id, |item| {
+                        if !item.tags.iter().any(|item_tag| item_tag.id == tag_id) {
+                            item.tags.push(tag);
+                        }
+                    }),
+                    |results| change_item(results, item_id, |item| item.tags.retain(|item_tag| item_tag.id != tag_id)),
+                    add_item_tag(item_id, tag_id),
+                    "Не вдалося додати тег",
+                ).await;
+            }
+        });
+
+        let remove_tag_action = create_action(move |tag_id: &TagId| {
+            let tag_id = *tag_id;
+            let removed_tag = shown_item.with_value(|item| find_removed(&item.tags, |tag| tag.id == tag_id));
+
+            async move {
+                optimistic.remove(
+                    |results| change_item(results, item_id, |item| item.tags.retain(|tag| tag.id != tag_id)),
+                    move |results| change_item(results, item_id, |item| restore(&mut item.tags, removed_tag.clone(), |tag, removed| tag.id == removed.id)),
+                    remove_item_tag(item_id, tag_id),
+                    "Тег прибрано",
+                    "Не вдалося прибрати тег",
+                ).await;
+            }
+        });
+
+        // The combinations are named as the server would, and replaced with the server's variants once it makes them
+        let add_variants_action = create_action(move |axes: &Vec<VariantAxis>| {
+            let axes = axes.clone();
+            let existing_names = shown_item.with_value(|item| item.variants.iter().map(|variant| variant.name.clone()).collect::<Vec<_>>());
+            let pending_variants = VariantAxis::combinations(&axes).into_iter()
+                .filter(|combination| !existing_names.contains(&combination.name))
+                .map(|combination| ItemVariant { id: ItemVariantId(pending_id()), name: combination.name, attributes: combination.attributes })
+                .collect::<Vec<_>>();
+            let pending_ids = pending_variants.iter().map(|variant| variant.id).collect::<Vec<_>>();
+
+            async move {
+                let new_item = optimistic.run(
+                    |results| change_item(results, item_id, |item| item.variants.extend(pending_variants)),
+                    |results| change_item(results, item_id, |item| item.variants.retain(|variant| !pending_ids.contains(&variant.id))),
+                    add_item_variants(item_id, axes),
+                    "Не вдалося створити варіанти",
+                ).await;
+
+                if let Some(new_item) = new_item {
+                    optimistic.update(|results| change_item(results, item_id, |item| item.variants = new_item.variants))
+                }
+            }
+        });
+
+        let remove_variant_action = create_action(move |variant_id: &ItemVariantId| {
+            let variant_id = *variant_id;
+            let removed_variant = shown_item.with_value(|item| find_removed(&item.variants, |variant| variant.id == variant_id));
+
+            async move {
+                optimistic.remove(
+                    |results| change_item(results, item_id, |item| item.variants.retain(|variant| variant.id != variant_id)),
+                    move |results| change_item(results, item_id, |item| restore(&mut item.variants, removed_variant.clone(), |variant, removed| variant.id == removed.id)),
+                    remove_item_variant(variant_id),
+                    "Варіант видалено",
+                    "Не вдалося видалити варіант",
+                ).await;
+            }
+        });
+
+        // The values are parsed as the server would, and replaced with the server's ones once it accepts them
+        let set_attributes_action = create_action(move |input: &Vec<AttributeInput>| {
+            let input = input.clone();
+            let (category_id, previous_attributes) = shown_item.with_value(|item| (item.category.id, item.attributes.clone()));
+            let parsed_attributes = untrack(|| attributes.with(|attributes| {
+                attributes.as_ref()
+                    .and_then(|attributes| attributes.as_ref().ok())
+                    .map(|attributes| attributes.iter().filter(|attribute| attribute.category_id == category_id).cloned().collect::<Vec<_>>())
+            }))
+                .and_then(|attributes| ItemAttributes::parse(&attributes, &input).ok())
+                // Invalid values are left to the server to reject
+                .unwrap_or_else(|| previous_attributes.clone());
+
+            async move {
+                let new_attributes = optimistic.run(
+                    |results| change_item(results, item_id, |item| item.attributes = parsed_attributes),
+                    |results| change_item(results, item_id, |item| item.attributes = previous_attributes),
+                    set_item_attributes(item_id, input),
+                    "Не вдалося змінити атрибути",
+                ).await;
+
+                if let Some(new_attributes) = new_attributes {
+                    optimistic.update(|results| change_item(results, item_id, |item| item.attributes = new_attributes))
+                }
+            }
+        });
+
+        view! {
+            <ItemCard item tags attributes locations set_attributes_action remove_item_action add_object_action remove_object_action transfer_object_action add_tag_action remove_tag_action add_variants_action remove_variant_action />
+        }
+    };
+
+    let did_you_mean = move || {
+        items_resource().and_then(|results| results.ok()).and_then(|results| results.did_you_mean).map(|corrected| {
+            let search_corrected = {
+                let corrected = corrected.clone();
+                move |_| {
+                    let mut search_query = search_query();
+                    search_query.q = Some(corrected.clone());
+                    search_query.set();
+                }
+            };
+
+            view! {
+                <div class="col-span-2">
+                    "Можливо, ви мали на увазі "
+                    <button class="underline" on:click=search_corrected>{corrected}</button>
+                    "?"
+                </div>
+            }
+        })
+    };
+
+    let loaded_items = move || {
+        items_resource().map(|results| {
+            match results.map(|results| results.items) {
+                Ok(items) if search_query().category.is_some() => items.into_iter().map(item_card).collect_view(),
+                // Items of all the categories are found, when none is chosen
+                Ok(items) => Item::group_by_category(items).into_iter().map(|(category, items)| {
+                    let select_category = {
+                        let category = category.clone();
+                        move |_| {
+                            let mut search_query = search_query();
+                            search_query.category = Some(category.clone());
+                            search_query.set();
                         }
-                    });
-
-                    let remove_tag_cb = move |tag_id: &TagId| {
-                        items_resource.update(|items| {
-                            // PANIC: unwraps are fine, because this action is passed to a component, that is
-                            //        rendered only after items have loaded.
-                            let item = items.as_mut().unwrap().as_mut().unwrap()
-                                .iter_mut().skip_while(|search_item| search_item.id != item.id)
-                                .next().unwrap();
-                            // PANIC: items are rendered from the vec, from which we're removing an item.
-                            let idx = item.tags
-                                .iter()
-                                .position(|tag| &tag.id == tag_id)
-                                .unwrap();
-                            item.tags.remove(idx);
-                        })
                     };
 
                     view! {
-                        <ItemCard item tags remove_item_cb add_object_action remove_object_cb add_tag_action remove_tag_cb />
+                        <button class="col-span-2 text-left text-2xl underline" on:click=select_category>
+                            {category.name}
+                        </button>
+                        {items.into_iter().map(item_card).collect_view()}
                     }
                 }).collect_view(),
                 Err(_) => view! { Помилка завантаження продуктів }.into_view(),
@@ -401,28 +791,49 @@ pub fn Items(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>) -> impl IntoVi
         let input = input.clone();
         // Category must be chosen in AddItem component
         let category = search_query().category.unwrap();
+        let category_id = category.id;
+        // Shown until the server gives the item its id
+        let pending_item = Item {
+            id: ItemId(pending_id()),
+            name: input.clone(),
+            category,
+            tags: vec![],
+            objects: vec![],
+            attributes: ItemAttributes::default(),
+            variants: vec![],
+        };
+        let pending_item_id = pending_item.id;
+
         async move {
-            if let Ok(new_item) = add_item(input, category).await {
-                items_resource.update(|items| {
-                    // PANIC: unwraps are fine, because this action is passed to a component, that is
-                    //        rendered only after items have loaded.
-                    items.as_mut().unwrap().as_mut().unwrap().push(new_item)
+            let new_item = optimistic.run(
+                |results| results.items.push(pending_item),
+                |results| results.items.retain(|item| item.id != pending_item_id),
+                add_item(input, category_id),
+                "Не вдалося додати товар",
+            ).await;
+
+            if let Some(new_item) = new_item {
+                optimistic.update(|results| {
+                    results.items.retain(|item| item.id != pending_item_id);
+                    if !results.items.iter().any(|item| item.id == new_item.id) {
+                        results.items.push(new_item)
+                    }
                 })
             }
         }
     });
 
-    let admin_state = use_context::<ReadSignal<AdminState>>()
-        .expect("`AdminState` to be added to the context");
-
     view! {
         <Suspense
             fallback=loading
         >
             <div class="grid gap-2 grid-cols-2 w-full h-max">
+                {move || admin_state().set.then(|| view! { <BulkActions items_resource tags /> })}
+                {did_you_mean}
                 {loaded_items}
                 {
-                    move || (!items_loading() && admin_state().set).then(||
+                    // New items are added to the chosen category
+                    move || (!items_loading() && admin_state().set && search_query().category.is_some()).then(||
                         view! {
                             <AddItem add_item_action />
                         }
@@ -0,0 +1,206 @@
+use std::rc::Rc;
+
+use leptos::*;
+
+/// Opens the command palette, even while typing.
+const PALETTE_SHORTCUT: &str = "Ctrl+k";
+
+/// An action, which is run by its shortcut or from the command palette.
+#[derive(Clone)]
+struct Command {
+    id: usize,
+    /// E.g. `"j"` or `"Ctrl+k"`, the key as `KeyboardEvent::key` names it.
+    shortcut: &'static str,
+    label: &'static str,
+    run: Rc<dyn Fn()>,
+}
+
+/// Shortcut of a key press, in the form the commands are registered with.
+fn shortcut_of(ev: &ev::KeyboardEvent) -> String {
+    let mut shortcut = String::new();
+    if ev.ctrl_key() || ev.meta_key() {
+        shortcut.push_str("Ctrl+");
+    }
+    if ev.alt_key() {
+        shortcut.push_str("Alt+");
+    }
+    shortcut.push_str(&ev.key());
+    shortcut
+}
+
+/// Whether the key press types into a field, so that the plain shortcuts must not take it.
+fn is_typing(ev: &ev::KeyboardEvent) -> bool {
+    let tag = event_target::<web_sys::Element>(ev).tag_name();
+    matches!(tag.as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+}
+
+/// Handler of `keydown`, which submits a form by Enter from any of its fields.
+pub fn on_enter(submit: impl Fn() + 'static) -> impl Fn(ev::KeyboardEvent) + 'static {
+    move |ev| {
+        if ev.key() == "Enter" {
+            ev.prevent_default();
+            submit();
+        }
+    }
+}
+
+/// Global keyboard shortcuts, which the components register while they are shown,
+/// and the command palette listing them.
+#[derive(Clone, Copy)]
+pub struct Keybindings {
+    commands: RwSignal<Vec<Command>>,
+    next_id: StoredValue<usize>,
+    palette_open: RwSignal<bool>,
+}
+
+impl Keybindings {
+    pub fn provide() {
+        let keybindings = Keybindings {
+            commands: create_rw_signal(vec![]),
+            next_id: store_value(0),
+            palette_open: create_rw_signal(false),
+        };
+        provide_context(keybindings);
+
+        let listener = window_event_listener(ev::keydown, move |ev| keybindings.on_keydown(&ev));
+        on_cleanup(move || listener.remove());
+    }
+
+    pub fn use_keybindings() -> Keybindings {
+        use_context::<Keybindings>()
+            .expect("`Keybindings` to be added to the context")
+    }
+
+    /// Registers the command for as long as the calling component is shown.
+    /// Shortcuts without a modifier are ignored while typing into a field.
+    pub fn register(&self, shortcut: &'static str, label: &'static str, run: impl Fn() + 'static) {
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id + 1);
+        self.commands.update(|commands| commands.push(Command { id, shortcut, label, run: Rc::new(run) }));
+
+        let commands = self.commands;
+        on_cleanup(move || commands.update(|commands| commands.retain(|command| command.id != id)));
+    }
+
+    fn on_keydown(&self, ev: &ev::KeyboardEvent) {
+        let shortcut = shortcut_of(ev);
+
+        if shortcut == PALETTE_SHORTCUT {
+            ev.prevent_default();
+            self.palette_open.update(|open| *open = !*open);
+            return;
+        }
+        if self.palette_open.get_untracked() || (!shortcut.contains('+') && is_typing(ev)) {
+            return;
+        }
+
+        // The latest registered command wins, e.g. of the page over the one of the whole app
+        let command = self.commands.with_untracked(|commands| {
+            commands.iter().rev().find(|command| command.shortcut == shortcut).cloned()
+        });
+        if let Some(command) = command {
+            ev.prevent_default();
+            (command.run)();
+        }
+    }
+}
+
+/// Lists the registered commands, filtered by the typed text, and runs the chosen one.
+#[component]
+pub fn CommandPalette() -> impl IntoView {
+    let keybindings = Keybindings::use_keybindings();
+    let (filter, filter_set) = create_signal(String::new());
+    let (highlighted, highlighted_set) = create_signal(0);
+    let input_ref = create_node_ref::<html::Input>();
+
+    let found = move || {
+        let filter = filter().to_lowercase();
+        keybindings.commands.with(|commands| {
+            commands.iter()
+                .filter(|command| command.label.to_lowercase().contains(&filter))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let close = move || {
+        keybindings.palette_open.set(false);
+        filter_set(String::new());
+        highlighted_set(0);
+    };
+
+    let run = move |command: Command| {
+        close();
+        (command.run)();
+    };
+
+    // The palette is typed into right away
+    create_effect(move |_| {
+        if keybindings.palette_open.get() {
+            if let Some(input) = input_ref.get() {
+                let _ = input.focus();
+            }
+        }
+    });
+
+    let on_keydown = move |ev: ev::KeyboardEvent| {
+        let count = found().len();
+        match ev.key().as_str() {
+            "ArrowDown" if count > 0 => {
+                ev.prevent_default();
+                highlighted_set((highlighted() + 1) % count);
+            },
+            "ArrowUp" if count > 0 => {
+                ev.prevent_default();
+                highlighted_set((highlighted() + count - 1) % count);
+            },
+            "Enter" => {
+                if let Some(command) = found().into_iter().nth(highlighted()) {
+                    run(command);
+                }
+            },
+            "Escape" => close(),
+            _ => (),
+        }
+    };
+
+    let commands_view = move || {
+        found().into_iter().enumerate().map(|(idx, command)| {
+            let shortcut = command.shortcut;
+            let label = command.label;
+            view! {
+                <button
+                    class="flex flex-row justify-between gap-4 px-2 py-1 text-left rounded-lg"
+                    class=("bg-slate-200", move || highlighted() == idx)
+                    on:mousedown=move |_| run(command.clone())
+                >
+                    <div>{label}</div>
+                    <div class="font-mono text-sm">{shortcut}</div>
+                </button>
+            }
+        }).collect_view()
+    };
+
+    view! {
+        <Show when=move || keybindings.palette_open.get() fallback=|| ()>
+            <div class="fixed inset-0 z-40 flex items-start justify-center pt-24 bg-black/30" on:click=move |_| close()>
+                <div class="flex flex-col gap-1 w-96 p-2 bg-white rounded-xl shadow-lg" on:click=|ev| ev.stop_propagation()>
+                    <input
+                        class="rounded-lg p-1 border border-solid border-black"
+                        type="text"
+                        placeholder="Команда"
+                        node_ref=input_ref
+                        on:input=move |ev| {
+                            filter_set(event_target_value(&ev));
+                            highlighted_set(0);
+                        }
+                        on:keydown=on_keydown
+                        prop:value=filter
+                    />
+                    {commands_view}
+                    <div class="text-sm text-slate-500">{format!("{PALETTE_SHORTCUT} — відкрити або закрити")}</div>
+                </div>
+            </div>
+        </Show>
+    }
+}
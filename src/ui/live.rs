@@ -0,0 +1,61 @@
+use leptos::*;
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::data::live::{LiveEvent, LIVE_EVENTS_PATH};
+
+/// The latest change made by anyone, received while the page is open.
+#[derive(Clone, Copy)]
+pub struct LiveEvents(ReadSignal<Option<LiveEvent>>);
+
+impl LiveEvents {
+    /// Subscribes the page to the changes and provides them to the components.
+    pub fn provide() {
+        let (event, event_set) = create_signal(None::<LiveEvent>);
+        provide_context(LiveEvents(event));
+
+        // Effects run only in the browser, which has the `EventSource`
+        create_effect(move |_| {
+            let Ok(source) = web_sys::EventSource::new(LIVE_EVENTS_PATH) else {
+                return;
+            };
+
+            let on_message = Closure::<dyn Fn(web_sys::MessageEvent)>::new(move |message: web_sys::MessageEvent| {
+                let event = message.data().as_string().and_then(|data| serde_json::from_str(&data).ok());
+                if event.is_some() {
+                    event_set(event);
+                }
+            });
+            source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+            // The browser reconnects by itself, but the changes made meanwhile are lost
+            let opened = store_value(false);
+            let on_open = Closure::<dyn Fn()>::new(move || {
+                if opened.get_value() {
+                    event_set(Some(LiveEvent::Missed));
+                }
+                opened.set_value(true);
+            });
+            source.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+            on_cleanup(move || {
+                source.close();
+                drop(on_message);
+                drop(on_open);
+            });
+        });
+    }
+
+    /// Calls `handler` with every change received after the component is created.
+    pub fn on_event(handler: impl Fn(LiveEvent) + 'static) {
+        let LiveEvents(event) = use_context::<LiveEvents>()
+            .expect("`LiveEvents` to be added to the context");
+
+        create_effect(move |subscribed: Option<()>| {
+            let event = event();
+            // The first run only subscribes, the event it sees was received before the component
+            if let (Some(()), Some(event)) = (subscribed, event) {
+                untrack(|| handler(event));
+            }
+        });
+    }
+}
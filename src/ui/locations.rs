@@ -0,0 +1,217 @@
+use leptos::*;
+
+use crate::{server_funcs::locations::{add_location, remove_location, get_object_transfers}, data::{locations::{Location, LocationId}, item::{ItemObject, ItemObjectId}, deletions::PendingDeletion}, ui::{state::AdminState, toasts::Toasts, keybindings::on_enter}};
+
+use super::state::SearchQuery;
+
+#[component]
+pub fn LocationSelect<F>(locations: Vec<Location>, selected: Option<LocationId>, on_select: F) -> impl IntoView
+where
+    F: Fn(Option<LocationId>) + 'static
+{
+    view! {
+        <select
+            class="rounded-lg p-1 border-solid border-slate-400 border"
+            on:change=move |ev| on_select(event_target_value(&ev).parse().ok())
+        >
+            <option value="" selected=selected.is_none()>"Без місця"</option>
+            {
+                locations.into_iter().map(|location| view! {
+                    <option value=location.id.to_string() selected=selected == Some(location.id)>{location.name}</option>
+                }).collect_view()
+            }
+        </select>
+    }
+}
+
+/// Number of objects at every location.
+#[component]
+pub fn StockCounts(locations: Vec<Location>, objects: Vec<ItemObject>) -> impl IntoView {
+    view! {
+        <div class="flex flex-row gap-2">
+            {
+                Location::stock_counts(&locations, &objects).into_iter().map(|(location, count)| {
+                    let name = location.map(|location| location.name.clone()).unwrap_or("Без місця".to_string());
+                    view! {
+                        <div class="bg-slate-200 rounded-lg px-1">{format!("{name}: {count}")}</div>
+                    }
+                }).collect_view()
+            }
+        </div>
+    }
+}
+
+/// History of the transfers of an object, loaded when it's shown.
+#[component]
+pub fn ObjectTransfers(object_id: ItemObjectId) -> impl IntoView {
+    let transfers = create_resource(move || object_id, get_object_transfers);
+
+    let transfers_view = move || {
+        transfers().map(|transfers| {
+            match transfers {
+                Ok(transfers) if transfers.is_empty() => view! { "Переміщень не було" }.into_view(),
+                Ok(transfers) => transfers.into_iter().map(|transfer| {
+                    let from = transfer.from_location.unwrap_or("—".to_string());
+                    let to = transfer.to_location.unwrap_or("—".to_string());
+                    view! {
+                        <div class="text-sm">
+                            {format!("{}: {from} → {to}", transfer.transferred_at.format("%d.%m.%Y %H:%M"))}
+                        </div>
+                    }
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження переміщень }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <Suspense fallback=move || view! { Завантаження переміщень... }>
+            <div class="flex flex-col">
+                {transfers_view}
+            </div>
+        </Suspense>
+    }
+}
+
+#[component]
+pub fn LocationButton<F>(location: Location, remove_location_cb: F) -> impl IntoView
+where
+    F: Fn(&LocationId, PendingDeletion) + Copy + 'static
+{
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let search_query = SearchQuery::use_query();
+    let is_location_chosen = move || search_query().location == Some(location.id);
+
+    let toggle_location = move |_| {
+        let mut search_query = search_query();
+        search_query.location = (!is_location_chosen()).then_some(location.id);
+        search_query.set();
+    };
+
+    let remove_location_action = create_action(move |_| {
+        async move {
+            let pending = remove_location(location.id).await?;
+            remove_location_cb(&location.id, pending);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <button
+                class="border-solid border-2 border-slate-400 rounded-lg px-1"
+                class=("bg-blue-200", is_location_chosen)
+                on:click=toggle_location
+            >
+                {location.name}
+            </button>
+            {
+                move || admin_state().set.then(|| view! {
+                    <button
+                        on:click=move |_| {
+                            remove_location_action.dispatch(())
+                        }
+                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
+                        disabled=remove_location_action.pending()
+                    >
+                        Видалити
+                    </button>
+                })
+            }
+        </div>
+    }
+}
+
+#[component]
+pub fn AddLocation(add_location_action: Action<String, ()>) -> impl IntoView {
+    let (new_location_name, new_location_set) = create_signal(String::new());
+
+    view! {
+        <div class="flex flex-col items-center border-solid border-black border">
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                type="text"
+                required
+                on:input=move |ev| {
+                    new_location_set(event_target_value(&ev))
+                }
+                on:keydown=on_enter(move || add_location_action.dispatch(new_location_name()))
+
+                prop:value=new_location_name
+            />
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                on:click=move |_| {
+                    add_location_action.dispatch(new_location_name())
+                }
+            >
+                "Додати"
+            </button>
+        </div>
+    }
+}
+
+/// Locations, which filter the found items to the ones available there.
+#[component]
+pub fn LocationsBlock(locations: Resource<(), Result<Vec<Location>, ServerFnError>>) -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let locations_loading = locations.loading();
+
+    let toasts = Toasts::use_toasts();
+    let remove_location_cb = move |location_id: &LocationId, pending: PendingDeletion| {
+        locations.update(|locations| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after locations have loaded.
+            locations.as_mut().unwrap().as_mut().unwrap().retain(|location| &location.id != location_id);
+        });
+        toasts.show_undo("Місце видалено".to_string(), "Не вдалося видалити місце", pending.id, move || locations.refetch());
+    };
+
+    let add_location_action = create_action(move |input: &String| {
+        let input = input.clone();
+        async move {
+            if let Ok(new_location) = add_location(input).await {
+                locations.update(|locations| {
+                    // PANIC: unwraps are fine, because this action is passed to a component, that is
+                    //        rendered only after locations have loaded.
+                    locations.as_mut().unwrap().as_mut().unwrap().push(new_location)
+                })
+            }
+        }
+    });
+
+    let loaded_locations = move || {
+        locations().map(|locations| {
+            match locations {
+                Ok(locations) => locations.into_iter().map(|location| {
+                    view! {
+                        <LocationButton location remove_location_cb />
+                    }
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження місць }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <Suspense
+            fallback=move || view! { Завантаження місць... }
+        >
+            <div class="flex flex-col gap-1 p-2">
+                <div class="font-bold">"Місця зберігання"</div>
+                {loaded_locations}
+                {
+                    move || (!locations_loading() && admin_state().set).then(||
+                        view! {
+                            <AddLocation add_location_action />
+                        }
+                    )
+                }
+            </div>
+        </Suspense>
+    }
+}
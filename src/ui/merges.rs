@@ -0,0 +1,159 @@
+use leptos::*;
+
+use crate::{server_funcs::merges::{get_duplicate_tags, get_duplicate_items, merge_tags, merge_items}, data::merges::{DuplicateTags, DuplicateItems}, ui::{state::AdminState, toasts::Toasts}};
+
+#[component]
+fn DuplicateTagsRow(duplicate: DuplicateTags, merge_action: Action<(DuplicateTags, bool), ()>) -> impl IntoView {
+    let into_first = format!("Залишити «{}»", duplicate.first_name);
+    let into_second = format!("Залишити «{}»", duplicate.second_name);
+    let first = duplicate.clone();
+    let second = duplicate.clone();
+
+    view! {
+        <div class="flex flex-row items-center gap-2 p-2 bg-slate-200 rounded-xl">
+            <div class="font-bold">{duplicate.first_name}</div>
+            <div class="text-sm">{format!("товарів: {}", duplicate.first_items)}</div>
+            <div>"—"</div>
+            <div class="font-bold">{duplicate.second_name}</div>
+            <div class="text-sm">{format!("товарів: {}", duplicate.second_items)}</div>
+            <div class="text-sm">{format!("схожість {:.0}%", duplicate.similarity * 100.0)}</div>
+            <button
+                class="ml-auto bg-green-700 disabled:text-slate-400 rounded-xl px-2"
+                disabled=merge_action.pending()
+                on:click=move |_| merge_action.dispatch((first.clone(), true))
+            >
+                {into_first}
+            </button>
+            <button
+                class="bg-green-700 disabled:text-slate-400 rounded-xl px-2"
+                disabled=merge_action.pending()
+                on:click=move |_| merge_action.dispatch((second.clone(), false))
+            >
+                {into_second}
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn DuplicateItemsRow(duplicate: DuplicateItems, merge_action: Action<(DuplicateItems, bool), ()>) -> impl IntoView {
+    let into_first = format!("Залишити «{}»", duplicate.first_name);
+    let into_second = format!("Залишити «{}»", duplicate.second_name);
+    let first = duplicate.clone();
+    let second = duplicate.clone();
+
+    view! {
+        <div class="flex flex-row items-center gap-2 p-2 bg-slate-200 rounded-xl">
+            <div class="font-bold">{duplicate.first_name}</div>
+            <div class="text-sm">{format!("{}, в наявності: {}", duplicate.first_category, duplicate.first_in_stock)}</div>
+            <div>"—"</div>
+            <div class="font-bold">{duplicate.second_name}</div>
+            <div class="text-sm">{format!("{}, в наявності: {}", duplicate.second_category, duplicate.second_in_stock)}</div>
+            <div class="text-sm">{format!("схожість {:.0}%", duplicate.similarity * 100.0)}</div>
+            <button
+                class="ml-auto bg-green-700 disabled:text-slate-400 rounded-xl px-2"
+                disabled=merge_action.pending()
+                on:click=move |_| merge_action.dispatch((first.clone(), true))
+            >
+                {into_first}
+            </button>
+            <button
+                class="bg-green-700 disabled:text-slate-400 rounded-xl px-2"
+                disabled=merge_action.pending()
+                on:click=move |_| merge_action.dispatch((second.clone(), false))
+            >
+                {into_second}
+            </button>
+        </div>
+    }
+}
+
+/// Tags and items with similar names, which can be merged into one of them.
+#[component]
+pub fn DuplicatesBlock() -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+    let toasts = Toasts::use_toasts();
+
+    let duplicate_tags = create_resource(|| (), |_| get_duplicate_tags());
+    let duplicate_items = create_resource(|| (), |_| get_duplicate_items());
+
+    // A merge changes the similarity of the other pairs, so the suggestions are loaded again
+    let merge_tags_action = create_action(move |input: &(DuplicateTags, bool)| {
+        let (duplicate, keep_first) = input.clone();
+        let (source, target, kept) = match keep_first {
+            true => (duplicate.second_id, duplicate.first_id, duplicate.first_name),
+            false => (duplicate.first_id, duplicate.second_id, duplicate.second_name),
+        };
+
+        async move {
+            match merge_tags(source, target).await {
+                Ok(()) => toasts.show(format!("Теги об'єднано в «{kept}»")),
+                Err(err) => toasts.show_error(err, "Не вдалося об'єднати теги"),
+            }
+            duplicate_tags.refetch();
+        }
+    });
+
+    let merge_items_action = create_action(move |input: &(DuplicateItems, bool)| {
+        let (duplicate, keep_first) = input.clone();
+        let (source, target) = match keep_first {
+            true => (duplicate.second_id, duplicate.first_id),
+            false => (duplicate.first_id, duplicate.second_id),
+        };
+
+        async move {
+            match merge_items(source, target).await {
+                Ok(item) => toasts.show(format!("Товари об'єднано в «{}»", item.name)),
+                Err(err) => toasts.show_error(err, "Не вдалося об'єднати товари"),
+            }
+            duplicate_items.refetch();
+        }
+    });
+
+    let loaded_tags = move || {
+        duplicate_tags().map(|duplicates| {
+            match duplicates {
+                Ok(duplicates) if duplicates.is_empty() => view! { "Схожих тегів немає" }.into_view(),
+                Ok(duplicates) => duplicates.into_iter().map(|duplicate| view! {
+                    <DuplicateTagsRow duplicate merge_action=merge_tags_action />
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження тегів }.into_view(),
+            }
+        })
+    };
+
+    let loaded_items = move || {
+        duplicate_items().map(|duplicates| {
+            match duplicates {
+                Ok(duplicates) if duplicates.is_empty() => view! { "Схожих товарів немає" }.into_view(),
+                Ok(duplicates) => duplicates.into_iter().map(|duplicate| view! {
+                    <DuplicateItemsRow duplicate merge_action=merge_items_action />
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження товарів }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2">
+            {
+                move || if admin_state().set {
+                    view! {
+                        <h2 class="text-2xl">"Схожі теги"</h2>
+                        <Transition fallback=move || view! { Завантаження тегів... }>
+                            {loaded_tags}
+                        </Transition>
+                        <h2 class="text-2xl">"Схожі товари"</h2>
+                        <div class="text-sm">"Предмети, файли й теги об'єднаного товару переходять до того, що залишається"</div>
+                        <Transition fallback=move || view! { Завантаження товарів... }>
+                            {loaded_items}
+                        </Transition>
+                    }.into_view()
+                } else {
+                    view! { "Дублікати об'єднує лише адміністратор" }.into_view()
+                }
+            }
+        </div>
+    }
+}
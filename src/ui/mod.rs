@@ -4,4 +4,22 @@ pub mod categories;
 pub mod state;
 pub mod search;
 pub mod tags;
-pub mod items;
\ No newline at end of file
+pub mod items;
+pub mod scan;
+pub mod attributes;
+pub mod attachments;
+pub mod purchasing;
+pub mod locations;
+pub mod sales;
+pub mod stock_takes;
+pub mod dashboard;
+pub mod saved_searches;
+pub mod api_tokens;
+pub mod webhooks;
+pub mod live;
+pub mod toasts;
+pub mod optimistic;
+pub mod bulk;
+pub mod merges;
+pub mod variants;
+pub mod keybindings;
\ No newline at end of file
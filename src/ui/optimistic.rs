@@ -0,0 +1,137 @@
+use std::{future::Future, sync::atomic::{AtomicU64, Ordering}};
+
+use leptos::*;
+use uuid::Uuid;
+
+use crate::data::deletions::PendingDeletion;
+
+use super::toasts::Toasts;
+
+/// An id for a change, which isn't saved yet.
+///
+/// The database makes random ids of version 4, which can't be equal to these.
+pub fn pending_id() -> Uuid {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    Uuid::from_u64_pair(0, NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+/// A value, which is about to be removed, with its index, so that a failed removal can [`restore`] it.
+pub fn find_removed<V: Clone>(values: &[V], is_removed: impl Fn(&V) -> bool) -> Option<(usize, V)> {
+    values.iter().position(is_removed).map(|index| (index, values[index].clone()))
+}
+
+/// Puts a value back at its former index, after its removal has failed,
+/// unless it's back already, e.g. from the live updates.
+pub fn restore<V>(values: &mut Vec<V>, removed: Option<(usize, V)>, is_same: impl Fn(&V, &V) -> bool) {
+    if let Some((index, value)) = removed {
+        if !values.iter().any(|present| is_same(present, &value)) {
+            values.insert(index.min(values.len()), value);
+        }
+    }
+}
+
+/// Changes a loaded resource before the server confirms the change, so that the page doesn't wait for it.
+///
+/// Made in a component, because the toasts can't be taken from the context inside of an action.
+pub struct Optimistic<S: 'static, T: 'static> {
+    resource: Resource<S, Result<T, ServerFnError>>,
+    toasts: Toasts,
+}
+
+impl<S: 'static, T: 'static> Clone for Optimistic<S, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: 'static, T: 'static> Copy for Optimistic<S, T> {}
+
+impl<S: Clone + 'static, T: 'static> Optimistic<S, T> {
+    pub fn new(resource: Resource<S, Result<T, ServerFnError>>) -> Self {
+        Optimistic {
+            resource,
+            toasts: Toasts::use_toasts(),
+        }
+    }
+
+    /// Changes the loaded value, if it's loaded.
+    pub fn update(&self, change: impl FnOnce(&mut T)) {
+        self.resource.update(|value| {
+            if let Some(Ok(value)) = value {
+                change(value);
+            }
+        })
+    }
+
+    /// Applies `change` right away and sends the `request`. If it fails, `rollback` undoes the change,
+    /// and the `failure` is shown as a toast.
+    pub async fn run<R>(
+        self,
+        change: impl FnOnce(&mut T),
+        rollback: impl FnOnce(&mut T),
+        request: impl Future<Output = Result<R, ServerFnError>>,
+        failure: &str,
+    ) -> Option<R> {
+        self.update(change);
+
+        match request.await {
+            Ok(response) => Some(response),
+            Err(err) => {
+                self.update(rollback);
+                self.toasts.show_error(err, failure);
+                None
+            },
+        }
+    }
+
+    /// Hides the removed entity right away and offers to undo the removal, while the server waits with it.
+    /// `restore` shows the entity again, if the removal fails or is undone.
+    pub async fn remove(
+        self,
+        change: impl FnOnce(&mut T),
+        restore: impl Fn(&mut T) + 'static,
+        request: impl Future<Output = Result<PendingDeletion, ServerFnError>>,
+        removed: &str,
+        failure: &str,
+    ) {
+        self.update(change);
+
+        match request.await {
+            Ok(pending) => self.toasts.show_undo(removed.to_string(), failure, pending.id, move || self.update(&restore)),
+            Err(err) => {
+                self.update(&restore);
+                self.toasts.show_error(err, failure);
+            },
+        }
+    }
+
+    /// Sends the `request`, which can't be applied before the server's response, showing the `failure` as a toast.
+    pub async fn confirmed<R>(self, request: impl Future<Output = Result<R, ServerFnError>>, failure: &str) -> Option<R> {
+        match request.await {
+            Ok(response) => Some(response),
+            Err(err) => {
+                self.toasts.show_error(err, failure);
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_removal_is_restored_at_its_index() {
+        let mut values = vec![1, 2, 3];
+        let removed = find_removed(&values, |value| *value == 2);
+        values.retain(|value| *value != 2);
+
+        restore(&mut values, removed, |value, removed| value == removed);
+        assert_eq!(values, [1, 2, 3]);
+
+        // Already back from the live updates
+        restore(&mut values, removed, |value, removed| value == removed);
+        assert_eq!(values, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,555 @@
+use leptos::*;
+
+use crate::{server_funcs::{suppliers::{get_suppliers, add_supplier, remove_supplier, get_purchase_orders, add_purchase_order, remove_purchase_order, add_purchase_order_line, remove_purchase_order_line, receive_purchase_order_line}, categories::get_categories, items::search_items, locations::get_locations}, data::{suppliers::{Supplier, SupplierId, PurchaseOrder, PurchaseOrderId, PurchaseOrderLine, PurchaseOrderLineId}, item::ItemId, locations::LocationId, categories::CategoryId, deletions::PendingDeletion}, ui::{state::AdminState, locations::LocationSelect, toasts::Toasts, keybindings::on_enter}};
+
+type SuppliersResource = Resource<(), Result<Vec<Supplier>, ServerFnError>>;
+type OrdersResource = Resource<(), Result<Vec<PurchaseOrder>, ServerFnError>>;
+
+/// Message of a failed server call, for showing to the user.
+fn error_message(err: ServerFnError, fallback: &str) -> String {
+    match err {
+        ServerFnError::ServerError(message) => message,
+        _ => fallback.to_string(),
+    }
+}
+
+#[component]
+pub fn AddSupplier(add_supplier_action: Action<(String, Option<String>), ()>) -> impl IntoView {
+    let (new_supplier_name, new_supplier_set) = create_signal(String::new());
+    let (new_contact, new_contact_set) = create_signal(String::new());
+
+    let submit = move || {
+        let contact = Some(new_contact()).filter(|contact| !contact.trim().is_empty());
+        add_supplier_action.dispatch((new_supplier_name(), contact))
+    };
+
+    view! {
+        <div class="flex flex-col items-center border-solid border-black border">
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                type="text"
+                placeholder="Назва"
+                on:input=move |ev| {
+                    new_supplier_set(event_target_value(&ev))
+                }
+                on:keydown=on_enter(submit)
+
+                prop:value=new_supplier_name
+            />
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                type="text"
+                placeholder="Контакт"
+                on:input=move |ev| {
+                    new_contact_set(event_target_value(&ev))
+                }
+                on:keydown=on_enter(submit)
+
+                prop:value=new_contact
+            />
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                on:click=move |_| submit()
+            >
+                "Додати"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+pub fn SupplierRow<F>(supplier: Supplier, remove_supplier_cb: F) -> impl IntoView
+where
+    F: Fn(&SupplierId, PendingDeletion) + Copy + 'static
+{
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let remove_supplier_action = create_action(move |_| {
+        async move {
+            let pending = remove_supplier(supplier.id).await?;
+            remove_supplier_cb(&supplier.id, pending);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    view! {
+        <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+            <div class="font-bold">{supplier.name}</div>
+            {supplier.contact.map(|contact| view! { <div class="text-sm">{contact}</div> })}
+            {
+                move || admin_state().set.then(|| view! {
+                    <button
+                        on:click=move |_| {
+                            remove_supplier_action.dispatch(())
+                        }
+                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
+                        disabled=remove_supplier_action.pending()
+                    >
+                        Видалити
+                    </button>
+                })
+            }
+        </div>
+    }
+}
+
+#[component]
+pub fn SuppliersBlock(suppliers: SuppliersResource) -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let suppliers_loading = suppliers.loading();
+
+    let toasts = Toasts::use_toasts();
+    let remove_supplier_cb = move |supplier_id: &SupplierId, pending: PendingDeletion| {
+        suppliers.update(|suppliers| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after suppliers have loaded.
+            suppliers.as_mut().unwrap().as_mut().unwrap().retain(|supplier| &supplier.id != supplier_id);
+        });
+        toasts.show_undo("Постачальника видалено".to_string(), "Не вдалося видалити постачальника", pending.id, move || suppliers.refetch());
+    };
+
+    let add_supplier_action = create_action(move |input: &(String, Option<String>)| {
+        let (name, contact) = input.clone();
+        async move {
+            if let Ok(new_supplier) = add_supplier(name, contact).await {
+                suppliers.update(|suppliers| {
+                    // PANIC: unwraps are fine, because this action is passed to a component, that is
+                    //        rendered only after suppliers have loaded.
+                    suppliers.as_mut().unwrap().as_mut().unwrap().push(new_supplier)
+                })
+            }
+        }
+    });
+
+    let loaded_suppliers = move || {
+        suppliers().map(|suppliers| {
+            match suppliers {
+                Ok(suppliers) => suppliers.into_iter().map(|supplier| {
+                    view! {
+                        <SupplierRow supplier remove_supplier_cb />
+                    }
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження постачальників }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <Suspense
+            fallback=move || view! { Завантаження постачальників... }
+        >
+            <div class="flex flex-col gap-1 p-2">
+                <h2 class="text-2xl">"Постачальники"</h2>
+                {loaded_suppliers}
+                {
+                    move || (!suppliers_loading() && admin_state().set).then(||
+                        view! {
+                            <AddSupplier add_supplier_action />
+                        }
+                    )
+                }
+            </div>
+        </Suspense>
+    }
+}
+
+/// Picks an item by choosing its category first.
+#[component]
+pub fn ItemPicker(item_id_set: WriteSignal<Option<ItemId>>) -> impl IntoView {
+    let categories = create_resource(|| (), |_| get_categories());
+    let (category, category_set) = create_signal(None::<CategoryId>);
+
+    let items = create_resource(
+        category,
+        |category| async move {
+            match category {
+                Some(category) => search_items(None, vec![], Some(category), false, vec![], None, None).await.map(|results| results.items),
+                None => Ok(vec![]),
+            }
+        }
+    );
+
+    let category_options = move || {
+        categories().and_then(|categories| categories.ok()).map(|categories| {
+            categories.into_iter().map(|category| view! {
+                <option value=category.id.0.to_string()>{category.name}</option>
+            }).collect_view()
+        })
+    };
+
+    let item_options = move || {
+        items().and_then(|items| items.ok()).map(|items| {
+            items.into_iter().map(|item| view! {
+                <option value=item.id.0.to_string()>{item.name}</option>
+            }).collect_view()
+        })
+    };
+
+    view! {
+        <Transition fallback=|| ()>
+            <select
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                on:change=move |ev| {
+                    category_set(event_target_value(&ev).parse().ok());
+                    item_id_set(None);
+                }
+            >
+                <option value="">"Категорія"</option>
+                {category_options}
+            </select>
+            <select
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                on:change=move |ev| {
+                    item_id_set(event_target_value(&ev).parse().ok())
+                }
+            >
+                <option value="">"Товар"</option>
+                {item_options}
+            </select>
+        </Transition>
+    }
+}
+
+#[component]
+pub fn AddPurchaseOrderLine(add_line_action: Action<(ItemId, i32), ()>) -> impl IntoView {
+    let (item_id, item_id_set) = create_signal(None::<ItemId>);
+    let (quantity, quantity_set) = create_signal(1);
+
+    let submit = move || {
+        if let Some(item_id) = item_id().filter(|_| quantity() >= 1) {
+            add_line_action.dispatch((item_id, quantity()))
+        }
+    };
+
+    view! {
+        <div class="flex flex-row items-center gap-1">
+            <ItemPicker item_id_set />
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border w-20"
+                type="number"
+                min="1"
+                on:input=move |ev| {
+                    quantity_set(event_target_value(&ev).parse().unwrap_or(0))
+                }
+                on:keydown=on_enter(submit)
+
+                prop:value=quantity
+            />
+            <button
+                class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                on:click=move |_| submit()
+                disabled=move || item_id().is_none() || quantity() < 1
+            >
+                "Додати"
+            </button>
+        </div>
+    }
+}
+
+/// Codes of received objects are entered one per line, e.g. by scanning them one after another.
+#[component]
+pub fn ReceiveLine<F>(line: PurchaseOrderLine, update_line_cb: F) -> impl IntoView
+where
+    F: Fn(PurchaseOrderLine) + Copy + 'static
+{
+    let (codes, codes_set) = create_signal(String::new());
+    let (location_id, location_id_set) = create_signal(None::<LocationId>);
+    let locations = create_resource(|| (), |_| get_locations());
+
+    let receive_action = create_action(move |input: &Vec<String>| {
+        let input = input.clone();
+        let location_id = location_id();
+        async move {
+            let (updated_line, _) = receive_purchase_order_line(line.id, input, location_id).await?;
+            update_line_cb(updated_line);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let entered_codes = move || {
+        codes().lines()
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    let receive_error = move || {
+        receive_action.value()().and_then(|result| result.err()).map(|err| view! {
+            <div class="text-red-700">{error_message(err, "Помилка отримання")}</div>
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <textarea
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                placeholder="Коди отриманих об'єктів, по одному в рядку"
+                on:input=move |ev| {
+                    codes_set(event_target_value(&ev))
+                }
+
+                prop:value=codes
+            />
+            <Transition fallback=|| ()>
+                {
+                    move || locations().and_then(|locations| locations.ok()).map(|locations| view! {
+                        <LocationSelect locations selected=location_id.get_untracked() on_select=location_id_set />
+                    })
+                }
+            </Transition>
+            <button
+                class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                on:click=move |_| {
+                    receive_action.dispatch(entered_codes());
+                    codes_set(String::new());
+                }
+                disabled=move || receive_action.pending()() || entered_codes().is_empty()
+            >
+                {move || format!("Отримати ({})", entered_codes().len())}
+            </button>
+            {receive_error}
+        </div>
+    }
+}
+
+#[component]
+pub fn PurchaseOrderLineRow<F, G>(line: PurchaseOrderLine, remove_line_cb: F, update_line_cb: G) -> impl IntoView
+where
+    F: Fn(&PurchaseOrderLineId, PendingDeletion) + Copy + 'static,
+    G: Fn(PurchaseOrderLine) + Copy + 'static,
+{
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let remove_line_action = create_action(move |_| {
+        async move {
+            let pending = remove_purchase_order_line(line.id).await?;
+            remove_line_cb(&line.id, pending);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let line = store_value(line);
+
+    view! {
+        <div class="flex flex-row items-start gap-2 p-1 border-b border-solid border-slate-400">
+            <div class="w-1/3">{line().item_name}</div>
+            <div class="w-1/6">{format!("{} / {}", line().received_quantity, line().quantity)}</div>
+            <div class="w-1/3">
+                {(line().remaining() > 0).then(|| view! { <ReceiveLine line=line() update_line_cb /> })}
+            </div>
+            {
+                move || admin_state().set.then(|| view! {
+                    <button
+                        on:click=move |_| {
+                            remove_line_action.dispatch(())
+                        }
+                        class="bg-red-700 disabled:text-slate-400 rounded-xl px-2"
+                        disabled=remove_line_action.pending()
+                    >
+                        Видалити
+                    </button>
+                })
+            }
+        </div>
+    }
+}
+
+#[component]
+pub fn PurchaseOrderCard<F>(order: PurchaseOrder, orders: OrdersResource, remove_order_cb: F) -> impl IntoView
+where
+    F: Fn(&PurchaseOrderId, PendingDeletion) + Copy + 'static
+{
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let order_id = order.id;
+    let is_received = order.is_received();
+
+    let update_order = move |f: &dyn Fn(&mut PurchaseOrder)| {
+        orders.update(|orders| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after orders have loaded.
+            let orders = orders.as_mut().unwrap().as_mut().unwrap();
+            // PANIC: the order is rendered from the vec, in which we're searching for it.
+            f(orders.iter_mut().find(|order| order.id == order_id).unwrap());
+        })
+    };
+
+    let toasts = Toasts::use_toasts();
+    let remove_line_cb = move |line_id: &PurchaseOrderLineId, pending: PendingDeletion| {
+        update_order(&|order| order.lines.retain(|line| &line.id != line_id));
+        toasts.show_undo("Рядок замовлення видалено".to_string(), "Не вдалося видалити рядок замовлення", pending.id, move || orders.refetch());
+    };
+
+    let update_line_cb = move |updated_line: PurchaseOrderLine| {
+        update_order(&|order| {
+            if let Some(line) = order.lines.iter_mut().find(|line| line.id == updated_line.id) {
+                *line = updated_line.clone();
+            }
+        })
+    };
+
+    let add_line_action = create_action(move |input: &(ItemId, i32)| {
+        let (item_id, quantity) = *input;
+        async move {
+            if let Ok(new_line) = add_purchase_order_line(order_id, item_id, quantity).await {
+                update_order(&|order| order.lines.push(new_line.clone()))
+            }
+        }
+    });
+
+    let remove_order_action = create_action(move |_| {
+        async move {
+            let pending = remove_purchase_order(order_id).await?;
+            remove_order_cb(&order_id, pending);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let lines_view = order.lines.into_iter().map(|line| view! {
+        <PurchaseOrderLineRow line remove_line_cb update_line_cb />
+    }).collect_view();
+
+    view! {
+        <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+            <div class="flex flex-row items-center gap-2">
+                <div class="font-bold">{order.supplier.name}</div>
+                <div class="text-sm">{order.created_at.format("%d.%m.%Y %H:%M").to_string()}</div>
+                <div class="ml-auto">{if is_received { "Отримано" } else { "Очікується" }}</div>
+            </div>
+            {lines_view}
+            {
+                move || admin_state().set.then(|| view! {
+                    <AddPurchaseOrderLine add_line_action />
+                    <button
+                        on:click=move |_| {
+                            remove_order_action.dispatch(())
+                        }
+                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
+                        disabled=remove_order_action.pending()
+                    >
+                        Видалити замовлення
+                    </button>
+                })
+            }
+        </div>
+    }
+}
+
+#[component]
+pub fn AddPurchaseOrder(suppliers: SuppliersResource, add_order_action: Action<SupplierId, ()>) -> impl IntoView {
+    let (supplier_id, supplier_id_set) = create_signal(None::<SupplierId>);
+
+    let supplier_options = move || {
+        suppliers().and_then(|suppliers| suppliers.ok()).map(|suppliers| {
+            suppliers.into_iter().map(|supplier| view! {
+                <option value=supplier.id.0.to_string()>{supplier.name}</option>
+            }).collect_view()
+        })
+    };
+
+    view! {
+        <div class="flex flex-row items-center gap-1">
+            <select
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                on:change=move |ev| {
+                    supplier_id_set(event_target_value(&ev).parse().ok())
+                }
+            >
+                <option value="">"Постачальник"</option>
+                {supplier_options}
+            </select>
+            <button
+                class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                on:click=move |_| {
+                    if let Some(supplier_id) = supplier_id() {
+                        add_order_action.dispatch(supplier_id)
+                    }
+                }
+                disabled=move || supplier_id().is_none()
+            >
+                "Нове замовлення"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+pub fn PurchaseOrdersBlock(suppliers: SuppliersResource) -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let orders = create_resource(|| (), |_| get_purchase_orders());
+    let orders_loading = orders.loading();
+
+    let toasts = Toasts::use_toasts();
+    let remove_order_cb = move |order_id: &PurchaseOrderId, pending: PendingDeletion| {
+        orders.update(|orders| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after orders have loaded.
+            orders.as_mut().unwrap().as_mut().unwrap().retain(|order| &order.id != order_id);
+        });
+        toasts.show_undo("Замовлення видалено".to_string(), "Не вдалося видалити замовлення", pending.id, move || orders.refetch());
+    };
+
+    let add_order_action = create_action(move |input: &SupplierId| {
+        let input = *input;
+        async move {
+            if let Ok(new_order) = add_purchase_order(input).await {
+                orders.update(|orders| {
+                    // PANIC: unwraps are fine, because this action is passed to a component, that is
+                    //        rendered only after orders have loaded.
+                    orders.as_mut().unwrap().as_mut().unwrap().insert(0, new_order)
+                })
+            }
+        }
+    });
+
+    let loaded_orders = move || {
+        orders().map(|loaded| {
+            match loaded {
+                Ok(loaded) => loaded.into_iter().map(|order| {
+                    view! {
+                        <PurchaseOrderCard order orders remove_order_cb />
+                    }
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження замовлень }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <Suspense
+            fallback=move || view! { Завантаження замовлень... }
+        >
+            <div class="flex flex-col gap-2 p-2 w-full">
+                <h2 class="text-2xl">"Замовлення"</h2>
+                {
+                    move || (!orders_loading() && admin_state().set).then(||
+                        view! {
+                            <AddPurchaseOrder suppliers add_order_action />
+                        }
+                    )
+                }
+                {loaded_orders}
+            </div>
+        </Suspense>
+    }
+}
+
+#[component]
+pub fn PurchasingBlock() -> impl IntoView {
+    let suppliers = create_resource(|| (), |_| get_suppliers());
+
+    view! {
+        <div class="flex flex-row">
+            <SuppliersBlock suppliers />
+            <PurchaseOrdersBlock suppliers />
+        </div>
+    }
+}
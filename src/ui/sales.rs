@@ -0,0 +1,306 @@
+use leptos::*;
+use leptos_router::{use_navigate, use_params_map, NavigateOptions, A};
+
+use crate::{server_funcs::sales::{find_sale_object, get_sale_objects, add_sale, get_sale, get_daily_sales_summary}, data::{sales::{SaleObject, CartLine, format_price, parse_price}, item::{ItemId, ItemObjectId}}};
+
+use super::{purchasing::ItemPicker, scan::ScanInput};
+
+/// An object in the cart, with the price as it's being entered.
+#[derive(Clone)]
+struct CartEntry {
+    object: SaleObject,
+    price: String,
+}
+
+#[component]
+fn CartRow(entry: CartEntry, cart_set: WriteSignal<Vec<CartEntry>>) -> impl IntoView {
+    let object_id = entry.object.object_id;
+
+    let update_price = move |ev| {
+        let price = event_target_value(&ev);
+        cart_set.update(|cart| {
+            if let Some(entry) = cart.iter_mut().find(|entry| entry.object.object_id == object_id) {
+                entry.price = price;
+            }
+        })
+    };
+
+    let price_valid = {
+        let price = entry.price.clone();
+        move || parse_price(&price).is_some()
+    };
+
+    view! {
+        <div class="flex flex-row items-center gap-2 p-1 border-b border-solid border-slate-400">
+            <div class="w-1/3">{entry.object.item_name}</div>
+            <div class="w-1/4">{entry.object.item_code.unwrap_or("Код відсутній".into())}</div>
+            <input
+                class="rounded-lg p-1 border-solid border w-24"
+                class=("border-slate-400", price_valid.clone())
+                class=("border-red-700", move || !price_valid())
+                type="text"
+                inputmode="decimal"
+                on:change=update_price
+                prop:value=entry.price
+            />
+            "грн"
+            <button
+                class="bg-red-700 rounded-xl px-2"
+                on:click=move |_| cart_set.update(|cart| cart.retain(|entry| entry.object.object_id != object_id))
+            >
+                "Прибрати"
+            </button>
+        </div>
+    }
+}
+
+/// Adds an object of an item, picked by its category and name, to the cart.
+#[component]
+fn PickObject<F>(add_to_cart: F) -> impl IntoView
+where
+    F: Fn(SaleObject) + Copy + 'static
+{
+    let (item_id, item_id_set) = create_signal(None::<ItemId>);
+    let (object_id, object_id_set) = create_signal(None::<ItemObjectId>);
+
+    let objects = create_resource(
+        item_id,
+        |item_id| async move {
+            match item_id {
+                Some(item_id) => get_sale_objects(item_id).await,
+                None => Ok(vec![]),
+            }
+        }
+    );
+
+    let object_options = move || {
+        objects().and_then(|objects| objects.ok()).map(|objects| {
+            objects.into_iter().map(|object| view! {
+                <option value=object.object_id.0.to_string()>
+                    {object.item_code.unwrap_or("Код відсутній".into())}
+                </option>
+            }).collect_view()
+        })
+    };
+
+    let add_picked = move |_| {
+        let picked = objects().and_then(|objects| objects.ok()).and_then(|objects| {
+            objects.into_iter().find(|object| Some(object.object_id) == object_id())
+        });
+        if let Some(picked) = picked {
+            add_to_cart(picked);
+        }
+    };
+
+    view! {
+        <div class="flex flex-row items-center gap-1">
+            <ItemPicker item_id_set />
+            <Transition fallback=|| ()>
+                <select
+                    class="rounded-lg p-1 border-solid border-slate-400 border"
+                    on:change=move |ev| {
+                        object_id_set(event_target_value(&ev).parse().ok().map(ItemObjectId))
+                    }
+                >
+                    <option value="">"Об'єкт"</option>
+                    {object_options}
+                </select>
+            </Transition>
+            <button
+                class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                on:click=add_picked
+                disabled=move || object_id().is_none()
+            >
+                "Додати"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+pub fn DailySummary() -> impl IntoView {
+    let (day, day_set) = create_signal(None);
+    let summary = create_resource(day, get_daily_sales_summary);
+
+    let summary_view = move || {
+        summary().map(|summary| {
+            match summary {
+                Ok(summary) => view! {
+                    <div>{format!("Продажів: {}", summary.sales_count)}</div>
+                    <div>{format!("Продано об'єктів: {}", summary.objects_sold)}</div>
+                    <div>{format!("Виручка: {} грн", format_price(summary.total))}</div>
+                }.into_view(),
+                Err(_) => view! { Помилка завантаження підсумків }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+            <div class="flex flex-row items-center gap-2">
+                <div class="font-bold">"Підсумки за день"</div>
+                <input
+                    type="date"
+                    on:change=move |ev| day_set(event_target_value(&ev).parse().ok())
+                />
+            </div>
+            <Transition fallback=move || view! { Завантаження підсумків... }>
+                {summary_view}
+            </Transition>
+        </div>
+    }
+}
+
+#[component]
+pub fn PosBlock() -> impl IntoView {
+    let (cart, cart_set) = create_signal(Vec::<CartEntry>::new());
+
+    let add_to_cart = move |object: SaleObject| {
+        cart_set.update(|cart| {
+            // The same object can be scanned twice by accident
+            if !cart.iter().any(|entry| entry.object.object_id == object.object_id) {
+                let price = object.last_price.map(format_price).unwrap_or_default();
+                cart.push(CartEntry { object, price });
+            }
+        })
+    };
+
+    let find_action = create_action(move |input: &String| {
+        let input = input.clone();
+        async move {
+            let object = find_sale_object(input).await?;
+            add_to_cart(object);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let sale_action = create_action(move |input: &Vec<CartLine>| {
+        let input = input.clone();
+        async move {
+            add_sale(input).await
+        }
+    });
+
+    // Navigation happens in an effect, since router context isn't available inside the action future
+    create_effect(move |_| {
+        if let Some(Ok(sale)) = sale_action.value()() {
+            cart_set(vec![]);
+            use_navigate()(&format!("/sales/{}", sale.id), NavigateOptions::default());
+        }
+    });
+
+    let cart_lines = move || {
+        cart().into_iter()
+            .map(|entry| Some(CartLine { object_id: entry.object.object_id, price: parse_price(&entry.price)? }))
+            .collect::<Option<Vec<_>>>()
+    };
+
+    let total = move || cart_lines().map(|lines| format_price(lines.iter().map(|line| line.price).sum()));
+
+    let error = move || {
+        let find_error = find_action.value()().and_then(|result| result.err());
+        let sale_error = sale_action.value()().and_then(|result| result.err());
+        find_error.or(sale_error).map(|err| {
+            let message = match err {
+                ServerFnError::ServerError(message) => message,
+                _ => "Помилка каси".to_string(),
+            };
+            view! {
+                <div class="text-red-700">{message}</div>
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-row gap-4 p-2">
+            <div class="flex flex-col gap-2 w-2/3">
+                <h2 class="text-2xl">"Каса"</h2>
+                <div class="flex flex-row items-center gap-2">
+                    <ScanInput button="Додати" pending=find_action.pending() on_scan=move |scanned| find_action.dispatch(scanned) />
+                </div>
+                <PickObject add_to_cart />
+                {error}
+                <div class="flex flex-col">
+                    {
+                        move || cart().into_iter().map(|entry| view! {
+                            <CartRow entry cart_set />
+                        }).collect_view()
+                    }
+                </div>
+                <div class="flex flex-row items-center gap-2">
+                    <div class="text-xl">
+                        {move || total().map(|total| format!("Разом: {total} грн")).unwrap_or("Некоректна ціна".to_string())}
+                    </div>
+                    <button
+                        class="bg-green-700 rounded-xl px-2 disabled:text-slate-400 ml-auto"
+                        on:click=move |_| {
+                            if let Some(lines) = cart_lines() {
+                                sale_action.dispatch(lines)
+                            }
+                        }
+                        disabled=move || sale_action.pending()() || cart().is_empty() || cart_lines().is_none()
+                    >
+                        "Підтвердити продаж"
+                    </button>
+                </div>
+            </div>
+            <div class="w-1/3">
+                <DailySummary />
+            </div>
+        </div>
+    }
+}
+
+/// Receipt of a sale, with its id taken from the route.
+#[component]
+pub fn Receipt() -> impl IntoView {
+    let params = use_params_map();
+    let sale = create_resource(
+        move || params.with(|params| params.get("id").and_then(|id| id.parse().ok())),
+        |sale_id| async move {
+            match sale_id {
+                Some(sale_id) => get_sale(sale_id).await.map(Some),
+                None => Ok(None),
+            }
+        }
+    );
+
+    let receipt_view = move || {
+        sale().map(|sale| {
+            match sale {
+                Ok(Some(sale)) => {
+                    let total = format_price(sale.total());
+                    view! {
+                        <div class="text-center font-bold">"Чек"</div>
+                        <div class="text-center text-sm">{sale.created_at.format("%d.%m.%Y %H:%M").to_string()}</div>
+                        {
+                            sale.lines.into_iter().map(|line| view! {
+                                <div class="flex flex-row gap-2">
+                                    <div>{line.item_name}</div>
+                                    <div class="text-sm">{line.item_code.unwrap_or_default()}</div>
+                                    <div class="ml-auto">{format_price(line.price)}</div>
+                                </div>
+                            }).collect_view()
+                        }
+                        <div class="flex flex-row border-t border-solid border-black font-bold">
+                            <div>"Разом"</div>
+                            <div class="ml-auto">{format!("{total} грн")}</div>
+                        </div>
+                    }.into_view()
+                },
+                Ok(None) | Err(_) => view! { Чек не знайдено }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="mx-auto w-96 flex flex-col gap-1 p-4 my-4 bg-white shadow-lg">
+            <Suspense fallback=move || view! { Завантаження чеку... }>
+                {receipt_view}
+            </Suspense>
+        </div>
+        <div class="mx-auto max-w-max">
+            <A href="/pos" class="underline">"До каси"</A>
+        </div>
+    }
+}
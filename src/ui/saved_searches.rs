@@ -0,0 +1,160 @@
+use leptos::*;
+use leptos_router::A;
+
+use crate::{server_funcs::saved_searches::{get_saved_searches, add_saved_search, remove_saved_search, set_default_saved_search}, data::{saved_searches::{SavedSearch, SavedSearchId}, deletions::PendingDeletion}, ui::toasts::Toasts};
+
+use super::state::SearchQuery;
+
+#[component]
+pub fn SavedSearchRow<F, D>(saved_search: SavedSearch, remove_saved_search_cb: F, set_default_cb: D) -> impl IntoView
+where
+    F: Fn(&SavedSearchId, PendingDeletion) + Copy + 'static,
+    D: Fn(Option<SavedSearchId>) + Copy + 'static,
+{
+    let saved_search_id = saved_search.id;
+    let is_default = saved_search.is_default;
+
+    let remove_saved_search_action = create_action(move |_| {
+        async move {
+            let pending = remove_saved_search(saved_search_id).await?;
+            remove_saved_search_cb(&saved_search_id, pending);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let set_default_action = create_action(move |_| {
+        // Choosing the default search again unsets it
+        let new_default = (!is_default).then_some(saved_search_id);
+        async move {
+            set_default_saved_search(new_default).await?;
+            set_default_cb(new_default);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    view! {
+        <div class="flex flex-row items-center gap-1">
+            <A href=saved_search.href class="underline">{saved_search.name}</A>
+            <button
+                class="ml-auto"
+                class=("text-yellow-500", is_default)
+                class=("text-slate-400", !is_default)
+                title="Відкривати за замовчуванням"
+                on:click=move |_| set_default_action.dispatch(())
+                disabled=set_default_action.pending()
+            >
+                "★"
+            </button>
+            <button
+                on:click=move |_| {
+                    remove_saved_search_action.dispatch(())
+                }
+                class="bg-red-700 disabled:text-slate-400 rounded-xl px-1"
+                disabled=remove_saved_search_action.pending()
+            >
+                Видалити
+            </button>
+        </div>
+    }
+}
+
+/// Saves the current search under a name.
+#[component]
+pub fn AddSavedSearch(add_saved_search_action: Action<String, ()>) -> impl IntoView {
+    let (new_search_name, new_search_set) = create_signal(String::new());
+
+    view! {
+        <div class="flex flex-col items-center border-solid border-black border">
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                type="text"
+                placeholder="Назва пошуку"
+                required
+                on:input=move |ev| {
+                    new_search_set(event_target_value(&ev))
+                }
+
+                prop:value=new_search_name
+            />
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                on:click=move |_| {
+                    add_saved_search_action.dispatch(new_search_name())
+                }
+            >
+                "Зберегти пошук"
+            </button>
+        </div>
+    }
+}
+
+/// Searches saved by the user, one of which can be opened instead of the bare home page.
+#[component]
+pub fn SavedSearchesBlock() -> impl IntoView {
+    let search_query = SearchQuery::use_query();
+    let saved_searches = create_resource(|| (), |_| get_saved_searches());
+
+    let toasts = Toasts::use_toasts();
+    let remove_saved_search_cb = move |saved_search_id: &SavedSearchId, pending: PendingDeletion| {
+        saved_searches.update(|saved_searches| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after saved searches have loaded.
+            saved_searches.as_mut().unwrap().as_mut().unwrap().retain(|saved_search| &saved_search.id != saved_search_id);
+        });
+        toasts.show_undo("Пошук видалено".to_string(), "Не вдалося видалити пошук", pending.id, move || saved_searches.refetch());
+    };
+
+    let set_default_cb = move |default_id: Option<SavedSearchId>| {
+        saved_searches.update(|saved_searches| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after saved searches have loaded.
+            for saved_search in saved_searches.as_mut().unwrap().as_mut().unwrap() {
+                saved_search.is_default = Some(saved_search.id) == default_id;
+            }
+        })
+    };
+
+    let add_saved_search_action = create_action(move |input: &String| {
+        let name = input.clone();
+        let href = search_query().href();
+        async move {
+            if let Ok(new_saved_search) = add_saved_search(name, href).await {
+                saved_searches.update(|saved_searches| {
+                    // PANIC: unwraps are fine, because this action is passed to a component, that is
+                    //        rendered only after saved searches have loaded.
+                    let saved_searches = saved_searches.as_mut().unwrap().as_mut().unwrap();
+                    // A search with the same name is replaced
+                    saved_searches.retain(|saved_search| saved_search.id != new_saved_search.id);
+                    saved_searches.push(new_saved_search);
+                })
+            }
+        }
+    });
+
+    let loaded_saved_searches = move || {
+        saved_searches().map(|saved_searches| {
+            match saved_searches {
+                Ok(saved_searches) => view! {
+                    {
+                        saved_searches.into_iter().map(|saved_search| view! {
+                            <SavedSearchRow saved_search remove_saved_search_cb set_default_cb />
+                        }).collect_view()
+                    }
+                    <AddSavedSearch add_saved_search_action />
+                }.into_view(),
+                Err(_) => view! { Помилка завантаження збережених пошуків }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <Suspense
+            fallback=move || view! { Завантаження збережених пошуків... }
+        >
+            <div class="flex flex-col gap-1 p-2">
+                <div class="font-bold">"Збережені пошуки"</div>
+                {loaded_saved_searches}
+            </div>
+        </Suspense>
+    }
+}
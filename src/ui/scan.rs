@@ -0,0 +1,99 @@
+use leptos::*;
+
+use crate::server_funcs::items::find_by_item_code;
+
+use super::state::SearchQuery;
+
+#[component]
+pub fn ScanBlock() -> impl IntoView {
+    let find_action = create_action(move |input: &String| {
+        let input = input.clone();
+        async move {
+            find_by_item_code(input).await
+        }
+    });
+
+    // Navigation happens in an effect, since router context isn't available inside the action future
+    create_effect(move |_| {
+        if let Some(Ok(lookup)) = find_action.value()() {
+            SearchQuery {
+                q: Some(lookup.item.name),
+                category: Some(lookup.item.category),
+                subcategories: false,
+                filter_tags: vec![],
+                attributes: vec![],
+                highlight: Some(lookup.object_id),
+                location: None,
+                tag: None,
+            }.set();
+        }
+    });
+
+    let not_found = move || {
+        find_action.value()().and_then(|result| result.err()).map(|err| {
+            let message = match err {
+                ServerFnError::ServerError(message) => message,
+                _ => "Помилка пошуку за кодом".to_string(),
+            };
+            view! {
+                <div class="text-red-700">{message}</div>
+            }
+        })
+    };
+
+    view! {
+        <div class="mx-auto max-w-max flex flex-row items-center gap-2 my-2">
+            <ScanInput button="Знайти" pending=find_action.pending() on_scan=move |scanned| find_action.dispatch(scanned) />
+            {not_found}
+        </div>
+    }
+}
+
+/// A field for codes, which keyboard-wedge scanners type and finish with Enter. Codes can be typed by hand too.
+#[component]
+pub fn ScanInput<F>(
+    /// Label of the button, which submits a code typed by hand.
+    button: &'static str,
+    /// Disables the button, while the last code is handled.
+    pending: ReadSignal<bool>,
+    on_scan: F,
+) -> impl IntoView
+where
+    F: Fn(String) + Copy + 'static
+{
+    let (code, code_set) = create_signal(String::new());
+
+    let submit = move || {
+        let scanned = code().trim().to_string();
+        if !scanned.is_empty() {
+            on_scan(scanned);
+        }
+        // Clear the field, so the next scan starts from scratch
+        code_set(String::new());
+    };
+
+    view! {
+        <input
+            class="rounded-lg p-1 border border-solid border-black"
+            type="text"
+            placeholder="Сканувати код"
+            on:input=move |ev| {
+                code_set(event_target_value(&ev))
+            }
+            on:keydown=move |ev| {
+                if ev.key() == "Enter" {
+                    submit()
+                }
+            }
+
+            prop:value=code
+        />
+        <button
+            class="bg-slate-400 rounded-xl px-2 disabled:text-slate-200"
+            on:click=move |_| submit()
+            disabled=pending
+        >
+            {button}
+        </button>
+    }
+}
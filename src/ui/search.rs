@@ -1,6 +1,36 @@
-use leptos::*;
+use std::time::Duration;
 
-use super::state::SearchQuery;
+use leptos::{*, leptos_dom::helpers::TimeoutHandle};
+
+use crate::{server_funcs::items::suggest, data::item::Suggestion};
+
+use super::{state::SearchQuery, keybindings::Keybindings};
+
+/// Delay after the last keystroke, before the suggestions are requested.
+const SUGGEST_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Shorter prefixes match too much to be useful.
+const SUGGEST_MIN_LENGTH: usize = 2;
+const SUGGEST_LIMIT: i64 = 10;
+
+#[component]
+fn SuggestionRow(suggestion: Suggestion) -> impl IntoView {
+    match suggestion {
+        Suggestion::Item { item_name, category, .. } => view! {
+            <div>{item_name}</div>
+            <div class="text-sm ml-auto">{category.name}</div>
+        }.into_view(),
+        Suggestion::Tag(tag) => view! {
+            <div class="border-solid border-2 rounded-lg px-1" style=format!("border-color: {}", tag.color)>
+                {tag.name}
+            </div>
+            <div class="text-sm ml-auto">"Тег"</div>
+        }.into_view(),
+        Suggestion::Object { item_code, item_name, .. } => view! {
+            <div>{item_code}</div>
+            <div class="text-sm ml-auto">{item_name}</div>
+        }.into_view(),
+    }
+}
 
 #[component]
 pub fn SearchBlock() -> impl IntoView {
@@ -8,29 +38,151 @@ pub fn SearchBlock() -> impl IntoView {
     let search_query_untracked = SearchQuery::use_query_untracked();
     let (search, search_set) = create_signal(search_query_untracked().q.unwrap_or_default());
 
-    let update_search_query = move |_| {
+    let input_ref = create_node_ref::<html::Input>();
+    Keybindings::use_keybindings().register("/", "Перейти до пошуку", move || {
+        if let Some(input) = input_ref.get_untracked() {
+            let _ = input.focus();
+        }
+    });
+
+    // The query can also be changed elsewhere, e.g. by a scan or a "did you mean" hint
+    create_effect(move |_| search_set(search_query().q.unwrap_or_default()));
+
+    let (prefix, prefix_set) = create_signal(String::new());
+    let debounce = store_value(None::<TimeoutHandle>);
+    let (open, open_set) = create_signal(false);
+    let (highlighted, highlighted_set) = create_signal(None::<usize>);
+
+    let suggestions = create_resource(
+        prefix,
+        |prefix| async move {
+            if prefix.chars().count() < SUGGEST_MIN_LENGTH {
+                return Ok(vec![]);
+            }
+            suggest(prefix, SUGGEST_LIMIT).await
+        }
+    );
+    let loaded_suggestions = move || suggestions().and_then(|suggestions| suggestions.ok()).unwrap_or_default();
+
+    let update_search_query = move || {
         let mut search_query = search_query();
         search_query.q = Some(search());
         search_query.set();
+        open_set(false);
+    };
+
+    let on_input = move |ev| {
+        let value = event_target_value(&ev);
+        search_set(value.clone());
+        open_set(true);
+        highlighted_set(None);
+
+        // Only the last keystroke in a quick succession requests the suggestions
+        if let Some(handle) = debounce.get_value() {
+            handle.clear();
+        }
+        let handle = set_timeout_with_handle(move || prefix_set(value.trim().to_string()), SUGGEST_DEBOUNCE).ok();
+        debounce.set_value(handle);
+    };
+
+    let choose = move |suggestion: Suggestion| {
+        open_set(false);
+        highlighted_set(None);
+        match suggestion {
+            Suggestion::Item { item_name, category, .. } => {
+                SearchQuery {
+                    q: Some(item_name),
+                    category: Some(category),
+                    ..Default::default()
+                }.set();
+            },
+            Suggestion::Tag(tag) => {
+                let mut search_query = search_query();
+                search_query.tag = Some(tag.id);
+                search_query.filter_tags.retain(|filtered| filtered != &tag.id);
+                search_query.set();
+            },
+            Suggestion::Object { object_id, item_name, category, .. } => {
+                SearchQuery {
+                    q: Some(item_name),
+                    category: Some(category),
+                    highlight: Some(object_id),
+                    ..Default::default()
+                }.set();
+            },
+        }
+    };
+
+    let on_keydown = move |ev: ev::KeyboardEvent| {
+        let count = loaded_suggestions().len();
+        match ev.key().as_str() {
+            "ArrowDown" if open() && count > 0 => {
+                ev.prevent_default();
+                highlighted_set(Some(highlighted().map_or(0, |idx| (idx + 1) % count)));
+            },
+            "ArrowUp" if open() && count > 0 => {
+                ev.prevent_default();
+                highlighted_set(Some(highlighted().map_or(count - 1, |idx| (idx + count - 1) % count)));
+            },
+            "Enter" => match highlighted().filter(|_| open()).and_then(|idx| loaded_suggestions().into_iter().nth(idx)) {
+                Some(suggestion) => choose(suggestion),
+                None => update_search_query(),
+            },
+            "Escape" => open_set(false),
+            _ => (),
+        }
+    };
+
+    let dropdown = move || {
+        let suggestions = loaded_suggestions();
+        (open() && !suggestions.is_empty()).then(|| view! {
+            <div class="absolute z-10 w-full flex flex-col bg-white shadow-lg rounded-lg">
+                {
+                    suggestions.into_iter().enumerate().map(|(idx, suggestion)| {
+                        let chosen = suggestion.clone();
+                        view! {
+                            <div
+                                class="flex flex-row items-center gap-2 p-1 cursor-pointer"
+                                class=("bg-blue-200", move || highlighted() == Some(idx))
+                                on:mouseenter=move |_| highlighted_set(Some(idx))
+                                // Chosen on mousedown, before the input loses focus and closes the dropdown
+                                on:mousedown=move |ev| {
+                                    ev.prevent_default();
+                                    choose(chosen.clone())
+                                }
+                            >
+                                <SuggestionRow suggestion />
+                            </div>
+                        }
+                    }).collect_view()
+                }
+            </div>
+        })
     };
 
     view! {
         <div class="mx-auto max-w-max">
-            <input
-                class="rounded-lg p-1 border border-solid border-black"
-                type="text"
-                on:input=move |ev| {
-                    search_set(event_target_value(&ev))
-                }
+            <div class="relative inline-block">
+                <input
+                    class="rounded-lg p-1 border border-solid border-black"
+                    type="text"
+                    node_ref=input_ref
+                    on:input=on_input
+                    on:keydown=on_keydown
+                    on:blur=move |_| open_set(false)
 
-                prop:value=search
-            />
+                    prop:value=search
+                />
+                <Transition fallback=|| ()>
+                    {dropdown}
+                </Transition>
+            </div>
             <button
                 class="bg-slate-400 rounded-xl px-2"
-                on:click=update_search_query
+                on:click=move |_| update_search_query()
             >
                 "Пошук"
             </button>
         </div>
     }
-}
\ No newline at end of file
+}
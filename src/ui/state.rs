@@ -1,37 +1,129 @@
-use leptos::{Memo, create_memo, SignalGetUntracked, SignalGet};
+use leptos::{Memo, SignalGetUntracked, use_context};
 use leptos_router::{NavigateOptions, State};
+use wasm_bindgen::JsValue;
 use serde::{Serialize, Deserialize};
 
+use crate::data::{item::{ItemObjectId, TagId}, attributes::AttributeFilter, locations::LocationId, categories::Category};
+
 #[derive(Clone, Default)]
 pub struct AdminState {
     pub set: bool
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct SearchQuery {
     pub q: Option<String>,
-    pub category: Option<String>,
+    /// Taken from the `/c/:slug` path, rather than the querystring.
+    #[serde(skip)]
+    pub category: Option<Category>,
+    /// Whether items of the sub-categories of `category` are shown too.
+    #[serde(default, skip_serializing_if="std::ops::Not::not")]
+    pub subcategories: bool,
+    #[serde(default, skip_serializing_if="Vec::is_empty")]
+    pub filter_tags: Vec<TagId>,
     #[serde(default, skip_serializing_if="Vec::is_empty")]
-    pub filter_tags: Vec<String>
+    pub attributes: Vec<AttributeFilter>,
+    /// Object to highlight among the found items, set after a scan lookup.
+    pub highlight: Option<ItemObjectId>,
+    /// Only items with objects at this location are shown.
+    pub location: Option<LocationId>,
+    /// Only items with this tag are shown.
+    pub tag: Option<TagId>,
 }
 
+/// Category, which the slug in the path resolves to, provided by the home page.
+#[derive(Clone, Copy)]
+pub struct ChosenCategory(pub Memo<Option<Category>>);
+
 impl SearchQuery {
     pub fn use_query() -> impl Fn() -> SearchQuery + Copy {
         let location = leptos_router::use_location();
-        move || serde_qs::from_str(&(location.search)()).expect("Any querystring to be parsable")
+        let chosen_category = use_context::<ChosenCategory>();
+        move || {
+            let mut search_query = SearchQuery::parse(&(location.search)());
+            search_query.category = chosen_category.and_then(|chosen_category| chosen_category.0());
+            search_query
+        }
     }
 
     pub fn use_query_untracked() -> impl Fn() -> SearchQuery + Copy {
         let location = leptos_router::use_location();
-        move || serde_qs::from_str(&(location.search).get_untracked()).expect("Any querystring to be parsable")
+        let chosen_category = use_context::<ChosenCategory>();
+        move || {
+            let mut search_query = SearchQuery::parse(&(location.search).get_untracked());
+            search_query.category = chosen_category.and_then(|chosen_category| chosen_category.0.get_untracked());
+            search_query
+        }
+    }
+
+    /// Parses the querystring, dropping the params which don't parse, e.g. of old bookmarks or edited by hand.
+    pub fn parse(querystring: &str) -> SearchQuery {
+        if let Ok(search_query) = serde_qs::from_str(querystring) {
+            return search_query;
+        }
+
+        let mut parsable = String::new();
+        for param in querystring.split('&') {
+            let with_param = match parsable.is_empty() {
+                true => param.to_string(),
+                false => format!("{parsable}&{param}"),
+            };
+            if serde_qs::from_str::<SearchQuery>(&with_param).is_ok() {
+                parsable = with_param;
+            }
+        }
+        serde_qs::from_str(&parsable).unwrap_or_default()
+    }
+
+    /// Link to the home page with this query, the chosen category is a part of the path.
+    pub fn href(&self) -> String {
+        let path = match &self.category {
+            Some(category) => format!("/c/{}", category.slug),
+            None => "/".to_string(),
+        };
+        path + "?" + &serde_qs::to_string(self).expect("SearchQuery to be serializable")
     }
 
+    /// Navigates to this query, marking the navigation as made on the home page, see [`SearchQuery::is_set_on_home_page`].
     pub fn set(&self) {
-        let path = leptos_router::use_location().pathname.get_untracked();
         let navigate = leptos_router::use_navigate();
         navigate(
-            &(path + "?" + &serde_qs::to_string(self).expect("SearchQuery to be serializable")),
-            NavigateOptions { resolve: true, replace: true, scroll: true, state: State(None) }
+            &self.href(),
+            NavigateOptions { resolve: true, replace: true, scroll: true, state: State(Some(JsValue::TRUE)) }
         );
     }
-}
\ No newline at end of file
+
+    /// Whether the current query was set on the home page, rather than opened by a link or the address.
+    pub fn is_set_on_home_page(state: &State) -> bool {
+        state.0.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchQuery;
+
+    #[test]
+    fn parse_drops_unparsable_params() {
+        let search_query = SearchQuery::parse("q=%D0%BA%D0%BE%D0%BC&filter_tags[0]=old-name&highlight=not-a-uuid&subcategories=true");
+        assert_eq!(search_query.q.as_deref(), Some("ком"));
+        assert!(search_query.filter_tags.is_empty());
+        assert!(search_query.highlight.is_none());
+        assert!(search_query.subcategories);
+    }
+
+    #[test]
+    fn parse_keeps_valid_query() {
+        let id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let search_query = SearchQuery::parse(&format!("filter_tags[0]={id}&highlight={id}"));
+        assert_eq!(search_query.filter_tags.len(), 1);
+        assert!(search_query.highlight.is_some());
+    }
+
+    #[test]
+    fn parse_never_panics() {
+        for querystring in ["", "&&", "=", "attributes[0][id]=x", "q[0]=a", "%zz"] {
+            SearchQuery::parse(querystring);
+        }
+    }
+}
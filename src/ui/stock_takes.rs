@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use leptos::*;
+use leptos_router::{use_navigate, use_params_map, NavigateOptions, A};
+
+use crate::{server_funcs::{stock_takes::{get_stock_takes, get_stock_take, add_stock_take, add_stock_take_scan, get_stock_take_report, approve_stock_take}, categories::get_categories, locations::get_locations}, data::{stock_takes::{StockTake, StockTakeId, StockTakeCorrection, MissingObject, UnexpectedScan}, categories::CategoryId, locations::LocationId, item::{ItemId, ItemObjectId}}, ui::{state::AdminState, locations::LocationSelect, purchasing::ItemPicker, scan::ScanInput}};
+
+#[component]
+fn StockTakeRow(stock_take: StockTake) -> impl IntoView {
+    view! {
+        <A href=format!("/stock-takes/{}", stock_take.id) class="flex flex-row gap-2 p-2 bg-slate-200 rounded-xl">
+            <div class="font-bold">{stock_take.scope()}</div>
+            <div class="text-sm">{stock_take.created_at.format("%d.%m.%Y %H:%M").to_string()}</div>
+            <div class="ml-auto">
+                {if stock_take.approved_at.is_some() { "Затверджено" } else { "Триває" }}
+            </div>
+        </A>
+    }
+}
+
+#[component]
+fn AddStockTake(add_stock_take_action: Action<(Option<CategoryId>, Option<LocationId>), Result<StockTake, ServerFnError>>) -> impl IntoView {
+    let categories = create_resource(|| (), |_| get_categories());
+    let locations = create_resource(|| (), |_| get_locations());
+    let (category_id, category_id_set) = create_signal(None::<CategoryId>);
+    let (location_id, location_id_set) = create_signal(None::<LocationId>);
+
+    let category_options = move || {
+        categories().and_then(|categories| categories.ok()).map(|categories| {
+            categories.into_iter().map(|category| view! {
+                <option value=category.id.0.to_string()>{category.name}</option>
+            }).collect_view()
+        })
+    };
+
+    view! {
+        <div class="flex flex-row items-center gap-1">
+            <Transition fallback=|| ()>
+                <select
+                    class="rounded-lg p-1 border-solid border-slate-400 border"
+                    on:change=move |ev| {
+                        category_id_set(event_target_value(&ev).parse().ok())
+                    }
+                >
+                    <option value="">"Усі категорії"</option>
+                    {category_options}
+                </select>
+                {
+                    move || locations().and_then(|locations| locations.ok()).map(|locations| view! {
+                        <LocationSelect locations selected=location_id.get_untracked() on_select=location_id_set />
+                    })
+                }
+            </Transition>
+            <button
+                class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                on:click=move |_| {
+                    add_stock_take_action.dispatch((category_id(), location_id()))
+                }
+                // A stock-take of everything isn't supported, the scope must be narrowed down
+                disabled=move || category_id().is_none() && location_id().is_none()
+            >
+                "Почати облік"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+pub fn StockTakesBlock() -> impl IntoView {
+    let stock_takes = create_resource(|| (), |_| get_stock_takes());
+
+    let add_stock_take_action = create_action(move |input: &(Option<CategoryId>, Option<LocationId>)| {
+        let (category_id, location_id) = *input;
+        async move {
+            add_stock_take(category_id, location_id).await
+        }
+    });
+
+    // Navigation happens in an effect, since router context isn't available inside the action future
+    create_effect(move |_| {
+        if let Some(Ok(stock_take)) = add_stock_take_action.value()() {
+            use_navigate()(&format!("/stock-takes/{}", stock_take.id), NavigateOptions::default());
+        }
+    });
+
+    let loaded_stock_takes = move || {
+        stock_takes().map(|stock_takes| {
+            match stock_takes {
+                Ok(stock_takes) => stock_takes.into_iter().map(|stock_take| view! {
+                    <StockTakeRow stock_take />
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження обліків }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2">
+            <h2 class="text-2xl">"Облік залишків"</h2>
+            <AddStockTake add_stock_take_action />
+            <Suspense fallback=move || view! { Завантаження обліків... }>
+                {loaded_stock_takes}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn MissingRow(object: MissingObject, approved: bool, kept: RwSignal<Vec<ItemObjectId>>) -> impl IntoView {
+    let object_id = object.object_id;
+    let is_removed = move || !kept().contains(&object_id);
+
+    let toggle_removed = move |ev| {
+        let removed = event_target_checked(&ev);
+        kept.update(|kept| {
+            if removed {
+                kept.retain(|kept_id| kept_id != &object_id);
+            } else {
+                kept.push(object_id);
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-row items-center gap-2">
+            <div>{object.item_name}</div>
+            <div class="text-sm">{object.item_code.unwrap_or("Код відсутній".into())}</div>
+            {
+                (!approved).then(|| view! {
+                    <label class="ml-auto flex flex-row items-center gap-1">
+                        <input type="checkbox" on:change=toggle_removed prop:checked=is_removed />
+                        "Видалити"
+                    </label>
+                })
+            }
+        </div>
+    }
+}
+
+/// An unexpected code, which can be added to an item as a new object.
+#[component]
+fn UnexpectedRow(scan: UnexpectedScan, approved: bool, additions: RwSignal<HashMap<String, ItemId>>) -> impl IntoView {
+    let (item_id, item_id_set) = create_signal(None::<ItemId>);
+
+    let item_code = scan.item_code.clone();
+    create_effect(move |_| {
+        let picked = item_id();
+        additions.update(|additions| match picked {
+            Some(item_id) => { additions.insert(item_code.clone(), item_id); },
+            None => { additions.remove(&item_code); },
+        })
+    });
+
+    view! {
+        <div class="flex flex-row items-center gap-2">
+            <div>{scan.item_code}</div>
+            {
+                match scan.item_name {
+                    // Known objects are counted elsewhere, and shouldn't be added twice
+                    Some(item_name) => view! { <div class="text-sm">{format!("Належить до «{item_name}»")}</div> }.into_view(),
+                    None if !approved => view! {
+                        <div class="ml-auto flex flex-row items-center gap-1">
+                            "Додати до:"
+                            <ItemPicker item_id_set />
+                        </div>
+                    }.into_view(),
+                    None => ().into_view(),
+                }
+            }
+        </div>
+    }
+}
+
+/// A stock-take with its id taken from the route: scanning, the discrepancy report and the approval.
+#[component]
+pub fn StockTakeSession() -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let params = use_params_map();
+    let stock_take_id = move || params.with(|params| params.get("id").and_then(|id| id.parse::<StockTakeId>().ok()));
+
+    let stock_take = create_resource(
+        stock_take_id,
+        |stock_take_id| async move {
+            match stock_take_id {
+                Some(stock_take_id) => get_stock_take(stock_take_id).await.map(Some),
+                None => Ok(None),
+            }
+        }
+    );
+    let report = create_resource(
+        stock_take_id,
+        |stock_take_id| async move {
+            match stock_take_id {
+                Some(stock_take_id) => get_stock_take_report(stock_take_id).await.map(Some),
+                None => Ok(None),
+            }
+        }
+    );
+
+    let kept = create_rw_signal(Vec::<ItemObjectId>::new());
+    let additions = create_rw_signal(HashMap::<String, ItemId>::new());
+
+    let update_stock_take = move |updated: StockTake| {
+        stock_take.set(Ok(Some(updated)));
+        report.refetch();
+    };
+
+    let scan_action = create_action(move |input: &(StockTakeId, String)| {
+        let (stock_take_id, item_code) = input.clone();
+        async move {
+            update_stock_take(add_stock_take_scan(stock_take_id, item_code).await?);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let approve_action = create_action(move |input: &(StockTakeId, Vec<StockTakeCorrection>)| {
+        let (stock_take_id, corrections) = input.clone();
+        async move {
+            update_stock_take(approve_stock_take(stock_take_id, corrections).await?);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let scan = move |scanned: String| {
+        if let Some(stock_take_id) = stock_take_id() {
+            scan_action.dispatch((stock_take_id, scanned));
+        }
+    };
+
+    let approve = move |_| {
+        let Some(stock_take_id) = stock_take_id() else { return };
+        let Some(Ok(Some(report))) = report() else { return };
+
+        let removals = report.missing.into_iter()
+            .filter(|object| !kept().contains(&object.object_id))
+            .map(|object| StockTakeCorrection::RemoveObject(object.object_id));
+        let additions = additions().into_iter()
+            .map(|(item_code, item_id)| StockTakeCorrection::AddObject { item_id, item_code });
+
+        approve_action.dispatch((stock_take_id, removals.chain(additions).collect()));
+    };
+
+    let error = move || {
+        let scan_error = scan_action.value()().and_then(|result| result.err());
+        let approve_error = approve_action.value()().and_then(|result| result.err());
+        scan_error.or(approve_error).map(|err| {
+            let message = match err {
+                ServerFnError::ServerError(message) => message,
+                _ => "Помилка обліку".to_string(),
+            };
+            view! {
+                <div class="text-red-700">{message}</div>
+            }
+        })
+    };
+
+    let is_approved = move || {
+        stock_take().and_then(|stock_take| stock_take.ok()).flatten()
+            .map(|stock_take| stock_take.approved_at.is_some())
+            .unwrap_or(true)
+    };
+
+    let header = move || {
+        stock_take().map(|stock_take| {
+            match stock_take {
+                Ok(Some(stock_take)) => view! {
+                    <h2 class="text-2xl">{format!("Облік: {}", stock_take.scope())}</h2>
+                    <div>{format!("Відскановано: {}", stock_take.scans_count)}</div>
+                    {
+                        stock_take.approved_at.map(|approved_at| view! {
+                            <div>{format!("Затверджено {}", approved_at.format("%d.%m.%Y %H:%M"))}</div>
+                        })
+                    }
+                }.into_view(),
+                Ok(None) | Err(_) => view! { Облік не знайдено }.into_view(),
+            }
+        })
+    };
+
+    let report_view = move || {
+        let approved = is_approved();
+        report().map(|report| {
+            match report {
+                Ok(Some(report)) => view! {
+                    <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                        <div class="font-bold">{format!("Не знайдено: {}", report.missing.len())}</div>
+                        {
+                            report.missing.into_iter().map(|object| view! {
+                                <MissingRow object approved kept />
+                            }).collect_view()
+                        }
+                    </div>
+                    <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                        <div class="font-bold">{format!("Неочікувані: {}", report.unexpected.len())}</div>
+                        {
+                            report.unexpected.into_iter().map(|scan| view! {
+                                <UnexpectedRow scan approved additions />
+                            }).collect_view()
+                        }
+                    </div>
+                    <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+                        <div class="font-bold">{format!("Скановано кілька разів: {}", report.duplicates.len())}</div>
+                        {
+                            report.duplicates.into_iter().map(|duplicate| view! {
+                                <div>{format!("{} × {}", duplicate.item_code, duplicate.count)}</div>
+                            }).collect_view()
+                        }
+                    </div>
+                }.into_view(),
+                Ok(None) | Err(_) => view! { Помилка завантаження звіту }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2">
+            <Suspense fallback=move || view! { Завантаження обліку... }>
+                {header}
+                {
+                    move || (!is_approved()).then(|| view! {
+                        <div class="flex flex-row items-center gap-2">
+                            <ScanInput button="Додати" pending=scan_action.pending() on_scan=scan />
+                        </div>
+                    })
+                }
+                {error}
+            </Suspense>
+            <Transition fallback=move || view! { Завантаження звіту... }>
+                {report_view}
+            </Transition>
+            {
+                move || (admin_state().set && !is_approved()).then(|| view! {
+                    <button
+                        class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                        on:click=approve
+                        disabled=approve_action.pending()
+                    >
+                        "Затвердити та виправити залишки"
+                    </button>
+                })
+            }
+            <A href="/stock-takes" class="underline">"До списку обліків"</A>
+        </div>
+    }
+}
@@ -1,67 +1,119 @@
 use leptos::*;
 
-use crate::{server_funcs::items::{add_tag, remove_tag}, data::item::{Tag, TagId}, ui::state::AdminState};
+use crate::{server_funcs::items::{add_tag, remove_tag, update_tag, add_tag_group, remove_tag_group}, data::{item::{Tag, TagId, TagGroup, TagGroupId, GroupedTags}, live::LiveEvent, deletions::PendingDeletion}, ui::{state::AdminState, toasts::Toasts, optimistic::Optimistic, keybindings::on_enter}};
 
 use super::state::SearchQuery;
 
+/// Default colour for new tags, the same as the database default.
+const DEFAULT_TAG_COLOR: &str = "#94a3b8";
+
+#[component]
+pub fn TagGroupSelect<F>(groups: Vec<TagGroup>, selected: Option<TagGroupId>, on_select: F) -> impl IntoView
+where
+    F: Fn(Option<TagGroupId>) + 'static
+{
+    view! {
+        <select
+            class="rounded-lg p-1 border-solid border-slate-400 border"
+            on:change=move |ev| on_select(event_target_value(&ev).parse().ok())
+        >
+            <option value="" selected=selected.is_none()>"Без групи"</option>
+            {
+                groups.into_iter().map(|group| view! {
+                    <option value=group.id.to_string() selected=selected == Some(group.id)>{group.name}</option>
+                }).collect_view()
+            }
+        </select>
+    }
+}
+
 #[component]
-pub fn TagToggle<F>(tag: Tag, remove_tag_cb: F) -> impl IntoView
+pub fn TagToggle<F>(tag: Tag, groups: Vec<TagGroup>, remove_tag_cb: F, update_tag_action: Action<Tag, ()>) -> impl IntoView
 where
-    F: Fn(&TagId) + Copy + 'static
+    F: Fn(&TagId, PendingDeletion) + Copy + 'static
 {
     let admin_state = use_context::<ReadSignal<AdminState>>()
         .expect("`AdminState` to be added to the context");
 
+    let toasts = Toasts::use_toasts();
     let remove_tag_action = create_action(move |_| {
         async move {
-            remove_tag(tag.id).await?;
-            remove_tag_cb(&tag.id);
-            Ok::<_, ServerFnError>(())
+            match remove_tag(tag.id).await {
+                Ok(pending) => remove_tag_cb(&tag.id, pending),
+                Err(err) => toasts.show_error(err, "Не вдалося видалити тег"),
+            }
         }
     });
 
     let search_params = SearchQuery::use_query();
 
-    let tag_active = {
-        let tag_name = tag.name.clone();
-        move || !search_params().filter_tags.contains(&tag_name)
-    };
+    let tag_id = tag.id;
+    let tag_active = move || !search_params().filter_tags.contains(&tag_id);
 
-    let dot_color = {
-        let tag_active = tag_active.clone();
-        move || if tag_active() { "green" } else { "red" }
-    };
+    let dot_color = move || if tag_active() { "green" } else { "red" };
 
-    let toggle_tag = {
-        let tag_name = tag.name.clone();
-        move |_| {
-            let mut search_params = search_params();
-            if tag_active() {
-                search_params.filter_tags.push(tag_name.clone());
-            } else {
-                // PANIC: tag must exist
-                let idx = search_params.filter_tags.iter().position(|tag_param| tag_param == &tag_name)
-                    .unwrap();
-                search_params.filter_tags.remove(idx);
-            }
-            search_params.set();
+    let toggle_tag = move |_| {
+        let mut search_params = search_params();
+        if tag_active() {
+            search_params.filter_tags.push(tag_id);
+        } else {
+            // PANIC: tag must exist
+            let idx = search_params.filter_tags.iter().position(|tag_param| tag_param == &tag_id)
+                .unwrap();
+            search_params.filter_tags.remove(idx);
         }
+        search_params.set();
+    };
+
+    let is_tag_required = move || search_params().tag == Some(tag_id);
+    let toggle_required = move |_| {
+        let mut search_params = search_params();
+        search_params.tag = (!is_tag_required()).then_some(tag_id);
+        search_params.filter_tags.retain(|filtered| filtered != &tag_id);
+        search_params.set();
     };
 
+    let tag = store_value(tag);
+    let groups = store_value(groups);
+
     view! {
         <div class="flex flex-col gap-1">
             <div
-                class="border-solid border-slate-400 border-2 rounded-lg flex flex-row items-center"
+                class="border-solid border-2 rounded-lg flex flex-row items-center"
+                style=format!("border-color: {}", tag().color)
                 on:click=toggle_tag
             >
                 <svg viewBox="0 0 120 120" version="1.1" xmlns="http://www.w3.org/2000/svg" width="10" height="10">
                     <circle cx="60" cy="60" r="50" fill={dot_color}/>
                 </svg>
-                {tag.name}
+                {tag().name}
             </div>
+            <button
+                class="border-solid border-2 border-slate-400 rounded-lg px-1 text-sm"
+                class=("bg-blue-200", is_tag_required)
+                on:click=toggle_required
+            >
+                "Лише з цим тегом"
+            </button>
 
             {
                 move || admin_state().set.then(|| view! {
+                    <div class="flex flex-row gap-1">
+                        <input
+                            type="color"
+                            prop:value=tag().color
+                            on:change=move |ev| {
+                                update_tag_action.dispatch(Tag { color: event_target_value(&ev), ..tag() })
+                            }
+                        />
+                        <TagGroupSelect
+                            groups=groups()
+                            selected=tag().group_id
+                            on_select=move |group_id| {
+                                update_tag_action.dispatch(Tag { group_id, ..tag() })
+                            }
+                        />
+                    </div>
                     <button
                         on:click=move |_| {
                             remove_tag_action.dispatch(())
@@ -78,8 +130,12 @@ where
 }
 
 #[component]
-pub fn AddTag(add_item_action: Action<String, ()>) -> impl IntoView {
+pub fn AddTag(groups: Vec<TagGroup>, add_item_action: Action<(String, Option<TagGroupId>, String), ()>) -> impl IntoView {
     let (new_tag_name, new_tag_set) = create_signal(String::new());
+    let (new_tag_group, new_tag_group_set) = create_signal(None::<TagGroupId>);
+    let (new_tag_color, new_tag_color_set) = create_signal(DEFAULT_TAG_COLOR.to_string());
+
+    let submit = move || add_item_action.dispatch((new_tag_name(), new_tag_group(), new_tag_color()));
 
     view! {
         <div class="flex flex-col items-center border-solid border-black border">
@@ -90,49 +146,191 @@ pub fn AddTag(add_item_action: Action<String, ()>) -> impl IntoView {
                 on:input=move |ev| {
                     new_tag_set(event_target_value(&ev))
                 }
+                on:keydown=on_enter(submit)
 
                 prop:value=new_tag_name
             />
+            <div class="flex flex-row gap-1">
+                <input
+                    type="color"
+                    on:input=move |ev| {
+                        new_tag_color_set(event_target_value(&ev))
+                    }
+
+                    prop:value=new_tag_color
+                />
+                <TagGroupSelect groups selected=None on_select=new_tag_group_set />
+            </div>
+            <button
+                class="bg-green-700 rounded-xl px-2"
+                on:click=move |_| submit()
+            >
+                "Додати"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+pub fn AddTagGroup(add_group_action: Action<String, ()>) -> impl IntoView {
+    let (new_group_name, new_group_set) = create_signal(String::new());
+
+    view! {
+        <div class="flex flex-col items-center border-solid border-black border">
+            <input
+                class="rounded-lg p-1 border-solid border-slate-400 border"
+                type="text"
+                required
+                on:input=move |ev| {
+                    new_group_set(event_target_value(&ev))
+                }
+                on:keydown=on_enter(move || add_group_action.dispatch(new_group_name()))
+
+                prop:value=new_group_name
+            />
             <button
                 class="bg-green-700 rounded-xl px-2"
                 on:click=move |_| {
-                    add_item_action.dispatch(new_tag_name())
+                    add_group_action.dispatch(new_group_name())
                 }
             >
-                "Додати"
+                "Додати групу"
             </button>
         </div>
     }
 }
 
 #[component]
-pub fn TagsBlock(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>) -> impl IntoView {
+pub fn TagGroupBlock<F, G>(
+    grouped: GroupedTags,
+    groups: Vec<TagGroup>,
+    remove_tag_cb: F,
+    remove_group_cb: G,
+    update_tag_action: Action<Tag, ()>,
+) -> impl IntoView
+where
+    F: Fn(&TagId, PendingDeletion) + Copy + 'static,
+    G: Fn(&TagGroupId, PendingDeletion) + Copy + 'static,
+{
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let (collapsed, collapsed_set) = create_signal(false);
+
+    let tags_view = grouped.tags.into_iter().map(|tag| {
+        view! {
+            <TagToggle tag groups=groups.clone() remove_tag_cb update_tag_action />
+        }
+    }).collect_view();
+
+    // Ungrouped tags are shown without a header, so they can't be collapsed
+    let Some(group) = grouped.group else {
+        return tags_view;
+    };
+
+    let toasts = Toasts::use_toasts();
+    let remove_group_action = create_action(move |_| {
+        async move {
+            match remove_tag_group(group.id).await {
+                Ok(pending) => remove_group_cb(&group.id, pending),
+                Err(err) => toasts.show_error(err, "Не вдалося видалити групу"),
+            }
+        }
+    });
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <button
+                class="text-left font-bold"
+                on:click=move |_| collapsed_set.update(|collapsed| *collapsed = !*collapsed)
+            >
+                {move || if collapsed() { "▸ " } else { "▾ " }}
+                {group.name}
+            </button>
+            <div class="flex flex-col gap-1 pl-2" class:hidden=collapsed>
+                {tags_view}
+            </div>
+            {
+                move || admin_state().set.then(|| view! {
+                    <button
+                        on:click=move |_| {
+                            remove_group_action.dispatch(())
+                        }
+                        class="bg-red-700 disabled:text-slate-400 rounded-xl"
+                        disabled=remove_group_action.pending()
+                    >
+                        Видалити групу
+                    </button>
+                })
+            }
+        </div>
+    }.into_view()
+}
+
+#[component]
+pub fn TagsBlock(tags: Resource<(), Result<Vec<GroupedTags>, ServerFnError>>) -> impl IntoView {
     let tags_loading = tags.loading();
 
     let loading = move || view! {
         Завантаження тегів...
     };
 
-    let remove_tag_cb = move |tag_id: &TagId| {
+    let groups = move || {
+        tags().and_then(|tags| tags.ok()).map(|tags| {
+            tags.into_iter().filter_map(|grouped| grouped.group).collect::<Vec<_>>()
+        }).unwrap_or_default()
+    };
+
+    let toasts = Toasts::use_toasts();
+    let remove_tag_cb = move |tag_id: &TagId, pending: PendingDeletion| {
         tags.update(|tags| {
             // PANIC: unwraps are fine, because this action is passed to a component, that is
             //        rendered only after tags have loaded.
-            let tags = tags.as_mut().unwrap().as_mut().unwrap();
-            // PANIC: tags are rendered from the vec, from which we're removing an item.
-            let idx = tags
-                .iter()
-                .position(|tag| &tag.id == tag_id)
-                .unwrap();
-            tags.remove(idx);
-        })
+            GroupedTags::remove(tags.as_mut().unwrap().as_mut().unwrap(), tag_id);
+        });
+        toasts.show_undo("Тег видалено".to_string(), "Не вдалося видалити тег", pending.id, move || tags.refetch());
+    };
+
+    let remove_group_cb = move |group_id: &TagGroupId, pending: PendingDeletion| {
+        tags.update(|tags| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after tags have loaded.
+            // The group might have been already removed by the live updates
+            LiveEvent::TagGroupRemoved(*group_id).apply_to_tags(tags.as_mut().unwrap().as_mut().unwrap());
+        });
+        toasts.show_undo("Групу видалено".to_string(), "Не вдалося видалити групу", pending.id, move || tags.refetch());
     };
 
-    let loaded_tags_toggles = move || {
+    let optimistic = Optimistic::new(tags);
+
+    let update_tag_action = create_action(move |input: &Tag| {
+        let input = input.clone();
+        let previous_tag = untrack(|| tags.with(|tags| {
+            tags.as_ref()
+                .and_then(|tags| tags.as_ref().ok())
+                .and_then(|tags| GroupedTags::all_tags(tags).find(|tag| tag.id == input.id).cloned())
+        }));
+
+        async move {
+            optimistic.run(
+                |tags| LiveEvent::TagUpdated(input.clone()).apply_to_tags(tags),
+                |tags| {
+                    if let Some(previous_tag) = previous_tag {
+                        LiveEvent::TagUpdated(previous_tag).apply_to_tags(tags)
+                    }
+                },
+                update_tag(input.id, input.name.clone(), input.group_id, input.color.clone()),
+                "Не вдалося змінити тег",
+            ).await;
+        }
+    });
+
+    let loaded_tag_groups = move || {
         tags().map(|tags| {
             match tags {
-                Ok(tags) => tags.into_iter().map(|tag| {
+                Ok(tags) => tags.into_iter().map(|grouped| {
                     view! {
-                        <TagToggle tag remove_tag_cb />
+                        <TagGroupBlock grouped groups=groups() remove_tag_cb remove_group_cb update_tag_action />
                     }
                 }).collect_view(),
                 Err(_) => view! { Помилка завантаження тегів }.into_view(),
@@ -140,19 +338,24 @@ pub fn TagsBlock(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>) -> impl In
         })
     };
 
-    let add_tag_action = create_action(move |input: &String| {
+    let add_tag_action = create_action(move |input: &(String, Option<TagGroupId>, String)| {
+        let (name, group_id, color) = input.clone();
+        async move {
+            if let Some(new_tag) = optimistic.confirmed(add_tag(name, group_id, color), "Не вдалося додати тег").await {
+                optimistic.update(|tags| LiveEvent::TagAdded(new_tag).apply_to_tags(tags))
+            }
+        }
+    });
+
+    let add_group_action = create_action(move |input: &String| {
         let input = input.clone();
         async move {
-            if let Ok(new_tag) = add_tag(input).await {
-                tags.update(|tags| {
-                    // PANIC: unwraps are fine, because this action is passed to a component, that is
-                    //        rendered only after tags have loaded.
-                    tags.as_mut().unwrap().as_mut().unwrap().push(new_tag)
-                })
+            if let Some(new_group) = optimistic.confirmed(add_tag_group(input), "Не вдалося додати групу").await {
+                optimistic.update(|tags| LiveEvent::TagGroupAdded(new_group).apply_to_tags(tags))
             }
         }
     });
-    
+
     let admin_state = use_context::<ReadSignal<AdminState>>()
         .expect("`AdminState` to be added to the context");
 
@@ -161,15 +364,16 @@ pub fn TagsBlock(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>) -> impl In
             fallback=loading
         >
             <div class="flex flex-col gap-1 p-2">
-                {loaded_tags_toggles}
+                {loaded_tag_groups}
                 {
                     move || (!tags_loading() && admin_state().set).then(||
                         view! {
-                            <AddTag add_item_action=add_tag_action />
+                            <AddTag groups=groups() add_item_action=add_tag_action />
+                            <AddTagGroup add_group_action />
                         }
                     )
                 }
             </div>
         </Suspense>
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,179 @@
+use std::{rc::Rc, time::Duration};
+
+use leptos::*;
+use leptos_router::use_location;
+
+use crate::{data::deletions::{PendingDeletionId, UNDO_WINDOW}, server_funcs::deletions::{undo_deletion, finish_deletion}};
+
+/// A toast is closed by itself after this long.
+const TOAST_DURATION: Duration = Duration::from_secs(6);
+
+/// A removal, which the toast offers to undo.
+#[derive(Clone)]
+struct Undo {
+    deletion_id: PendingDeletionId,
+    /// Shown if the removal fails, when it's carried out.
+    failure: String,
+    /// Shows the entity again, if the removal is undone or fails.
+    restore: Rc<dyn Fn()>,
+}
+
+#[derive(Clone)]
+struct Toast {
+    id: usize,
+    message: String,
+    undo: Option<Undo>,
+}
+
+/// Notifications shown over any page, e.g. about the failed changes.
+#[derive(Clone, Copy)]
+pub struct Toasts {
+    toasts: RwSignal<Vec<Toast>>,
+    next_id: StoredValue<usize>,
+}
+
+impl Toasts {
+    pub fn provide() {
+        provide_context(Toasts {
+            toasts: create_rw_signal(vec![]),
+            next_id: store_value(0),
+        });
+    }
+
+    pub fn use_toasts() -> Toasts {
+        use_context::<Toasts>()
+            .expect("`Toasts` to be added to the context")
+    }
+
+    fn push(&self, message: String, undo: Option<Undo>, duration: Duration) {
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id + 1);
+        self.toasts.update(|toasts| toasts.push(Toast { id, message, undo }));
+
+        let toasts = *self;
+        set_timeout(move || toasts.dismiss(id), duration);
+    }
+
+    pub fn show(&self, message: String) {
+        self.push(message, None, TOAST_DURATION);
+    }
+
+    /// Shows a failed server call, with the server's reason if it has one.
+    pub fn show_error(&self, err: ServerFnError, failure: &str) {
+        match err {
+            ServerFnError::ServerError(message) => self.show(format!("{failure}: {message}")),
+            _ => self.show(failure.to_string()),
+        }
+    }
+
+    /// Offers to undo a removal, which is carried out once the toast is gone.
+    /// `restore` shows the entity again, if the removal is undone or fails.
+    pub fn show_undo(&self, message: String, failure: &str, deletion_id: PendingDeletionId, restore: impl Fn() + 'static) {
+        let undo = Undo {
+            deletion_id,
+            failure: failure.to_string(),
+            restore: Rc::new(restore),
+        };
+        self.push(message, Some(undo), UNDO_WINDOW);
+    }
+
+    fn close(&self, id: usize) -> Option<Toast> {
+        let mut closed = None;
+        self.toasts.update(|toasts| {
+            if let Some(idx) = toasts.iter().position(|toast| toast.id == id) {
+                closed = Some(toasts.remove(idx));
+            }
+        });
+        closed
+    }
+
+    /// Closes the toast, a removal it offered to undo is carried out.
+    fn dismiss(&self, id: usize) {
+        if let Some(Toast { undo: Some(undo), .. }) = self.close(id) {
+            self.finish(undo, true);
+        }
+    }
+
+    fn undo(&self, id: usize) {
+        let Some(Toast { undo: Some(undo), .. }) = self.close(id) else {
+            return;
+        };
+
+        let toasts = *self;
+        spawn_local(async move {
+            match undo_deletion(undo.deletion_id).await {
+                Ok(()) => (undo.restore)(),
+                Err(err) => toasts.show_error(err, "Не вдалося скасувати видалення"),
+            }
+        });
+    }
+
+    fn finish(&self, undo: Undo, restore_on_failure: bool) {
+        let toasts = *self;
+        spawn_local(async move {
+            if let Err(err) = finish_deletion(undo.deletion_id).await {
+                if restore_on_failure {
+                    (undo.restore)();
+                }
+                toasts.show_error(err, &undo.failure);
+            }
+        });
+    }
+
+    /// Carries out the removals, which can still be undone, as the page they were made on is left.
+    fn finish_removals(&self) {
+        let mut finished = vec![];
+        self.toasts.update(|toasts| toasts.retain(|toast| match &toast.undo {
+            Some(undo) => {
+                finished.push(undo.clone());
+                false
+            },
+            None => true,
+        }));
+
+        // The page, which showed the entities, is gone, so there is nothing to restore
+        for undo in finished {
+            self.finish(undo, false);
+        }
+    }
+}
+
+#[component]
+pub fn ToastsView() -> impl IntoView {
+    let toasts = Toasts::use_toasts();
+    let location = use_location();
+
+    create_effect(move |previous_path: Option<String>| {
+        let path = location.pathname.get();
+        if previous_path.is_some_and(|previous_path| previous_path != path) {
+            untrack(|| toasts.finish_removals());
+        }
+        path
+    });
+
+    let toasts_view = move || {
+        toasts.toasts.get().into_iter().map(|toast| {
+            let id = toast.id;
+            let (color, undo_button) = match toast.undo {
+                Some(_) => ("bg-gray-800", Some(view! {
+                    <button class="font-bold underline" on:click=move |_| toasts.undo(id)>"Скасувати"</button>
+                })),
+                None => ("bg-red-700", None),
+            };
+
+            view! {
+                <div class=format!("flex flex-row items-center gap-2 p-2 {color} text-white rounded-xl shadow-lg")>
+                    <div>{toast.message}</div>
+                    {undo_button}
+                    <button on:click=move |_| toasts.dismiss(id)>"✕"</button>
+                </div>
+            }
+        }).collect_view()
+    };
+
+    view! {
+        <div class="fixed bottom-4 right-4 z-50 flex flex-col gap-2">
+            {toasts_view}
+        </div>
+    }
+}
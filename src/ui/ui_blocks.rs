@@ -1,14 +1,33 @@
-use leptos::{component, WriteSignal, IntoView, view, create_resource, Resource, ServerFnError};
+use leptos::{component, ReadSignal, WriteSignal, IntoView, view, create_resource, Resource, ServerFnError, use_context, SignalUpdate};
+use leptos_router::A;
 
-use crate::{ui::{tags::TagsBlock, items::Items}, server_funcs::items::get_tags, data::item::Tag};
+use crate::{ui::{tags::TagsBlock, items::Items, categories::CategoryBreadcrumbs, attributes::AttributesBlock, locations::LocationsBlock, saved_searches::SavedSearchesBlock}, server_funcs::{items::get_tags, attributes::get_attributes, locations::get_locations}, data::{item::GroupedTags, attributes::CategoryAttribute, locations::Location, live::LiveEvent}};
 
-use super::{state::AdminState, admin_changer::AdminChanger};
+use super::{state::AdminState, admin_changer::AdminChanger, live::LiveEvents};
 
 #[component]
-pub fn TopBlock(admin_state_setter: WriteSignal<AdminState>) -> impl IntoView {
+pub fn TopBlock() -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+    let admin_state_setter = use_context::<WriteSignal<AdminState>>()
+        .expect("`AdminState` setter to be added to the context");
+
     view! {
         <div class="grid gap-4 grid-cols-3">
-            <div></div>
+            <nav class="m-auto flex flex-row gap-4 text-xl">
+                <A href="/" class="underline">"Головна"</A>
+                <A href="/dashboard" class="underline">"Огляд"</A>
+                <A href="/purchasing" class="underline">"Закупівлі"</A>
+                <A href="/pos" class="underline">"Каса"</A>
+                <A href="/stock-takes" class="underline">"Облік"</A>
+                {
+                    move || admin_state().set.then(|| view! {
+                        <A href="/api-tokens" class="underline">"Токени"</A>
+                        <A href="/webhooks" class="underline">"Вебхуки"</A>
+                        <A href="/duplicates" class="underline">"Дублікати"</A>
+                    })
+                }
+            </nav>
 
             <div class="mx-auto max-w-max bg-slate-200 rounded-xl shadow-lg my-3">
                 <h1 class="text-center text-4xl py-3">База даних магазину</h1>
@@ -24,17 +43,41 @@ pub fn TopBlock(admin_state_setter: WriteSignal<AdminState>) -> impl IntoView {
 #[component]
 pub fn MainBlock() -> impl IntoView {
     let tags = create_resource(|| (), |_| get_tags());
+    let attributes = create_resource(|| (), |_| get_attributes());
+    let locations = create_resource(|| (), |_| get_locations());
+
+    LiveEvents::on_event(move |event| match event {
+        LiveEvent::Missed => tags.refetch(),
+        event => tags.update(|tags| {
+            if let Some(Ok(tags)) = tags {
+                event.apply_to_tags(tags);
+            }
+        }),
+    });
+
     view! {
         <div class="flex flex-row">
-            <LeftBlock tags />
-            <Items tags />
+            <LeftBlock tags attributes locations />
+            <div class="flex flex-col w-full">
+                <CategoryBreadcrumbs />
+                <Items tags attributes locations />
+            </div>
         </div>
     }
 }
 
 #[component]
-pub fn LeftBlock(tags: Resource<(), Result<Vec<Tag>, ServerFnError>>) -> impl IntoView {
+pub fn LeftBlock(
+    tags: Resource<(), Result<Vec<GroupedTags>, ServerFnError>>,
+    attributes: Resource<(), Result<Vec<CategoryAttribute>, ServerFnError>>,
+    locations: Resource<(), Result<Vec<Location>, ServerFnError>>,
+) -> impl IntoView {
     view! {
-        <TagsBlock tags />
+        <div class="flex flex-col">
+            <TagsBlock tags />
+            <SavedSearchesBlock />
+            <LocationsBlock locations />
+            <AttributesBlock attributes />
+        </div>
     }
 }
\ No newline at end of file
@@ -0,0 +1,117 @@
+use leptos::*;
+
+use crate::{data::{item::{Item, ItemVariant, ItemVariantId}, attributes::{AttributeKind, CategoryAttribute}, variants::VariantAxis}, ui::state::AdminState};
+
+/// Chooses the variant of a new object, if the item has variants.
+#[component]
+pub fn VariantSelect<F>(variants: Vec<ItemVariant>, on_select: F) -> impl IntoView
+where
+    F: Fn(Option<ItemVariantId>) + 'static
+{
+    (!variants.is_empty()).then(|| view! {
+        <select
+            class="rounded-lg p-1 border-solid border-slate-400 border"
+            on:change=move |ev| on_select(event_target_value(&ev).parse().ok())
+        >
+            <option value="">"Без варіанту"</option>
+            {
+                variants.into_iter().map(|variant| view! {
+                    <option value=variant.id.to_string()>{variant.name}</option>
+                }).collect_view()
+            }
+        </select>
+    })
+}
+
+/// Objects in stock of every variant, which can be removed while it has none.
+#[component]
+pub fn VariantStock(item: Item, remove_variant_action: Action<ItemVariantId, ()>) -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let stock = item.variant_stock().into_iter().map(|(variant, count)| {
+        let name = variant.map(|variant| variant.name.clone()).unwrap_or("Без варіанту".to_string());
+        let variant_id = variant.map(|variant| variant.id);
+
+        view! {
+            <div class="flex flex-row gap-1 bg-slate-200 rounded-lg px-1">
+                {format!("{name}: {count}")}
+                {
+                    move || variant_id.filter(|_| admin_state().set && count == 0).map(|variant_id| view! {
+                        <button class="text-red-700" on:click=move |_| remove_variant_action.dispatch(variant_id)>"✕"</button>
+                    })
+                }
+            </div>
+        }
+    }).collect_view();
+
+    view! {
+        <div class="flex flex-row flex-wrap gap-2">{stock}</div>
+    }
+}
+
+/// Makes variants from the combinations of the chosen options of the category's choice attributes.
+#[component]
+pub fn VariantMatrix(attributes: Vec<CategoryAttribute>, add_variants_action: Action<Vec<VariantAxis>, ()>) -> impl IntoView {
+    let choices = attributes.into_iter()
+        .filter(|attribute| attribute.kind == AttributeKind::Enum && !attribute.options.is_empty())
+        .collect::<Vec<_>>();
+    let has_choices = !choices.is_empty();
+    let axes = create_rw_signal(
+        choices.iter()
+            .map(|attribute| VariantAxis { attribute_id: attribute.id, options: vec![] })
+            .collect::<Vec<_>>()
+    );
+
+    let variants_count = move || axes.with(|axes| VariantAxis::combinations(axes).len());
+
+    let axes_view = choices.into_iter().enumerate().map(|(axis_idx, attribute)| {
+        let options = attribute.options.into_iter().map(|option| {
+            let is_chosen = {
+                let option = option.clone();
+                move || axes.with(|axes| axes[axis_idx].options.contains(&option))
+            };
+            let toggle = {
+                let option = option.clone();
+                move |ev| {
+                    let checked = event_target_checked(&ev);
+                    axes.update(|axes| {
+                        let options = &mut axes[axis_idx].options;
+                        options.retain(|chosen| chosen != &option);
+                        if checked {
+                            options.push(option.clone());
+                        }
+                    })
+                }
+            };
+
+            view! {
+                <label class="flex flex-row items-center gap-1">
+                    <input type="checkbox" prop:checked=is_chosen on:change=toggle />
+                    {option}
+                </label>
+            }
+        }).collect_view();
+
+        view! {
+            <div class="flex flex-row flex-wrap gap-2">
+                <div class="font-bold">{attribute.name}":"</div>
+                {options}
+            </div>
+        }
+    }).collect_view();
+
+    // Variants are made only from the choice attributes
+    has_choices.then(|| view! {
+        <div class="flex flex-col gap-1 border-solid border-black border p-1">
+            {axes_view}
+            <button
+                class="bg-green-700 disabled:text-slate-400 rounded-xl px-2"
+                disabled=move || variants_count() == 0 || add_variants_action.pending()()
+                on:click=move |_| add_variants_action.dispatch(axes.get())
+            >
+                {move || format!("Створити варіанти ({})", variants_count())}
+            </button>
+        </div>
+    })
+}
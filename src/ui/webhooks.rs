@@ -0,0 +1,267 @@
+use leptos::*;
+
+use crate::{server_funcs::webhooks::{get_webhooks, add_webhook, remove_webhook, get_webhook_deliveries, test_webhook}, data::{webhooks::{Webhook, WebhookId, WebhookEventType, WebhookDelivery, WebhookDeliveryStatus}, deletions::PendingDeletion}, ui::{state::AdminState, toasts::Toasts, keybindings::on_enter}};
+
+#[component]
+fn DeliveryRow(delivery: WebhookDelivery) -> impl IntoView {
+    let status = match delivery.status {
+        WebhookDeliveryStatus::Pending if delivery.attempts == 0 => "Очікує".to_string(),
+        WebhookDeliveryStatus::Pending => format!("Повтор о {}", delivery.next_attempt_at.format("%H:%M:%S")),
+        WebhookDeliveryStatus::Delivered => "Доставлено".to_string(),
+        WebhookDeliveryStatus::Failed => "Не доставлено".to_string(),
+    };
+    let outcome = delivery.last_error
+        .or(delivery.last_status_code.map(|code| code.to_string()))
+        .unwrap_or_default();
+
+    view! {
+        <div class="flex flex-row gap-2 text-sm">
+            <div>{delivery.created_at.format("%d.%m.%Y %H:%M:%S").to_string()}</div>
+            <div class="font-mono">{delivery.event_type.as_str()}</div>
+            <div
+                class=("text-green-700", delivery.status == WebhookDeliveryStatus::Delivered)
+                class=("text-red-700", delivery.status == WebhookDeliveryStatus::Failed)
+            >
+                {status}
+            </div>
+            <div>{format!("Спроб: {}", delivery.attempts)}</div>
+            <div class="truncate">{outcome}</div>
+        </div>
+    }
+}
+
+/// Latest deliveries of a webhook, loaded when they are shown and reloaded after a test.
+#[component]
+fn DeliveryLog(webhook_id: WebhookId, tested: Signal<usize>) -> impl IntoView {
+    let deliveries = create_resource(tested, move |_| get_webhook_deliveries(webhook_id));
+
+    let loaded_deliveries = move || {
+        deliveries().map(|deliveries| {
+            match deliveries {
+                Ok(deliveries) if deliveries.is_empty() => view! { "Доставок ще не було" }.into_view(),
+                Ok(deliveries) => deliveries.into_iter().map(|delivery| view! {
+                    <DeliveryRow delivery />
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження журналу }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <Transition fallback=move || view! { Завантаження журналу... }>
+            <div class="flex flex-col">
+                {loaded_deliveries}
+            </div>
+        </Transition>
+    }
+}
+
+#[component]
+fn WebhookRow<F>(webhook: Webhook, remove_webhook_cb: F) -> impl IntoView
+where
+    F: Fn(&WebhookId, PendingDeletion) + Copy + 'static
+{
+    let webhook_id = webhook.id;
+    let (log_shown, log_shown_set) = create_signal(false);
+
+    let remove_webhook_action = create_action(move |_| {
+        async move {
+            let pending = remove_webhook(webhook_id).await?;
+            remove_webhook_cb(&webhook_id, pending);
+            Ok::<_, ServerFnError>(())
+        }
+    });
+
+    let test_webhook_action = create_action(move |_| test_webhook(webhook_id));
+    let tested = Signal::derive(move || test_webhook_action.version()());
+
+    let test_outcome = move || {
+        test_webhook_action.value()().map(|delivery| match delivery {
+            Ok(delivery) if delivery.status == WebhookDeliveryStatus::Delivered => "Перевірку доставлено".to_string(),
+            Ok(delivery) => format!("Перевірку не доставлено: {}", delivery.last_error.unwrap_or_default()),
+            Err(_) => "Не вдалося надіслати перевірку".to_string(),
+        })
+    };
+
+    let event_types = webhook.event_types.iter()
+        .map(|event_type| event_type.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    view! {
+        <div class="flex flex-col gap-1 p-2 bg-slate-200 rounded-xl">
+            <div class="flex flex-row items-center gap-2">
+                <div class="font-bold">{webhook.url}</div>
+                <div class="text-sm font-mono">{event_types}</div>
+                <button
+                    class="ml-auto bg-slate-400 rounded-xl px-2"
+                    on:click=move |_| log_shown_set(!log_shown())
+                >
+                    "Журнал"
+                </button>
+                <button
+                    class="bg-slate-400 disabled:text-slate-200 rounded-xl px-2"
+                    on:click=move |_| {
+                        log_shown_set(true);
+                        test_webhook_action.dispatch(())
+                    }
+                    disabled=test_webhook_action.pending()
+                >
+                    "Перевірити"
+                </button>
+                <button
+                    on:click=move |_| {
+                        remove_webhook_action.dispatch(())
+                    }
+                    class="bg-red-700 disabled:text-slate-400 rounded-xl px-2"
+                    disabled=remove_webhook_action.pending()
+                >
+                    "Видалити"
+                </button>
+            </div>
+            <div class="text-sm">{test_outcome}</div>
+            {
+                move || log_shown().then(|| view! {
+                    <DeliveryLog webhook_id tested />
+                })
+            }
+        </div>
+    }
+}
+
+#[component]
+fn AddWebhook(add_webhook_action: Action<(String, Vec<WebhookEventType>, String), Result<Webhook, ServerFnError>>) -> impl IntoView {
+    let (url, url_set) = create_signal(String::new());
+    let (secret, secret_set) = create_signal(String::new());
+    let event_types = create_rw_signal(WebhookEventType::SUBSCRIBABLE.to_vec());
+
+    let can_add = move || !url().trim().is_empty() && !secret().is_empty() && !event_types().is_empty();
+    let submit = move || {
+        if can_add() {
+            add_webhook_action.dispatch((url(), event_types(), secret()))
+        }
+    };
+
+    let add_error = move || {
+        add_webhook_action.value()().and_then(|added| added.err()).map(|_| view! {
+            <div class="text-red-700">"Не вдалося додати, перевірте адресу"</div>
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-1">
+            <div class="flex flex-row items-center gap-1">
+                <input
+                    class="rounded-lg p-1 border-solid border-slate-400 border"
+                    type="url"
+                    placeholder="https://..."
+                    required
+                    on:input=move |ev| url_set(event_target_value(&ev))
+                    on:keydown=on_enter(submit)
+                    prop:value=url
+                />
+                <input
+                    class="rounded-lg p-1 border-solid border-slate-400 border"
+                    type="text"
+                    placeholder="Секрет підпису"
+                    required
+                    on:input=move |ev| secret_set(event_target_value(&ev))
+                    on:keydown=on_enter(submit)
+                    prop:value=secret
+                />
+                <button
+                    class="bg-green-700 rounded-xl px-2 disabled:text-slate-400"
+                    on:click=move |_| submit()
+                    disabled=move || !can_add()
+                >
+                    "Додати"
+                </button>
+            </div>
+            <div class="flex flex-row flex-wrap gap-2">
+                {
+                    WebhookEventType::SUBSCRIBABLE.into_iter().map(|event_type| view! {
+                        <label class="flex flex-row items-center gap-1 font-mono text-sm">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || event_types().contains(&event_type)
+                                on:change=move |ev| {
+                                    let checked = event_target_checked(&ev);
+                                    event_types.update(|event_types| {
+                                        event_types.retain(|chosen| chosen != &event_type);
+                                        if checked {
+                                            event_types.push(event_type);
+                                        }
+                                    })
+                                }
+                            />
+                            {event_type.as_str()}
+                        </label>
+                    }).collect_view()
+                }
+            </div>
+            {add_error}
+        </div>
+    }
+}
+
+/// Subscriptions of the external systems to the changes of the catalogue.
+#[component]
+pub fn WebhooksBlock() -> impl IntoView {
+    let admin_state = use_context::<ReadSignal<AdminState>>()
+        .expect("`AdminState` to be added to the context");
+
+    let webhooks = create_resource(|| (), |_| get_webhooks());
+
+    let toasts = Toasts::use_toasts();
+    let remove_webhook_cb = move |webhook_id: &WebhookId, pending: PendingDeletion| {
+        webhooks.update(|webhooks| {
+            // PANIC: unwraps are fine, because this action is passed to a component, that is
+            //        rendered only after webhooks have loaded.
+            webhooks.as_mut().unwrap().as_mut().unwrap().retain(|webhook| &webhook.id != webhook_id);
+        });
+        toasts.show_undo("Вебхук видалено".to_string(), "Не вдалося видалити вебхук", pending.id, move || webhooks.refetch());
+    };
+
+    let add_webhook_action = create_action(move |input: &(String, Vec<WebhookEventType>, String)| {
+        let (url, event_types, secret) = input.clone();
+        async move {
+            let webhook = add_webhook(url, event_types, secret).await?;
+            webhooks.update(|webhooks| {
+                // PANIC: unwraps are fine, because this action is passed to a component, that is
+                //        rendered only after webhooks have loaded.
+                webhooks.as_mut().unwrap().as_mut().unwrap().push(webhook.clone())
+            });
+            Ok(webhook)
+        }
+    });
+
+    let loaded_webhooks = move || {
+        webhooks().map(|webhooks| {
+            match webhooks {
+                Ok(webhooks) if webhooks.is_empty() => view! { "Вебхуків немає" }.into_view(),
+                Ok(webhooks) => webhooks.into_iter().map(|webhook| view! {
+                    <WebhookRow webhook remove_webhook_cb />
+                }).collect_view(),
+                Err(_) => view! { Помилка завантаження вебхуків }.into_view(),
+            }
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-2">
+            <h2 class="text-2xl">"Вебхуки"</h2>
+            {
+                move || if admin_state().set {
+                    view! {
+                        <AddWebhook add_webhook_action />
+                        <Suspense fallback=move || view! { Завантаження вебхуків... }>
+                            {loaded_webhooks}
+                        </Suspense>
+                    }.into_view()
+                } else {
+                    view! { "Вебхуками керує лише адміністратор" }.into_view()
+                }
+            }
+        </div>
+    }
+}
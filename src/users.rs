@@ -0,0 +1,46 @@
+use std::{convert::Infallible, future::{Ready, ready}};
+
+use actix_web::{FromRequest, cookie::Cookie, http::header::{HeaderValue, SET_COOKIE}};
+use uuid::Uuid;
+
+use crate::data::saved_searches::UserId;
+
+/// Cookie, which identifies the anonymous user of a browser.
+const USER_COOKIE: &str = "web_db_user";
+
+/// User of the request, `None` if the browser wasn't issued an id yet.
+pub struct CurrentUser(pub Option<UserId>);
+
+impl CurrentUser {
+    /// Id of the user, a new one is issued with a cookie in the response, if there is none yet.
+    ///
+    /// Must be called during a server function or rendering, where the response options are provided.
+    pub fn get_or_issue(self) -> UserId {
+        if let Some(user_id) = self.0 {
+            return user_id;
+        }
+
+        let user_id = UserId(Uuid::new_v4());
+        let cookie = Cookie::build(USER_COOKIE, user_id.to_string())
+            .path("/")
+            .http_only(true)
+            .permanent()
+            .finish();
+
+        let response = leptos::expect_context::<leptos_actix::ResponseOptions>();
+        // PANIC: cookie of a uuid is always a valid header value
+        response.append_header(SET_COOKIE, HeaderValue::from_str(&cookie.to_string()).unwrap());
+
+        user_id
+    }
+}
+
+impl FromRequest for CurrentUser {
+    type Error = Infallible;
+    type Future = Ready<Result<CurrentUser, Infallible>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let user_id = req.cookie(USER_COOKIE).and_then(|cookie| cookie.value().parse().ok());
+        ready(Ok(CurrentUser(user_id)))
+    }
+}
@@ -0,0 +1,258 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::Utc;
+use futures::{stream, StreamExt};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{data::webhooks::{WebhookId, WebhookDelivery, WebhookDeliveryStatus}, db::{Repository, DbError, webhooks::{WebhookDB, DueDelivery}}};
+
+/// How often the queue is checked for the due deliveries.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const BATCH_SIZE: i64 = 20;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// A taken delivery isn't taken again for this long. The batch is delivered concurrently,
+/// so every attempt of it ends within [`REQUEST_TIMEOUT`], well before the lease runs out.
+const LEASE: Duration = Duration::from_secs(60);
+/// A delivery fails for good after this many attempts.
+const MAX_ATTEMPTS: i32 = 8;
+/// Delay before the first retry, doubled with every next one.
+const FIRST_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+
+/// Hex HMAC-SHA256 of `{timestamp}.{body}`, so that a receiver can check the body and its freshness.
+pub fn sign(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    // PANIC: HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent("web_db-webhooks")
+        .build()
+        .expect("Could not build the webhook client")
+}
+
+/// Delay before the next attempt, after `attempts` attempts were made.
+fn backoff(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    FIRST_BACKOFF.saturating_mul(2u32.pow(exponent)).min(MAX_BACKOFF)
+}
+
+/// Status of a delivery after its `attempts`-th attempt, which has succeeded or `failed`.
+fn status_after(attempts: i32, failed: bool) -> WebhookDeliveryStatus {
+    if !failed {
+        WebhookDeliveryStatus::Delivered
+    } else if attempts >= MAX_ATTEMPTS {
+        WebhookDeliveryStatus::Failed
+    } else {
+        WebhookDeliveryStatus::Pending
+    }
+}
+
+/// Posts the event, returning the status code of the response, or why there is none.
+async fn post(client: &reqwest::Client, delivery: &DueDelivery) -> (Option<u16>, Option<String>) {
+    let mut body = delivery.payload.clone();
+    if let Some(body) = body.as_object_mut() {
+        body.insert("id".to_string(), delivery.id.0.to_string().into());
+        body.insert("created_at".to_string(), delivery.created_at.to_rfc3339().into());
+    }
+    // PANIC: a JSON value always serializes
+    let body = serde_json::to_vec(&body).unwrap();
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or_default();
+    let response = client.post(&delivery.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Webhook-Id", delivery.id.0.to_string())
+        .header("X-Webhook-Event", delivery.event_type.as_str())
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, format!("sha256={}", sign(&delivery.secret, timestamp, &body)))
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+        Ok(response) => (Some(response.status().as_u16()), Some(format!("Отримано статус {}", response.status()))),
+        Err(error) => (None, Some(error.to_string())),
+    }
+}
+
+/// Makes an attempt of the delivery and records its outcome, scheduling a retry if it failed.
+async fn deliver(db: &Repository, client: &reqwest::Client, delivery: DueDelivery) -> Result<WebhookDelivery, DbError> {
+    let (status_code, error) = post(client, &delivery).await;
+    let attempts = delivery.attempts + 1;
+
+    let status = status_after(attempts, error.is_some());
+    // PANIC: the backoff is at most an hour
+    let next_attempt_at = Utc::now() + chrono::Duration::from_std(backoff(attempts)).unwrap();
+
+    db.record_delivery_attempt(delivery.id, status, status_code.map(i32::from), error.as_deref(), next_attempt_at).await
+}
+
+/// Sends a ping to the webhook right away, returning the outcome.
+pub async fn send_test(db: &Repository, webhook_id: WebhookId) -> Result<WebhookDelivery, DbError> {
+    let delivery = db.add_ping_delivery(webhook_id, LEASE.as_secs_f64()).await?;
+    deliver(db, &client(), delivery).await
+}
+
+/// Makes an attempt of each due delivery, returning how many were taken.
+async fn deliver_due(db: &Repository, client: &reqwest::Client) -> Result<usize, DbError> {
+    let due = db.take_due_deliveries(BATCH_SIZE, LEASE.as_secs_f64()).await?;
+    let taken = due.len();
+
+    stream::iter(due)
+        .for_each_concurrent(None, |delivery| async move {
+            if let Err(error) = deliver(db, client, delivery).await {
+                log::error!("Could not record a webhook delivery: {error:?}");
+            }
+        })
+        .await;
+
+    Ok(taken)
+}
+
+/// Delivers the queued events for as long as the server runs.
+pub async fn run_delivery_worker(db: Repository) {
+    let client = client();
+    let mut interval = actix_web::rt::time::interval(POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = deliver_due(&db, &client).await {
+            log::error!("Could not take the webhook deliveries: {error:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, sync::{mpsc, Arc, Mutex}, thread};
+
+    use actix_web::{dev::ServerHandle, web, App, HttpRequest, HttpResponse, HttpServer};
+    use sqlx::PgPool;
+
+    use crate::{data::webhooks::WebhookEventType, db::{categories::CategoryDB, fixtures::with_category}};
+
+    use super::*;
+
+    #[test]
+    fn signature_is_hmac_of_timestamp_and_body() {
+        assert_eq!(
+            sign("secret", 1_700_000_000, br#"{"type":"ping"}"#),
+            "5a2a8f7d964e86f8fb6f3a65e439891e4154c53e76bca44002f5200ba270fdf6",
+        );
+        assert_ne!(sign("secret", 1_700_000_001, br#"{"type":"ping"}"#), sign("secret", 1_700_000_000, br#"{"type":"ping"}"#));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        assert_eq!(backoff(1), Duration::from_secs(30));
+        assert_eq!(backoff(2), Duration::from_secs(60));
+        assert_eq!(backoff(5), Duration::from_secs(480));
+        assert_eq!(backoff(8), Duration::from_secs(3600));
+        assert_eq!(backoff(i32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn deliveries_fail_after_the_last_attempt() {
+        assert_eq!(status_after(1, false), WebhookDeliveryStatus::Delivered);
+        assert_eq!(status_after(MAX_ATTEMPTS - 1, true), WebhookDeliveryStatus::Pending);
+        assert_eq!(status_after(MAX_ATTEMPTS, true), WebhookDeliveryStatus::Failed);
+    }
+
+    #[test]
+    fn lease_outlasts_an_attempt() {
+        assert!(REQUEST_TIMEOUT < LEASE);
+    }
+
+    /// Timestamp, signature and body of a request to the stand-in receiver.
+    type Request = (String, String, Vec<u8>);
+
+    /// Requests received by the stand-in receiver, it fails the first one.
+    #[derive(Clone, Default)]
+    struct Received(Arc<Mutex<Vec<Request>>>);
+
+    async fn receive(req: HttpRequest, body: web::Bytes, received: web::Data<Received>) -> HttpResponse {
+        let header = |name| req.headers().get(name).and_then(|value| value.to_str().ok()).unwrap_or_default().to_string();
+        let mut received = received.0.lock().unwrap();
+        received.push((header(TIMESTAMP_HEADER), header(SIGNATURE_HEADER), body.to_vec()));
+
+        match received.len() {
+            1 => HttpResponse::InternalServerError().finish(),
+            _ => HttpResponse::Ok().finish(),
+        }
+    }
+
+    /// Runs the stand-in receiver in a system of its own, as the test's runtime isn't the actix one.
+    fn start_receiver(received: Received) -> (SocketAddr, ServerHandle) {
+        let (started, start) = mpsc::channel();
+
+        thread::spawn(move || {
+            actix_web::rt::System::new().block_on(async move {
+                let server = HttpServer::new(move || App::new().app_data(web::Data::new(received.clone())).default_service(web::to(receive)))
+                    .workers(1)
+                    .bind(("127.0.0.1", 0))
+                    .unwrap();
+                let addr = server.addrs()[0];
+                let server = server.run();
+                started.send((addr, server.handle())).unwrap();
+                server.await.unwrap();
+            });
+        });
+
+        start.recv().unwrap()
+    }
+
+    #[sqlx::test]
+    async fn failed_delivery_is_retried_through_the_queue(pool: PgPool) {
+        let received = Received::default();
+        let (addr, server) = start_receiver(received.clone());
+
+        let (db, category) = with_category(pool.clone()).await;
+        let webhook = db.add_webhook(&format!("http://{addr}/hook"), &[WebhookEventType::CategoryDeleted], "secret").await.unwrap();
+        db.remove_category(category.id, None).await.unwrap();
+        let client = client();
+
+        // The first attempt fails, so the retry waits for the backoff
+        assert_eq!(deliver_due(&db, &client).await.unwrap(), 1);
+        let delivery = db.get_webhook_deliveries(webhook.id, 10).await.unwrap().remove(0);
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.last_status_code, Some(500));
+        assert!(delivery.next_attempt_at > Utc::now() + chrono::Duration::from_std(FIRST_BACKOFF / 2).unwrap());
+        assert_eq!(deliver_due(&db, &client).await.unwrap(), 0);
+
+        sqlx::query("UPDATE webhook_delivery SET next_attempt_at = now()").execute(&pool).await.unwrap();
+
+        assert_eq!(deliver_due(&db, &client).await.unwrap(), 1);
+        let delivery = db.get_webhook_deliveries(webhook.id, 10).await.unwrap().remove(0);
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(delivery.last_status_code, Some(200));
+        assert!(delivery.delivered_at.is_some());
+        assert_eq!(deliver_due(&db, &client).await.unwrap(), 0);
+
+        server.stop(false).await;
+
+        let received = received.0.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        for (timestamp, signature, body) in received.iter() {
+            let timestamp = timestamp.parse().unwrap();
+            assert_eq!(signature, &format!("sha256={}", sign("secret", timestamp, body)));
+
+            let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+            assert_eq!(body["id"], delivery.id.0.to_string());
+            assert_eq!(body["type"], "category.deleted");
+        }
+    }
+}